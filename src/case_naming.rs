@@ -0,0 +1,224 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use crate::handle_error;
+
+// Zips often extract to a folder, or keep inputs and outputs in separate folders, so subfolders are scanned up to this depth
+const MAX_SCAN_DEPTH: usize = 4;
+// Conventions tried when no extensions are given, in order of preference when they find the same number of cases
+const EXTENSION_CONVENTIONS: [(&str, &str); 4] = [("in", "out"), ("in", "ans"), ("in", "sol"), ("in.txt", "out.txt")];
+const PREFIX_CONVENTIONS: [(&str, &str); 1] = [("input", "output")];
+const NUMBERED_CONVENTIONS: [(&str, &str); 2] = [("I", "O"), ("in", "out")];
+// Values of --naming on add, to only try one kind of convention
+pub const NAMING_STYLES: [&str; 3] = ["extensions", "prefixes", "numbered"];
+
+// How the input and output files of a case are named, so they can be paired up
+#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum CaseNaming {
+    // 1.in and 1.out, extensions can have dots like 1.in.txt and 1.out.txt
+    EXTENSIONS(String, String),
+    // input1.txt and output1.txt, the case is named 1
+    PREFIXES(String, String),
+    // Old USACO data like I.1 and O.1, or task.in.1 and task.out.1, the case is named 1
+    NUMBERED(String, String),
+}
+
+impl fmt::Display for CaseNaming {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CaseNaming::EXTENSIONS(input, output) => write!(f, "*.{} and *.{}", input, output),
+            CaseNaming::PREFIXES(input, output) => write!(f, "{}* and {}*", input, output),
+            CaseNaming::NUMBERED(input, output) => write!(f, "{}.N and {}.N", input, output),
+        }
+    }
+}
+
+impl CaseNaming {
+    // The extensions the cases are stored with, cases found by prefix are stored as .in and .out
+    pub fn extensions(&self) -> (String, String) {
+        match self {
+            CaseNaming::EXTENSIONS(input, output) => (input.clone(), output.clone()),
+            CaseNaming::PREFIXES(_, _) | CaseNaming::NUMBERED(_, _) => ("in".to_string(), "out".to_string()),
+        }
+    }
+
+    // The case name if the file is an input file
+    pub fn case_name(&self, file_name: &str) -> Option<String> {
+        let name = match self {
+            CaseNaming::EXTENSIONS(input, _) => file_name.strip_suffix(&format!(".{}", input))?.to_string(),
+            CaseNaming::PREFIXES(input, _) => {
+                let rest = file_name.strip_prefix(input.as_str())?.trim_start_matches(['_', '-']);
+                rest.split('.').next()?.to_string()
+            }
+            CaseNaming::NUMBERED(input, _) => {
+                let (_, number) = split_numbered(file_name, input)?;
+                number.to_string()
+            }
+        };
+        if name.is_empty() {
+            None
+        } else {
+            Some(name)
+        }
+    }
+
+    pub fn output_file_name(&self, input_file_name: &str) -> Option<String> {
+        match self {
+            CaseNaming::EXTENSIONS(input, output) => Some(format!("{}.{}", input_file_name.strip_suffix(&format!(".{}", input))?, output)),
+            CaseNaming::PREFIXES(input, output) => Some(format!("{}{}", output, input_file_name.strip_prefix(input.as_str())?)),
+            CaseNaming::NUMBERED(input, output) => {
+                let (task, number) = split_numbered(input_file_name, input)?;
+                Some(format!("{}{}.{}", task, output, number))
+            }
+        }
+    }
+
+    // For reporting outputs that have no input
+    pub fn is_output_file(&self, file_name: &str) -> bool {
+        match self {
+            CaseNaming::EXTENSIONS(_, output) => file_name.len() > output.len() + 1 && file_name.ends_with(&format!(".{}", output)),
+            CaseNaming::PREFIXES(_, output) => file_name.len() > output.len() && file_name.starts_with(output.as_str()),
+            CaseNaming::NUMBERED(_, output) => split_numbered(file_name, output).is_some(),
+        }
+    }
+
+    // Case name, input file, output file for cases with both files in dir
+    pub fn pair_files(&self, dir: &Path, files: &[PathBuf]) -> Vec<(String, PathBuf, PathBuf)> {
+        let mut pairs = vec![];
+        for file in files {
+            let Some(file_name) = file.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let (Some(case_name), Some(output_file_name)) = (self.case_name(file_name), self.output_file_name(file_name)) else {
+                continue;
+            };
+            let output_path = dir.join(output_file_name);
+            if output_path.is_file() {
+                pairs.push((case_name, file.clone(), output_path));
+            }
+        }
+        pairs
+    }
+
+    // The conventions of a style from NAMING_STYLES, or all of them
+    pub fn conventions(style: Option<&str>) -> Vec<CaseNaming> {
+        let mut conventions = vec![];
+        if style.is_none_or(|style| style == "extensions") {
+            conventions.extend(
+                EXTENSION_CONVENTIONS
+                    .iter()
+                    .map(|(input, output)| CaseNaming::EXTENSIONS(input.to_string(), output.to_string())),
+            );
+        }
+        if style.is_none_or(|style| style == "prefixes") {
+            conventions.extend(
+                PREFIX_CONVENTIONS
+                    .iter()
+                    .map(|(input, output)| CaseNaming::PREFIXES(input.to_string(), output.to_string())),
+            );
+        }
+        if style.is_none_or(|style| style == "numbered") {
+            conventions.extend(
+                NUMBERED_CONVENTIONS
+                    .iter()
+                    .map(|(input, output)| CaseNaming::NUMBERED(input.to_string(), output.to_string())),
+            );
+        }
+        conventions
+    }
+
+    // Picks the convention that pairs up the most files, None if none of them pair any
+    pub fn detect(folder: &Path, style: Option<&str>) -> Option<CaseNaming> {
+        let conventions = CaseNaming::conventions(style);
+        let dirs = find_folders(folder)
+            .into_iter()
+            .map(|dir| {
+                let files = read_files(&dir).unwrap_or_default();
+                (dir, files)
+            })
+            .collect::<Vec<(PathBuf, Vec<PathBuf>)>>();
+        let mut best: Option<(usize, CaseNaming)> = None;
+        for naming in conventions {
+            let count = dirs.iter().map(|(dir, files)| naming.pair_files(dir, files).len()).sum::<usize>();
+            if count > 0 && best.as_ref().is_none_or(|(best_count, _)| count > *best_count) {
+                best = Some((count, naming));
+            }
+        }
+        best.map(|(_, naming)| naming)
+    }
+}
+
+// Splits I.1 into ("", "1") and task.in.1 into ("task.", "1") when input is I or in
+fn split_numbered<'a>(file_name: &'a str, input: &str) -> Option<(&'a str, &'a str)> {
+    let (head, number) = file_name.rsplit_once('.')?;
+    if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let task = head.strip_suffix(input)?;
+    if !task.is_empty() && !task.ends_with('.') {
+        return None;
+    }
+    Some((task, number))
+}
+
+// The folder and its subfolders, hidden folders(Like .git or __MACOSX metadata) are skipped
+pub fn find_folders(folder: &Path) -> Vec<PathBuf> {
+    let mut dirs = vec![folder.to_path_buf()];
+    find_subfolders(folder, 1, &mut dirs);
+    dirs
+}
+
+fn find_subfolders(dir: &Path, depth: usize, dirs: &mut Vec<PathBuf>) {
+    if depth > MAX_SCAN_DEPTH {
+        return;
+    }
+    let Ok(entries) = dir.read_dir() else {
+        return;
+    };
+    let mut subfolders = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect::<Vec<PathBuf>>();
+    subfolders.sort();
+    for subfolder in subfolders {
+        let name = subfolder.file_name().unwrap_or_default().to_string_lossy().to_string();
+        if name.starts_with('.') || name.starts_with("__") {
+            continue;
+        }
+        dirs.push(subfolder.clone());
+        find_subfolders(&subfolder, depth + 1, dirs);
+    }
+}
+
+pub fn read_files(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let files = handle_error!(dir.read_dir(), "Invalid folder, can't read directory");
+    let mut files = files
+        .filter_map(|file| {
+            if file.is_err() {
+                println!("Invalid file in folder: {}", file.as_ref().err().unwrap());
+            }
+
+            file.ok()
+        })
+        .map(|file| file.path())
+        .filter(|path| path.is_file())
+        .collect::<Vec<PathBuf>>();
+    files.sort();
+    Ok(files)
+}
+
+// For errors when no cases are found
+pub fn present_extensions(folder: &Path) -> Vec<String> {
+    let mut extensions = find_folders(folder)
+        .iter()
+        .flat_map(|dir| read_files(dir).unwrap_or_default())
+        .filter_map(|file| file.extension().map(|extension| extension.to_string_lossy().to_string()))
+        .collect::<Vec<String>>();
+    extensions.sort();
+    extensions.dedup();
+    extensions
+}
@@ -1,5 +1,6 @@
-use crate::commands::{add, config, list, remove, rename, run};
-use std::fmt::Debug;
+use crate::commands::{add, add_alternate, config, describe, doctor, duplicate, history, init, list, listen, migrate_storage, note, open, remove, rename, report, restore, run, set_checker, set_config_tag, set_subtasks, statement, tag, template};
+use crate::error::EXIT_CODES_HELP;
+use std::{fmt::Debug, num::NonZeroUsize, path::PathBuf};
 
 #[allow(unused_imports)]
 use clap::{error::ErrorKind, Args, CommandFactory, Parser, Subcommand};
@@ -13,26 +14,73 @@ use clap::{error::ErrorKind, Args, CommandFactory, Parser, Subcommand};
     about = "A simple command line tool that can be used to easily add tests for Competitive Programming problems and run them.\nSupports C, C++, Java, and Python, but Java and Python use the versions installed on your system and C uses the default version.\nJava files name should be the same as the class name"
 )]
 pub struct CliData {
+    #[arg(
+        long,
+        global = true,
+        help = "The folder tests are stored in, used instead of the CP_TESTER_HOME environment variable and data_dir in the config"
+    )]
+    pub data_dir: Option<PathBuf>,
+    #[arg(long, global = true, help = "Print without colors, also done when the NO_COLOR environment variable is set")]
+    pub no_color: bool,
+    #[arg(long, global = true, help = "How many threads read and write test case files at once(Default: the amount of CPU cores, up to 8)")]
+    pub threads: Option<NonZeroUsize>,
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
 
 #[derive(Subcommand, Debug)]
-#[allow(non_camel_case_types)]
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
 pub enum Commands {
-    #[command(about = "Add a test case", arg_required_else_help = true)]
-    ADD(add::AddArgs),
+    #[command(about = "Add a test case", arg_required_else_help = true, visible_alias = "a")]
+    ADD(Box<add::AddArgs>),
+    #[command(about = "Add another accepted output to a case, for problems with more than one correct answer", arg_required_else_help = true)]
+    ADD_ALTERNATE(add_alternate::AddAlternateArgs),
     #[command(about = "Work with the config of the program", arg_required_else_help = true)]
     CONFIG(config::ConfigArgs),
-    #[command(about = "List tests, test cases, or test info")]
+    #[command(about = "Set or show the description of a test", arg_required_else_help = true)]
+    DESCRIBE(describe::DescribeArgs),
+    #[command(about = "Find tests whose directory is missing and directories no test uses")]
+    DOCTOR(doctor::DoctorArgs),
+    #[command(about = "Copy a test under a new name", arg_required_else_help = true)]
+    DUPLICATE(duplicate::DuplicateArgs),
+    #[command(about = "Show results of past runs of a test", arg_required_else_help = true)]
+    HISTORY(history::HistoryArgs),
+    #[command(about = "Create a project config file(.cptester.json) in the current directory so `cp-tester run` needs no arguments here")]
+    INIT(init::InitArgs),
+    #[command(about = "List tests, test cases, or test info", visible_aliases = ["l", "ls"])]
     LIST(list::ListArgs),
-    #[command(about = "Remove a test case", arg_required_else_help = true)]
+    #[command(about = "Add tests sent by the Competitive Companion browser extension")]
+    LISTEN(listen::ListenArgs),
+    #[command(about = "Convert stored tests to compressed(zstd) or plain files")]
+    MIGRATE_STORAGE(migrate_storage::MigrateStorageArgs),
+    #[command(about = "Edit or show the notes of a test", arg_required_else_help = true)]
+    NOTE(note::NoteArgs),
+    #[command(about = "Open the problem page of a test in the browser", arg_required_else_help = true)]
+    OPEN(open::OpenArgs),
+    #[command(about = "Remove a test case", arg_required_else_help = true, visible_alias = "rm")]
     REMOVE(remove::RemoveArgs),
     #[command(about = "Rename a test case", arg_required_else_help = true)]
     RENAME(rename::RenameArgs),
+    #[command(about = "Write a Markdown report of a past run of a test", arg_required_else_help = true)]
+    REPORT(report::ReportArgs),
+    #[command(about = "Extract the cases of a test again from the archive kept when it was added", arg_required_else_help = true)]
+    RESTORE(restore::RestoreArgs),
     #[command(
         about = "Run a test case, supports C, C++, Java, and Python. Java and Python use the versions installed on your system",
-        arg_required_else_help = true
+        visible_alias = "r",
+        after_help = EXIT_CODES_HELP
     )]
     RUN(run::RunArgs),
+    #[command(about = "Set or remove the checker runs of a test use instead of comparing the output", arg_required_else_help = true)]
+    SET_CHECKER(set_checker::SetCheckerArgs),
+    #[command(about = "Set or remove the config tag runs of a test use instead of the default config", arg_required_else_help = true)]
+    SET_CONFIG_TAG(set_config_tag::SetConfigTagArgs),
+    #[command(about = "Set, show, or remove the subtasks a run of a test is scored by", arg_required_else_help = true)]
+    SET_SUBTASKS(set_subtasks::SetSubtasksArgs),
+    #[command(about = "Show the problem statement of a test, downloaded from its problem link", arg_required_else_help = true)]
+    STATEMENT(statement::StatementArgs),
+    #[command(about = "Add or remove tags on a test", arg_required_else_help = true)]
+    TAG(tag::TagArgs),
+    #[command(about = "Create a solution file for a test from a template", arg_required_else_help = true)]
+    TEMPLATE(template::TemplateArgs),
 }
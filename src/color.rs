@@ -0,0 +1,80 @@
+use std::{
+    env,
+    io::{self, IsTerminal},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, OnceLock,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+
+const RED: &str = "31";
+const YELLOW: &str = "33";
+
+// Set with `cp-tester config set-color`, auto only uses colors when the output is a terminal
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorSetting {
+    AUTO,
+    ALWAYS,
+    NEVER,
+}
+
+static NO_COLOR_FLAG: AtomicBool = AtomicBool::new(false);
+// Auto until the config is loaded, so errors from loading it still work
+static SETTING: Mutex<ColorSetting> = Mutex::new(ColorSetting::AUTO);
+static ANSI_SUPPORTED: OnceLock<bool> = OnceLock::new();
+
+// --no-color, set before anything is printed
+pub fn set_no_color_flag(no_color: bool) {
+    NO_COLOR_FLAG.store(no_color, Ordering::Relaxed);
+}
+
+// The setting in the config, set every time it is loaded
+pub fn set_setting(setting: ColorSetting) {
+    if let Ok(mut current) = SETTING.lock() {
+        *current = setting;
+    }
+}
+
+pub fn red(text: &str) -> String {
+    paint(text, RED)
+}
+
+pub fn yellow(text: &str) -> String {
+    paint(text, YELLOW)
+}
+
+fn paint(text: &str, color: &str) -> String {
+    if enabled() {
+        format!("\x1b[{}m{}\x1b[0m", color, text)
+    } else {
+        text.to_string()
+    }
+}
+
+// --no-color and NO_COLOR win over the config, like https://no-color.org asks
+fn enabled() -> bool {
+    if NO_COLOR_FLAG.load(Ordering::Relaxed) || env::var_os("NO_COLOR").is_some_and(|no_color| !no_color.is_empty()) {
+        return false;
+    }
+    let setting = SETTING.lock().map_or(ColorSetting::AUTO, |setting| *setting);
+    let enabled = match setting {
+        ColorSetting::ALWAYS => true,
+        ColorSetting::NEVER => false,
+        ColorSetting::AUTO => io::stdout().is_terminal() && io::stderr().is_terminal(),
+    };
+    enabled && *ANSI_SUPPORTED.get_or_init(enable_ansi)
+}
+
+// Older Windows consoles print escape codes as text unless ANSI processing is turned on, colors are left out if it can't be
+#[cfg(target_os = "windows")]
+fn enable_ansi() -> bool {
+    enable_ansi_support::enable_ansi_support().is_ok()
+}
+#[cfg(not(target_os = "windows"))]
+fn enable_ansi() -> bool {
+    true
+}
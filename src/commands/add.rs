@@ -1,85 +1,275 @@
+use crate::case_naming::{CaseNaming, NAMING_STYLES};
+use crate::color;
+use crate::config::{Config, DEFAULT_INPUT_EXTENSION, DEFAULT_OUTPUT_EXTENSION};
+use crate::contest::Contest;
+use crate::data_dir;
+use crate::http;
+use crate::names;
+use crate::package::{Package, PackageType};
+use crate::json_file;
+use crate::paths;
+use crate::subtasks::Subtask;
 use crate::program_data::ProgramData;
-use crate::test_data::IOType;
+use crate::test_data::{IOType, ScanOptions, Test};
+use crate::error::{Context, Error};
 use crate::{handle_error, handle_option};
 use clap::Args;
+use flate2::read::GzDecoder;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt::Display;
 use std::fs;
-use std::path::PathBuf;
-use tempfile::TempDir;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tabled::{Table, Tabled};
+use tar::Archive;
+use tempfile::{NamedTempFile, TempDir};
 use zip::ZipArchive;
 
+pub const OJUZ_NO_CASES_ERROR: &str =
+    "No usable test cases found for this oj.uz problem, it is probably a grader based problem(Where you implement a function), which can't be tested with cp-tester";
 const ZIP_BYTES: [u8; 4] = [0x50, 0x4b, 0x03, 0x04];
+const GZIP_BYTES: [u8; 2] = [0x1f, 0x8b];
+// Tar files have "ustar" at this offset instead of a signature at the start
+const TAR_MAGIC_OFFSET: usize = 257;
+const TAR_MAGIC: &[u8] = b"ustar";
+const SIGNATURE_BYTES: u64 = 512;
+// Longest entity decode_entities looks for, like &#x1F600;
+const MAX_ENTITY_LENGTH: usize = 10;
+// Other archive formats, only detected to give a better error
+const RAR_BYTES: [u8; 4] = [0x52, 0x61, 0x72, 0x21];
+const SEVEN_ZIP_BYTES: [u8; 4] = [0x37, 0x7a, 0xbc, 0xaf];
 const USACO_LINK_PREFIX: &str = "http://www.usaco.org/index.php?page=viewproblem2&cpid=";
 const CODEFORCES_LINK_PREFIX: &str = "https://codeforces.com/problemset/problem/";
 const CODEFORCES_LINK_ALTERNATE_PREFIX: &str = "https://codeforces.com/contest/";
 const ATCODER_LINK_PREFIX: &str = "https://atcoder.jp/contests/";
+const CSES_LINK_PREFIX: &str = "https://cses.fi/problemset/task/";
+const KATTIS_LINK_PREFIX: &str = "https://open.kattis.com/problems/";
+const OJUZ_LINK_PREFIX: &str = "https://oj.uz/problem/view/";
+const OJUZ_HOST: &str = "https://oj.uz";
+// The judges whose problem pages can be added from a saved page with --html, for --site
+const SITES: [&str; 6] = ["usaco", "codeforces", "atcoder", "cses", "kattis", "oj.uz"];
+// Kattis answer files use .ans instead of .out
+const KATTIS_OUTPUT_EXTENSION: &str = "ans";
 const USACO_RETURN_TO_PROBLEM_BUTTON_REGEX_STR: &str = r#"<button style=\"margin-bottom:6px;\" type=\"button\" onClick=\"window\.location='index\.php\?page=(?<results>[A-Za-z0-9]+)';\">Return to Problem List</button>"#;
 const USACO_TEST_DATA_BUTTON_REGEX_STR: &str =
     r#"<a href='index\.php\?page=viewproblem2&cpid=(?<id>[0-9]+)'>View problem</a>&nbsp \| &nbsp <a href='(?<test_data>[^']*)'>Test data</a> &nbsp"#;
 const PROBLEM_IO_REGEX_STR: &str = r#"INPUT FORMAT \((?<io>[^)]*)\):"#;
-const USACO_STANDARD_IO_STR: &str = "input arrives from the terminal / stdin";
-const USACO_PROBLEM_NAME_REGEX_STR: &str = r#"<h2> (?<description>USACO 20(?<year>\d\d) (?<competition>.+), (?<divison>.+) <\/h2>
-<h2> Problem \d\. (?<name>.+)) <\/h2>"#;
-const USACO_EXAMPLE_PROBLEM_STR: &str = r#"<h4>SAMPLE INPUT:<\/h4>.*?<pre class='in'>\n(?<input>(.|\n)*?)<\/pre>.*?<h4>SAMPLE OUTPUT:<\/h4>.*?<pre class='out'>\n(?<output>(.|\n)*?)<\/pre>"#;
-const ATCODER_NAME_REGEX_STR: &str = r#"<span class="h2">(?<name>((.|\n)*?))<"#;
+// The wording of standard IO has changed over the years, like "input arrives from the terminal / stdin"
+const USACO_STANDARD_IO_WORDS: [&str; 3] = ["stdin", "standard input", "terminal"];
+// Spacing and newlines around the headers have changed over the years, so they are all optional
+const USACO_PROBLEM_NAME_REGEX_STR: &str = r#"<h2>\s*USACO\s+(?<year>\d{4})\s+(?<competition>[^,<]+?)\s*,\s*(?<divison>[^<]+?)\s*<\/h2>\s*<h2>\s*Problem\s+(?<number>\d+)\.\s*(?<name>[^<]+?)\s*<\/h2>"#;
+// Newer problems have more than one of each header, each is followed by the <pre> with the sample
+const USACO_SAMPLE_HEADER_REGEX_STR: &str = r#"(?i)<h\d[^>]*>\s*SAMPLE\s+(?<kind>INPUT|OUTPUT)\b[^<]*</h\d>"#;
+const USACO_SAMPLE_REGEX_STR: &str = r#"(?is)<pre[^>]*>(?<sample>.*?)</pre>"#;
+// The index(A, B, Ex, ...) is optional since older contests have headings without it
+const ATCODER_NAME_REGEX_STR: &str = r#"<span class="h2">\s*(?<heading>(?:(?<index>[A-Za-z0-9]+)\s*[-:]\s+)?[^<]*?)\s*<"#;
+// Like "C - K-th Element", older contests add " | AtCoder" or the contest after it
+const ATCODER_TITLE_REGEX_STR: &str = r#"<title>\s*(?<heading>(?:(?<index>[A-Za-z0-9]+)\s*[-:]\s+)?[^<|]*?)(?:\s+-\s+AtCoder[^<]*|\s*\|[^<]*)?\s*</title>"#;
 const ATCODER_DESCRIPTION_REGEX_STR: &str = r#"<a class="contest-title".*?>(?<contest_info>(.*?))<\/a>"#;
 const CODEFORCES_NAME_REGEX_STR: &str = r#"<div class="title">(?<name>((.|\n)*?))<"#;
+// Both /problemset/problem/1700/A and /contest/1700/problem/A
+const CODEFORCES_PROBLEM_LINK_REGEX_STR: &str = r#"/(?:problemset/problem|contest)/(?<contest>\d+)/(?:problem/)?(?<index>[A-Za-z]\d*)\b"#;
+const CODEFORCES_EXAMPLE_REGEX_STR: &str = r#"(?s)<div class="input">.*?<pre[^>]*>(?<input>.*?)</pre>.*?<div class="output">.*?<pre[^>]*>(?<output>.*?)</pre>"#;
+// Only the English headers, the Japanese statement has the same examples
+const ATCODER_EXAMPLE_REGEX_STR: &str = r#"(?s)<h3>\s*Sample Input \d+\s*</h3>\s*<pre[^>]*>(?<input>.*?)</pre>.*?<h3>\s*Sample Output \d+\s*</h3>\s*<pre[^>]*>(?<output>.*?)</pre>"#;
+const CSES_NAME_REGEX_STR: &str = r#"<title>CSES - (?<name>.*?)<\/title>"#;
+const CSES_EXAMPLE_REGEX_STR: &str = r#"<p>Input:<\/p>\s*<pre>(?<input>(.|\n)*?)<\/pre>\s*<p>Output:<\/p>\s*<pre>(?<output>(.|\n)*?)<\/pre>"#;
+const KATTIS_NAME_REGEX_STR: &str = r#"<h1[^>]*>(?<name>(.|\n)*?)<\/h1>"#;
+const OJUZ_NAME_REGEX_STR: &str = r#"<title>(?<name>(.|\n)*?)<\/title>"#;
+const OJUZ_ATTACHMENT_REGEX_STR: &str = r#"href="(?<link>[^"]*(attachment[^"]*|\.zip))""#;
+const OJUZ_EXAMPLE_REGEX_STR: &str = r#"(?is)<h[1-6][^>]*>[^<]*input[^<]*<\/h[1-6]>\s*<pre[^>]*>(?<input>.*?)<\/pre>\s*<h[1-6][^>]*>[^<]*output[^<]*<\/h[1-6]>\s*<pre[^>]*>(?<output>.*?)<\/pre>"#;
+// Codeforces has "time limit per test</div>2 seconds", AtCoder "Time Limit: 2 sec", CSES "Time limit:</b> 1.00 s"
+const TIME_LIMIT_REGEX_STR: &str = r#"(?i)time limit[^0-9]{0,80}?(?<time_limit>[0-9]+(\.[0-9]+)?)\s*(?<unit>ms|milliseconds?|s|secs?|seconds?)\b"#;
+const MEMORY_LIMIT_REGEX_STR: &str = r#"(?i)memory limit[^0-9]{0,80}?(?<memory_limit>[0-9]+)\s*(?<unit>mb|mib|megabytes?|gb|gib|gigabytes?)\b"#;
+// USACO doesn't show limits on problem pages, these are the limits for C and C++ from its instructions
+const USACO_TIME_LIMIT_MS: u64 = 2000;
+const USACO_MEMORY_LIMIT_MB: u64 = 256;
 const CODEFORCES_DESCRIPTION_REGEX_STR: &str = r#"<a style="color: black" href=".*?">(?<contest_info>(.|\n)*?)<\/a>"#;
 
-#[derive(Args, Debug)]
+#[derive(Args, Debug, Clone)]
 pub struct AddArgs {
     #[command(flatten)]
     input_type: InputType,
 
+    // Not part of InputType since it goes with --input-file instead of replacing it
+    #[arg(long, requires = "input_file", help = "File with the expected output of the case from --input-file")]
+    output_file: Option<PathBuf>,
+
     #[arg(short, long)]
-    #[arg(default_value = "in", requires = "input", help = "Extension of input files, don't use a dot")]
-    pub input_extension: String,
+    #[arg(
+        requires = "input",
+        help = "Extension of input files, don't use a dot. If neither extension is given, common conventions(in/out, in/ans, in/sol, in.txt/out.txt, input*/output*) are tried(Default: the default input extension in the config, else in)"
+    )]
+    input_extension: Option<String>,
 
     #[arg(short, long)]
-    #[arg(default_value = "out", requires = "input", help = "Extension of output files, don't use a dot")]
-    pub output_extension: String,
+    #[arg(requires = "input", help = "Extension of output files, don't use a dot(Default: ans for Kattis, else the default output extension in the config, else out)")]
+    output_extension: Option<String>,
+
+    #[arg(long, requires = "input", value_parser = NAMING_STYLES)]
+    #[arg(
+        help = "Only try one kind of naming convention when pairing up cases: extensions(1.in/1.out), prefixes(input1.txt/output1.txt), or numbered(I.1/O.1 and task.in.1/task.out.1). With it, the input and output extensions are used as the prefixes or the parts before the numbers"
+    )]
+    naming: Option<String>,
 
     #[arg(
         short,
         long,
-        help = "Defaults to filename of link(foo for https://usaco.org/foo.zip), defaults to folder name for --folder, inferred for USACO, Codeforces, and AtCoder links"
+        help = "Defaults to filename of link(foo for https://usaco.org/foo.zip), defaults to folder name for --folder, archive name for --zip, and input file name for --input-file, inferred for USACO, Codeforces, AtCoder, CSES, Kattis, and oj.uz links"
     )]
     #[arg(requires = "input")]
     pub name: Option<String>,
 
     #[arg(long, requires = "input", value_delimiter = ',')]
     #[arg(
-        help = "FYI: this is unnecessary if links are from USACO, Codeforces, or AtCoder.\nInput and output files(Without extension, comma separated), in that order(If you provide only one value, that will be assumed to be the file name for both input and output).\nAssumed to be stdin/stdout unless using usaco link or id, in which case regex will be used to infer it(Is stdin/stdout for Codeforces and AtCoder).\nDoesn't support a file input/output and stdin/stdout for the other one, reach out to me if you need this feature"
+        help = "FYI: this is unnecessary if links are from USACO, Codeforces, AtCoder, CSES, Kattis, or oj.uz.\nInput and output files(Without extension, comma separated), in that order(If you provide only one value, that will be assumed to be the file name for both input and output).\nAssumed to be stdin/stdout unless using usaco link or id, in which case regex will be used to infer it(Is stdin/stdout for Codeforces, AtCoder, CSES, Kattis, and oj.uz).\nUse stdin or stdout for one of them to read a file and print to stdout or the other way around, like --io problem,stdout"
     )]
     pub io: Option<Vec<String>>,
 
     #[arg(long, short, requires = "input")]
     #[arg(
-        help = "Optional. Description of test, will be shown when listing tests (Overrides inference). Inferred for USACO, Codeforces, and AtCoder links"
+        help = "Optional. Description of test, will be shown when listing tests (Overrides inference). Inferred for USACO, Codeforces, AtCoder, CSES, Kattis, and oj.uz links"
     )]
     pub description: Option<String>,
+
+    #[arg(long, requires = "input", value_delimiter = ',')]
+    #[arg(help = "Optional. Tags for the test(Comma separated), can be used to filter tests when listing them")]
+    pub tags: Vec<String>,
+
+    #[arg(
+        long,
+        requires = "input",
+        help = "If a test with the name exists, add the new cases to it instead of failing. The description and problem link are refreshed, tags and history are kept"
+    )]
+    pub update: bool,
+
+    #[arg(long, requires = "update", help = "With --update, replace existing cases that have the same name but different data")]
+    pub overwrite_cases: bool,
+
+    #[arg(
+        long,
+        requires = "input",
+        help = "Download or scan the test and show its name, description, IO, and cases without saving it"
+    )]
+    pub dry_run: bool,
+
+    #[arg(long, requires = "input", help = "Fail instead of warning about empty, unpaired, or identical cases")]
+    pub strict: bool,
+
+    #[arg(
+        long,
+        requires = "input",
+        help = "Add inputs without an output as cases with no expected output, they are run but not judged"
+    )]
+    pub allow_missing_outputs: bool,
+
+    #[arg(long, requires = "input", help = "Add cases bigger than the max case size in the config instead of skipping them")]
+    pub no_size_limit: bool,
+
+    #[arg(long, requires = "link", value_delimiter = ',')]
+    #[arg(help = "Only add these problems(Comma separated letters, like A,C,D) from Codeforces or AtCoder contest links")]
+    problems: Vec<String>,
+
+    #[arg(long, requires = "html", value_parser = SITES)]
+    #[arg(help = "Which judge the page from --html is from, inferred from --page-link if not given")]
+    site: Option<String>,
+
+    #[arg(long, requires = "html")]
+    #[arg(help = "Link of the page from --html, stored with the test for `open` and `statement`(Default: the path of the saved page)")]
+    page_link: Option<String>,
+
+    // Shown instead of the link when adding several tests, set for problems from a contest
+    #[arg(skip)]
+    label: Option<String>,
 }
 
-#[derive(Args, Debug, Serialize, Deserialize)]
+// A row of the table printed after adding several tests
+#[derive(Tabled, Debug)]
+pub struct AddSummary {
+    #[tabled(rename = "Problem")]
+    input: String,
+    #[tabled(rename = "Test Name")]
+    pub test_name: String,
+    #[tabled(rename = "Cases")]
+    cases: String,
+    #[tabled(rename = "Result")]
+    result: String,
+}
+
+impl AddSummary {
+    pub fn new(input: String, result: Result<(String, usize), Error>, dry_run: bool) -> AddSummary {
+        match result {
+            Ok((test_name, case_count)) => AddSummary {
+                input,
+                test_name,
+                cases: case_count.to_string(),
+                result: if dry_run { "Would add" } else { "Added" }.to_string(),
+            },
+            Err(_) => AddSummary {
+                input,
+                test_name: String::new(),
+                cases: String::new(),
+                result: "Failed".to_string(),
+            },
+        }
+    }
+}
+
+#[derive(Args, Debug, Clone, Serialize, Deserialize)]
 #[group(required = true, multiple = false)]
 struct InputType {
-    #[arg(short, long, help = "Supports any link that will download a zip file that extracts to test cases")]
+    #[arg(
+        short,
+        long,
+        value_delimiter = ',',
+        help = "Supports any link that will download a zip or tar.gz file that extracts to test cases. Several links(Comma separated or repeated) add a test for each"
+    )]
     #[arg(group = "input")]
-    link: Option<String>,
+    link: Vec<String>,
 
     #[arg(short, long, help = "Folder containing test cases")]
     #[arg(group = "input")]
     #[arg(value_parser=validate_folder)]
     folder: Option<PathBuf>,
 
+    #[arg(short, long, help = "Zip or tar.gz file on disk that extracts to test cases")]
+    #[arg(group = "input")]
+    #[arg(value_parser=validate_zip)]
+    zip: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "File with the input of a single test case, needs --output-file unless --allow-missing-outputs is given. The case is named 1"
+    )]
+    #[arg(group = "input")]
+    input_file: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "ID of usaco problem, is cpid in the link, and will be used to create a link to the problem page. Several IDs(Comma separated or repeated) add a test for each"
+    )]
+    #[arg(group = "input")]
+    usaco_id: Vec<i32>,
+
+    #[arg(
+        long,
+        help = "A problem page saved from a browser, the name, description, IO, and examples are read from it without downloading anything. Needs --site or --page-link"
+    )]
+    #[arg(group = "input")]
+    #[arg(value_parser=validate_html)]
+    html: Option<PathBuf>,
+
     #[arg(
         long,
-        help = "ID of usaco problem, is cpid in the link, and will be used to create a link to the problem page"
+        help = "A Polygon(problem.xml) or ICPC(problem.yaml) problem package, as a zip or an extracted folder. All tests are added, with sample_ or secret_ before their names"
     )]
     #[arg(group = "input")]
-    usaco_id: Option<i32>,
+    #[arg(value_parser=validate_package)]
+    package: Option<PathBuf>,
 }
 
 fn validate_folder(folder: &str) -> Result<PathBuf, String> {
@@ -99,11 +289,51 @@ fn validate_folder(folder: &str) -> Result<PathBuf, String> {
     Ok(folder)
 }
 
+fn validate_zip(zip: &str) -> Result<PathBuf, String> {
+    let zip = PathBuf::from(zip);
+    if !zip.exists() {
+        return Err(String::from("Zip file does not exist"));
+    }
+    if !zip.is_file() {
+        return Err(String::from("Path is to a folder, not a zip file, use --folder instead"));
+    }
+    Ok(zip)
+}
+
+fn validate_html(html: &str) -> Result<PathBuf, String> {
+    let html = PathBuf::from(html);
+    if !html.is_file() {
+        return Err(String::from("Saved page does not exist or is not a file"));
+    }
+    Ok(html)
+}
+
+fn validate_package(package: &str) -> Result<PathBuf, String> {
+    let package = PathBuf::from(package);
+    if !package.exists() {
+        return Err(String::from("Package does not exist"));
+    }
+    Ok(package)
+}
+
+// Found from the signature, not the extension, since links don't always end in one
+#[derive(Debug, Clone, Copy)]
+#[allow(clippy::upper_case_acronyms)]
+enum ArchiveFormat {
+    ZIP,
+    GZIP,
+    TAR,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum SubmissionType {
     USACO,
     CODEFORCES,
     ATCODER,
+    CSES,
+    KATTIS,
+    OJUZ,
 }
 
 impl Display for SubmissionType {
@@ -112,6 +342,9 @@ impl Display for SubmissionType {
             SubmissionType::USACO => "USACO",
             SubmissionType::CODEFORCES => "Codeforces",
             SubmissionType::ATCODER => "AtCoder",
+            SubmissionType::CSES => "CSES",
+            SubmissionType::KATTIS => "Kattis",
+            SubmissionType::OJUZ => "oj.uz",
         };
         write!(f, "{}", string)
     }
@@ -121,6 +354,9 @@ impl Display for SubmissionType {
 pub struct SubmissionData {
     pub submission_type: SubmissionType,
     pub link: String,
+    // The problem page when it was read from a saved file with --html, the scrapers use it instead of downloading the link
+    #[serde(skip)]
+    page_html: Option<String>,
 }
 
 impl SubmissionData {
@@ -131,6 +367,12 @@ impl SubmissionData {
             Some(SubmissionType::CODEFORCES)
         } else if link.contains(ATCODER_LINK_PREFIX) {
             Some(SubmissionType::ATCODER)
+        } else if link.contains(CSES_LINK_PREFIX) {
+            Some(SubmissionType::CSES)
+        } else if link.contains(KATTIS_LINK_PREFIX) {
+            Some(SubmissionType::KATTIS)
+        } else if link.contains(OJUZ_LINK_PREFIX) {
+            Some(SubmissionType::OJUZ)
         } else {
             None
         };
@@ -141,340 +383,266 @@ impl SubmissionData {
         Some(SubmissionData {
             submission_type,
             link: link.clone(),
+            page_html: None,
         })
     }
 
-    pub fn get_data_link(&self) -> Result<String, String> {
-        match self.submission_type {
-            SubmissionType::USACO => self.usaco_data_link(),
-            _ => unreachable!(),
+    // Parsed with the scrapers of the site, the link is only stored
+    // site is one of SITES, which is also what the lowercase name of each submission type is
+    pub fn from_html(site: &str, link: String, page_html: String) -> Result<SubmissionData, Error> {
+        let submission_type = match site {
+            "usaco" => SubmissionType::USACO,
+            "codeforces" => SubmissionType::CODEFORCES,
+            "atcoder" => SubmissionType::ATCODER,
+            "cses" => SubmissionType::CSES,
+            "kattis" => SubmissionType::KATTIS,
+            "oj.uz" => SubmissionType::OJUZ,
+            _ => return Err(format!("Unknown site \"{}\", it can be {}", site, SITES.join(", ")).into()),
+        };
+        Ok(SubmissionData {
+            submission_type,
+            link,
+            page_html: Some(page_html),
+        })
+    }
+
+    // The saved page if there is one, else the page is downloaded
+    fn problem_page(&self) -> Result<String, Error> {
+        match &self.page_html {
+            Some(page_html) => Ok(page_html.clone()),
+            None => get_link_html(&self.link),
         }
     }
 
-    pub fn get_test_name(&self) -> Result<String, String> {
+    // The page is downloaded and parsed the first time anything on it is needed, later calls use what was parsed then
+    fn scraped(&self) -> Result<ScrapedProblem, Error> {
+        if let Some(scraped) = SCRAPED_PROBLEMS.lock().ok().and_then(|scraped| scraped.get(&self.link).cloned()) {
+            return Ok(scraped);
+        }
+        let page = self.problem_page()?;
+        let scraped = self.scrape(&page);
+        if let Ok(mut scraped_problems) = SCRAPED_PROBLEMS.lock() {
+            scraped_problems.insert(self.link.clone(), scraped.clone());
+        }
+        Ok(scraped)
+    }
+
+    fn scrape(&self, page: &str) -> ScrapedProblem {
         match self.submission_type {
-            SubmissionType::USACO => self.usaco_test_name(),
-            SubmissionType::CODEFORCES => self.codeforces_test_name(),
-            SubmissionType::ATCODER => self.atcoder_test_name(),
+            SubmissionType::USACO => ScrapedProblem {
+                name: usaco_test_name(page),
+                description: usaco_test_description(page),
+                io_file: usaco_io(page),
+                limits: Ok((Some(USACO_TIME_LIMIT_MS), Some(USACO_MEMORY_LIMIT_MB))),
+                examples: usaco_examples(page),
+                data_page: usaco_results_page(page).map(Some),
+            },
+            SubmissionType::CODEFORCES => ScrapedProblem {
+                name: codeforces_test_name(page, &self.link),
+                description: codeforces_test_description(page),
+                io_file: Ok(None),
+                limits: page_limits(page),
+                examples: page_examples(page, CODEFORCES_EXAMPLE_REGEX_STR),
+                data_page: Ok(None),
+            },
+            SubmissionType::ATCODER => ScrapedProblem {
+                name: self.atcoder_test_name(page),
+                description: atcoder_test_description(page, &self.link),
+                io_file: Ok(None),
+                limits: page_limits(page),
+                examples: page_examples(page, ATCODER_EXAMPLE_REGEX_STR),
+                data_page: Ok(None),
+            },
+            SubmissionType::CSES => ScrapedProblem {
+                name: cses_problem_name(page).map(|name| name.replace(" ", "_").to_ascii_lowercase()),
+                description: cses_problem_name(page).map(|name| format!("CSES Problem Set: {} (Examples only)", name)),
+                io_file: Ok(None),
+                limits: page_limits(page),
+                examples: page_examples(page, CSES_EXAMPLE_REGEX_STR),
+                data_page: Ok(None),
+            },
+            // Kattis test data is the samples zip, so only the description is on the page
+            SubmissionType::KATTIS => ScrapedProblem {
+                name: self.kattis_problem_id(),
+                description: kattis_test_description(page),
+                io_file: Ok(None),
+                limits: Ok((None, None)),
+                examples: Ok(vec![]),
+                data_page: Ok(None),
+            },
+            // Attachments are downloads, so a saved page only gives the examples
+            SubmissionType::OJUZ => ScrapedProblem {
+                name: self.ojuz_test_name(),
+                description: ojuz_test_description(page),
+                io_file: Ok(None),
+                limits: Ok((None, None)),
+                examples: page_examples(page, OJUZ_EXAMPLE_REGEX_STR),
+                data_page: ojuz_attachment(page).map(|attachment| attachment.filter(|_| self.page_html.is_none())),
+            },
         }
     }
 
-    pub fn get_test_description(&self) -> Result<String, String> {
+    pub fn get_data_link(&self) -> Result<String, Error> {
         match self.submission_type {
-            SubmissionType::USACO => self.usaco_test_description(),
-            SubmissionType::CODEFORCES => self.codeforces_test_description(),
-            SubmissionType::ATCODER => self.atcoder_test_description(),
+            SubmissionType::USACO => self.usaco_data_link(),
+            SubmissionType::KATTIS => self.kattis_data_link(),
+            _ => Err(Error::OTHER(format!("{} has no data link, the examples are read from the problem page", self.submission_type))),
         }
     }
 
-    pub fn get_data(&self) -> Result<PathBuf, String> {
+    pub fn get_test_name(&self) -> Result<String, Error> {
+        Ok(self.scraped()?.name?)
+    }
+
+    // Problem titles can have entities like &amp; or &#39;, they are decoded for every judge
+    pub fn get_test_description(&self) -> Result<String, Error> {
+        let description = self.scraped()?.description?;
+        Ok(decode_entities(&description))
+    }
+
+    pub fn get_data(&self, input_extension: &String, output_extension: &String) -> Result<PathBuf, Error> {
         match self.submission_type {
-            SubmissionType::ATCODER => self.atcoder_data(),
-            SubmissionType::CODEFORCES => self.codeforces_data(),
-            SubmissionType::USACO => unreachable!(),
+            SubmissionType::ATCODER | SubmissionType::CODEFORCES | SubmissionType::CSES => self.examples_data(input_extension, output_extension),
+            SubmissionType::OJUZ => self.ojuz_data(input_extension, output_extension),
+            SubmissionType::USACO | SubmissionType::KATTIS => Err(Error::OTHER(format!(
+                "{} examples are read from the test data zip, not the problem page",
+                self.submission_type
+            ))),
         }
     }
 
-    pub fn get_io(&self, input_extension: &String, output_extension: &String) -> Result<(IOType, IOType), String> {
+    pub fn get_io(&self, input_extension: &String, output_extension: &String) -> Result<(IOType, IOType), Error> {
         match self.submission_type {
             SubmissionType::USACO => self.usaco_io(input_extension, output_extension),
             SubmissionType::CODEFORCES => Ok((IOType::STD, IOType::STD)),
             SubmissionType::ATCODER => Ok((IOType::STD, IOType::STD)),
+            SubmissionType::CSES => Ok((IOType::STD, IOType::STD)),
+            SubmissionType::KATTIS => Ok((IOType::STD, IOType::STD)),
+            SubmissionType::OJUZ => Ok((IOType::STD, IOType::STD)),
         }
     }
 
-    fn usaco_io(&self, input_extension: &String, output_extension: &String) -> Result<(IOType, IOType), String> {
-        let problem_page = handle_error!(
-            reqwest::blocking::get(&self.link),
-            format!("Failed to access problem link: {}", self.link)
-        );
-        if problem_page.status() != reqwest::StatusCode::OK {
-            return Err(format!(
-                "Failed to access link, status code is not 200 it is {}, link: {} ",
-                problem_page.status(),
-                self.link
-            ));
+    // Time limit in ms and memory limit in MB, None if the page doesn't have them
+    pub fn get_limits(&self) -> Result<(Option<u64>, Option<u64>), Error> {
+        match self.submission_type {
+            SubmissionType::USACO => Ok((Some(USACO_TIME_LIMIT_MS), Some(USACO_MEMORY_LIMIT_MB))),
+            SubmissionType::CODEFORCES | SubmissionType::ATCODER | SubmissionType::CSES => Ok(self.scraped()?.limits?),
+            SubmissionType::KATTIS | SubmissionType::OJUZ => Ok((None, None)),
         }
-        let problem_page_text = handle_error!(problem_page.text(), "Failed to get HTML from problem page");
-        let io_regex = handle_error!(Regex::new(PROBLEM_IO_REGEX_STR), "Failed to create regex for problem io");
-        let io_match = io_regex.captures(&problem_page_text);
-        let io_match = handle_option!(
-            io_match,
-            "Failed to get io from problem page, page doesn't have \"INPUT FORMAT\" section, could mean ID/Link is invalid"
-        );
-        let io_str = &io_match["io"];
-        let io_str = io_str.trim();
-        let io_string = if io_str == USACO_STANDARD_IO_STR {
-            USACO_STANDARD_IO_STR.to_string()
-        } else {
-            io_str.split(' ').last().unwrap().to_string()
-        };
+    }
 
-        let input_io = if io_string == USACO_STANDARD_IO_STR {
-            IOType::STD
-        } else {
-            IOType::FILE(PathBuf::from(&io_string).with_extension(&input_extension))
-        };
-        let output_io = if io_string == USACO_STANDARD_IO_STR {
-            IOType::STD
-        } else {
-            IOType::FILE(PathBuf::from(&io_string).with_extension(&output_extension))
+    fn usaco_io(&self, input_extension: &String, output_extension: &String) -> Result<(IOType, IOType), Error> {
+        let (input_io, output_io) = match self.scraped()?.io_file? {
+            None => (IOType::STD, IOType::STD),
+            Some(stem) => (
+                IOType::FILE(json_file::with_suffix(Path::new(&stem), input_extension)),
+                IOType::FILE(json_file::with_suffix(Path::new(&stem), output_extension)),
+            ),
         };
         Ok((input_io, output_io))
     }
 
-    pub fn write_usaco_examples(&self, write_path: PathBuf, input_extension: &String, output_extension: &String) -> Result<(), String> {
-        let problem_page = handle_error!(
-            reqwest::blocking::get(&self.link),
-            format!("Failed to access problem link: {}", self.link)
-        );
-        if problem_page.status() != reqwest::StatusCode::OK {
-            return Err(format!(
-                "Failed to access link, status code is not 200 it is {}, link: {} ",
-                problem_page.status(),
-                self.link
-            ));
+    pub fn write_usaco_examples(&self, write_path: PathBuf, input_extension: &String, output_extension: &String) -> Result<(), Error> {
+        let examples = self.scraped()?.examples?;
+        if examples.is_empty() {
+            eprintln!("{}: Found no examples on the USACO problem page", color::yellow("WARNING"));
+            return Ok(());
         }
-        let problem_page_text = handle_error!(problem_page.text(), "Failed to get HTML from problem page");
-
-        let example_regex = handle_error!(Regex::new(USACO_EXAMPLE_PROBLEM_STR), "Failed to create regex for example problem");
-
-        let example_matches: Vec<(String, String)> = example_regex
-            .captures_iter(&problem_page_text)
-            .map(|cap| {
-                let input = cap.name("input").expect("Regex error").as_str().to_string();
-                let output = cap.name("output").expect("Regex error").as_str().to_string();
-                (input, output)
-            })
-            .collect();
-
-        for (i, (input, output)) in example_matches.iter().enumerate() {
-            let input_path = write_path.join(format!("example{}.{}", i + 1, input_extension));
-            let output_path = write_path.join(format!("example{}.{}", i + 1, output_extension));
-            handle_error!(fs::write(&input_path, input), "Failed to write example input");
-            handle_error!(fs::write(&output_path, output), "Failed to write example output");
-        }
-
+        write_examples(&write_path, &examples, input_extension, output_extension)?;
+        println!("Found {} examples on the problem page", examples.len());
         Ok(())
     }
 
-    fn atcoder_test_name(&self) -> Result<String, String> {
-        let problem_page_text = get_link_html(&self.link)?;
-        let name_regex = handle_error!(
-            Regex::new(ATCODER_NAME_REGEX_STR),
-            format!("Failed to create regex from string - String is {}", ATCODER_NAME_REGEX_STR)
-        );
-        let contest_name_task = {
-            let cutoff_link = &self.link.split("/tasks/").last();
-            let cutoff_link = handle_option!(
-                cutoff_link,
-                "Failed to get get contest name from link, leave github issue, probably mean atcoder link format has changed"
-            );
-            cutoff_link
-        };
-        let (_name,formatted_name) = handle_option!(
-            name_regex
-                .captures_iter(&problem_page_text)
-                .map(|cap| {
-                    let name = handle_option!(cap.name("name"), "Failed to get name of problem from problem page using regex");
-                    let formatted_name = name.as_str().trim().split("-").last().unwrap().split("(").next().unwrap().trim().replace(" ","_").replace("\n","_").to_ascii_lowercase();
-                    Ok((name.as_str(),formatted_name))
-                })
-                .next(),
+    // Like k_th_element_abc300_c, the task of the link keeps problems with the same title apart
+    fn atcoder_test_name(&self, problem_page_text: &str) -> Result<String, String> {
+        let (_, title) = handle_option!(
+            atcoder_title(problem_page_text)?,
             "Failed to infer name from AtCoder problem page, please leave a github issue and pass a name when adding the test to make it work for now"
-        )?;
-        Ok(format!("{}_{}", formatted_name, contest_name_task))
-    }
-
-    fn atcoder_test_description(&self) -> Result<String, String> {
-        let problem_page_text = get_link_html(&self.link)?;
-        let name_regex = handle_error!(
-            Regex::new(ATCODER_NAME_REGEX_STR),
-            format!("Failed to create regex from string - String is {}", ATCODER_NAME_REGEX_STR)
         );
-        let description_regex = handle_error!(
-            Regex::new(ATCODER_DESCRIPTION_REGEX_STR),
-            format!("Failed to create regex from string - String is {}", ATCODER_DESCRIPTION_REGEX_STR)
-        );
-        let unformatted_name = handle_option!(
-            name_regex
-                .captures_iter(&problem_page_text)
-                .map(|cap| {
-                    let name = handle_option!(cap.name("name"), "Failed to get name of problem from problem page using regex");
-                    Ok(name.as_str())
-                })
-                .next(),
-            "Failed to infer name from AtCoder problem page, please leave a github issue and pass a name when adding the test to make it work for now"
-        )?;
-        let description = handle_option!(
-            description_regex
-                .captures_iter(&problem_page_text)
-                .map(|cap| {
-                    let description = handle_option!(cap.name("contest_info"), "Failed to get description of problem from problem page using regex");
-                    Ok(description.as_str().trim().to_string())
-                })
-                .next(),
-            "Failed to infer description from AtCoder problem page, please leave a github issue and pass a description when adding the test to make it work for now"
-        )?;
-        let description = format!("{}: {}", description.trim(), unformatted_name.trim());
-        Ok(description)
-    }
-
-    fn atcoder_data(&self) -> Result<PathBuf, String> {
-        unimplemented!()
+        let title = title
+            .to_ascii_lowercase()
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<&str>>()
+            .join("_");
+        // Saved pages might not have a task link, so their names don't have the task
+        match (atcoder_task(&self.link), title.is_empty()) {
+            (Some(task), true) => Ok(task),
+            (Some(task), false) => Ok(format!("{}_{}", title, task)),
+            (None, _) if self.page_html.is_some() && !title.is_empty() => Ok(title),
+            (None, _) => Err("Failed to get get contest name from link, leave github issue, probably mean atcoder link format has changed".to_string()),
+        }
     }
 
-    fn codeforces_test_name(&self) -> Result<String, String> {
-        let problem_page_text = get_link_html(&self.link)?;
-        let name_regex = handle_error!(
-            Regex::new(CODEFORCES_NAME_REGEX_STR),
-            format!("Failed to create regex from string - String is {}", CODEFORCES_NAME_REGEX_STR)
-        );
-        let name = handle_option!(
-            name_regex
-                .captures_iter(&problem_page_text)
-                .map(|cap| {
-                    let name = handle_option!(cap.name("name"), "Failed to get name of problem from problem page using regex");
-                    Ok(name.as_str().trim().replace(" ", "_").replace("/n","_").replace(".","").to_ascii_lowercase())
-                })
-                .next(),
-            "Failed to infer name from Codeforces problem page, please leave a github issue and pass a name when adding the test to make it work for now"
-        )?;
-        Ok(name)
+    // The examples on the problem page, written to a temp dir like an extracted zip
+    fn examples_data(&self, input_extension: &String, output_extension: &String) -> Result<PathBuf, Error> {
+        let examples = self.scraped()?.examples?;
+        if examples.is_empty() {
+            return Err(format!(
+                "Failed to find examples on {} problem page, please leave a github issue",
+                self.submission_type
+            )
+            .into());
+        }
+        let temp_dir = TempDir::new().context("Failed to create temporary directory to store examples")?;
+        write_examples(temp_dir.path(), &examples, input_extension, output_extension)?;
+        Ok(temp_dir.into_path())
     }
 
-    fn codeforces_test_description(&self) -> Result<String, String> {
-        let problem_page_text = get_link_html(&self.link)?;
-        let description_regex = handle_error!(
-            Regex::new(CODEFORCES_DESCRIPTION_REGEX_STR),
-            format!("Failed to create regex from string - String is {}", CODEFORCES_DESCRIPTION_REGEX_STR)
-        );
-        let description = handle_option!(
-            description_regex
-                .captures_iter(&problem_page_text)
-                .map(|cap| {
-                    let description = handle_option!(cap.name("contest_info"), "Failed to get description of problem from problem page using regex");
-                    Ok(description.as_str().trim().to_string())
-                })
-                .next(),
-            "Failed to infer description from Codeforces problem page, please leave a github issue"
-        )?;
-        let name_regex = handle_error!(
-            Regex::new(CODEFORCES_NAME_REGEX_STR),
-            format!("Failed to create regex from string - String is {}", CODEFORCES_NAME_REGEX_STR)
-        );
-        let name = handle_option!(
-            name_regex
-                .captures_iter(&problem_page_text)
-                .map(|cap| {
-                    let name = handle_option!(cap.name("name"), "Failed to get name of problem from problem page using regex");
-                    Ok(name.as_str().trim().to_string())
-                })
-                .next(),
-            "Failed to infer name for description from Codeforces problem page, please leave a github issue"
-        )?;
-        Ok(format!("{}: {} (Examples only)", description, name))
+    // The problem ID is the part of the link after /problems/, like hello for https://open.kattis.com/problems/hello
+    fn kattis_problem_id(&self) -> Result<String, String> {
+        let id = handle_option!(self.link.split(KATTIS_LINK_PREFIX).nth(1), "Failed to get problem ID from Kattis link");
+        let id = handle_option!(id.split(['/', '?', '#']).next(), "Failed to get problem ID from Kattis link");
+        if id.is_empty() {
+            return Err(format!("Kattis link {} doesn't have a problem ID", self.link));
+        }
+        Ok(id.to_string())
     }
 
-    fn codeforces_data(&self) -> Result<PathBuf, String> {
-        unimplemented!()
+    fn kattis_data_link(&self) -> Result<String, Error> {
+        Ok(format!("{}{}/file/statement/samples.zip", KATTIS_LINK_PREFIX, self.kattis_problem_id()?))
     }
 
-    fn usaco_test_name(&self) -> Result<String, String> {
-        let problem_page_text = get_link_html(&self.link)?;
-
-        let name_regex = handle_error!(
-            Regex::new(USACO_PROBLEM_NAME_REGEX_STR),
-            format!("Failed to create regex from string - String is {}", USACO_PROBLEM_NAME_REGEX_STR)
-        );
-        let name =
-            handle_option!(
-            name_regex
-                .captures_iter(&problem_page_text)
-                .map(|cap| {
-                    let year = handle_option!(cap.name("year"), "Failed to get year of contest from problem page using regex");
-                    let competition = handle_option!(cap.name("competition"), "Failed to get name of contest from problem page using regex");
-                    let divison = handle_option!(cap.name("divison"), "Failed to get divison of contest from problem page using regex");
-                    let name = handle_option!(cap.name("name"), "Failed to get name of problem from problem page using regex");
-                    let competition = competition.as_str().trim().to_ascii_lowercase();
-                    let competition = if competition.contains("us open") { "open" } else { &competition[0..3] };
-                    Ok(format!(
-                        "{}_{}_{}{}",
-                        if name.as_str().contains("Contest") {
-                            name.as_str().split("Contest").next().unwrap().trim().replace(" ", "_").to_ascii_lowercase()
-                        } else {
-                            name.as_str().trim().replace(" ", "_").to_ascii_lowercase()
-                        },
-                        divison.as_str().trim().to_ascii_lowercase(),
-                        competition,
-                        year.as_str().trim()
-                    ))
-                })
-                .next(),
-            "Failed to infer name from USACO problem page, please leave a github issue and pass a name when adding the test to make it work for now"
-        )?;
-        Ok(name)
+    // oj.uz problem IDs are already good names, like IOI16_aliens
+    fn ojuz_test_name(&self) -> Result<String, String> {
+        let id = handle_option!(self.link.split(OJUZ_LINK_PREFIX).nth(1), "Failed to get problem ID from oj.uz link");
+        let id = handle_option!(id.split(['/', '?', '#']).next(), "Failed to get problem ID from oj.uz link");
+        if id.is_empty() {
+            return Err(format!("oj.uz link {} doesn't have a problem ID", self.link));
+        }
+        Ok(id.to_ascii_lowercase())
     }
 
-    fn usaco_test_description(&self) -> Result<String, String> {
-        let problem_page_text = get_link_html(&self.link)?;
-
-        let name_regex = handle_error!(
-            Regex::new(USACO_PROBLEM_NAME_REGEX_STR),
-            format!("Failed to create regex from string - String is {}", USACO_PROBLEM_NAME_REGEX_STR)
-        );
-        let description =
-            handle_option!(
-            name_regex
-                .captures_iter(&problem_page_text)
-                .map(|cap| {
-                    let description = handle_option!(cap.name("description"), "Failed to get description of problem from problem page using regex");
-                    let description = description.as_str().trim().replace(" </h2>\n<h2>", ":");
-                    Ok(description)
-                })
-                .next(),
-            "Failed to infer name from USACO problem page, please leave a github issue and pass a name when adding the test to make it work for now"
-        )?;
-        Ok(description)
+    // Uses the attachment zip if the problem has one, else the examples on the page
+    fn ojuz_data(&self, input_extension: &String, output_extension: &String) -> Result<PathBuf, Error> {
+        let scraped = self.scraped()?;
+        let temp_dir = TempDir::new().context("Failed to create temporary directory to store test data")?;
+        if let Some(link) = scraped.data_page? {
+            let link = if link.starts_with('/') { format!("{}{}", OJUZ_HOST, link) } else { link };
+            println!("Downloading attachment zip file...");
+            let download = download_archive(&link)?;
+            extract_archive(download.path(), temp_dir.path())?;
+            return Ok(temp_dir.into_path());
+        }
+        let examples = scraped.examples?;
+        if examples.is_empty() {
+            return Err(OJUZ_NO_CASES_ERROR.to_string().into());
+        }
+        write_examples(temp_dir.path(), &examples, input_extension, output_extension)?;
+        Ok(temp_dir.into_path())
     }
 
-    fn usaco_data_link(&self) -> Result<String, String> {
+    fn usaco_data_link(&self) -> Result<String, Error> {
         let link = &self.link;
-        let problem_page = handle_error!(reqwest::blocking::get(link), "Failed to access link");
-        if problem_page.status() != reqwest::StatusCode::OK {
-            return handle_error!(
-                Err(problem_page.status()),
-                format!("Failed to access link, status code is not 200, link: {} ", link)
-            );
-        }
-        let problem_page_text = handle_error!(problem_page.text(), "Failed to get HTML from problem page");
-
-        let button_regex = handle_error!(
-            Regex::new(USACO_RETURN_TO_PROBLEM_BUTTON_REGEX_STR),
-            "Failed to create regex for results page button"
-        );
-        let button_match = button_regex.captures_iter(&problem_page_text).map(|cap| {
-            let result = handle_option!(cap.name("results"), "Failed to get results page name from regex capture, page doesn't have \"Return To Problem List\" Button, could mean ID/Link is invalid");
-            let result = result.as_str();
-            Ok(result)
-        }).next();
-
-        let button_match =
-            button_match.ok_or("Failed to get results page name from regex capture, page doesn't have \"Return To Problem List\" Button")?;
-        let button_match = button_match?;
+        let results_page = self.scraped()?.data_page?.context("USACO problem page has no results page")?;
 
         let problem_id = link.split("=").last().unwrap().to_string().parse::<i32>();
-        let problem_id = handle_error!(problem_id, "Failed to parse problem id from link");
-        let results_page_link = format!("http://www.usaco.org/index.php?page={}", button_match);
-        let results_page = handle_error!(reqwest::blocking::get(&results_page_link), "Failed to access results page");
-        if results_page.status() != reqwest::StatusCode::OK {
-            return handle_error!(
-                Err(results_page.status()),
-                format!("Failed to access link, status code is not 200, link: {} ", link)
-            );
-        }
-        let results_page_text = handle_error!(results_page.text(), "Failed to get HTML from results page");
-        let test_data_regex = handle_error!(Regex::new(USACO_TEST_DATA_BUTTON_REGEX_STR), "Failed to create regex for solution button");
+        let problem_id = problem_id.map_err(|e| e.to_string()).context("Failed to parse problem id from link")?;
+        let results_page_link = format!("http://www.usaco.org/index.php?page={}", results_page);
+        let results_page_text = http::get_text(&results_page_link).context("Failed to access results page")?;
+        let test_data_regex = Regex::new(USACO_TEST_DATA_BUTTON_REGEX_STR)
+            .map_err(|e| e.to_string())
+            .context("Failed to create regex for solution button")?;
         let test_data_matches: Vec<(i32, String)> = test_data_regex
             .captures_iter(&results_page_text)
             .map(|cap| {
@@ -494,7 +662,7 @@ impl SubmissionData {
             }
         }
         if test_data_link.is_none() {
-            return Err(format!("Failed to find test data link for problem id {}, at link {}", problem_id, link));
+            return Err(format!("Failed to find test data link for problem id {}, at link {}", problem_id, link).into());
         }
         let test_data_link = test_data_link.unwrap();
         let test_data_link = format!("http://www.usaco.org/{}", test_data_link);
@@ -502,19 +670,294 @@ impl SubmissionData {
     }
 }
 
+// Everything add uses from a problem page, parsed from one download of it
+// A part that fails to parse keeps its error, which is only returned if the part is used(Like the name without --name)
+#[derive(Debug, Clone)]
+struct ScrapedProblem {
+    name: Result<String, String>,
+    description: Result<String, String>,
+    // The name of the IO files without an extension, None for standard IO
+    io_file: Result<Option<String>, String>,
+    limits: Result<(Option<u64>, Option<u64>), String>,
+    examples: Result<Vec<(String, String)>, String>,
+    // The results page with the test data link for USACO, the attachment zip for oj.uz
+    data_page: Result<Option<String>, String>,
+}
+
+// By link, so the page is downloaded once even when more than one SubmissionData is made for it(Like for the IO)
+static SCRAPED_PROBLEMS: Mutex<BTreeMap<String, ScrapedProblem>> = Mutex::new(BTreeMap::new());
+
+fn usaco_test_name(problem_page_text: &str) -> Result<String, String> {
+    let title = usaco_problem_title(problem_page_text)?;
+    let name = title.name.split("Contest").next().unwrap().trim().to_ascii_lowercase();
+    let name = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>()
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<&str>>()
+        .join("_");
+    // Contests are "December Contest", "Feb Contest", "US Open", or "Open", they become dec, feb, and open
+    let competition = title.competition.to_ascii_lowercase();
+    let competition = if competition.contains("open") {
+        "open".to_string()
+    } else {
+        competition.chars().take(3).collect()
+    };
+    let divison = title.divison.to_ascii_lowercase();
+    let divison = divison.trim_end_matches("division").trim();
+    Ok(format!("{}_{}_{}{}", name, divison, competition, &title.year[2..]))
+}
+
+fn usaco_test_description(problem_page_text: &str) -> Result<String, String> {
+    let title = usaco_problem_title(problem_page_text)?;
+    Ok(format!(
+        "USACO {} {}, {}: Problem {}. {}",
+        title.year, title.competition, title.divison, title.number, title.name
+    ))
+}
+
+fn usaco_io(problem_page_text: &str) -> Result<Option<String>, String> {
+    let io_regex = handle_error!(Regex::new(PROBLEM_IO_REGEX_STR), "Failed to create regex for problem io");
+    let io_match = handle_option!(
+        io_regex.captures(problem_page_text),
+        "Failed to get io from problem page, page doesn't have \"INPUT FORMAT\" section, could mean ID/Link is invalid"
+    );
+    usaco_io_file(&io_match["io"])
+}
+
+// The page the "Return to Problem List" button goes to, which has the test data links of the contest
+fn usaco_results_page(problem_page_text: &str) -> Result<String, String> {
+    let button_regex = handle_error!(
+        Regex::new(USACO_RETURN_TO_PROBLEM_BUTTON_REGEX_STR),
+        "Failed to create regex for results page button"
+    );
+    let button_match = handle_option!(
+        button_regex.captures(problem_page_text),
+        "Failed to get results page name from regex capture, page doesn't have \"Return To Problem List\" Button, could mean ID/Link is invalid"
+    );
+    Ok(button_match["results"].to_string())
+}
+
+// The contest is the link in the header, or the contest of the task on pages without it(Like older contests)
+fn atcoder_test_description(problem_page_text: &str, link: &str) -> Result<String, String> {
+    let (heading, _) = handle_option!(
+        atcoder_title(problem_page_text)?,
+        "Failed to infer name from AtCoder problem page, please leave a github issue and pass a name when adding the test to make it work for now"
+    );
+    let description_regex = handle_error!(
+        Regex::new(ATCODER_DESCRIPTION_REGEX_STR),
+        format!("Failed to create regex from string - String is {}", ATCODER_DESCRIPTION_REGEX_STR)
+    );
+    let contest = description_regex
+        .captures(problem_page_text)
+        .map(|cap| cap["contest_info"].trim().to_string())
+        .filter(|contest| !contest.is_empty())
+        .or_else(|| link.split("/contests/").nth(1)?.split(['/', '?', '#']).next().map(|contest| contest.to_ascii_uppercase()));
+    let contest = handle_option!(
+        contest,
+        "Failed to infer description from AtCoder problem page, please leave a github issue and pass a description when adding the test to make it work for now"
+    );
+    Ok(format!("{}: {}", contest, heading))
+}
+
+// The heading of the task, like "C - K-th Element", and the title in it, from the h2 of the statement or else the <title> of the page
+// Titles can have hyphens themselves, so only the first separator after the index is split on
+fn atcoder_title(problem_page_text: &str) -> Result<Option<(String, String)>, String> {
+    for title_regex in [ATCODER_NAME_REGEX_STR, ATCODER_TITLE_REGEX_STR] {
+        let title_regex = handle_error!(
+            Regex::new(title_regex),
+            format!("Failed to create regex from string - String is {}", title_regex)
+        );
+        let Some(cap) = title_regex.captures(problem_page_text) else {
+            continue;
+        };
+        let heading = decode_entities(&cap["heading"]).split_whitespace().collect::<Vec<&str>>().join(" ");
+        let title = match &cap.name("index") {
+            Some(index) => heading[index.as_str().len()..].trim_start().trim_start_matches(['-', ':']).trim().to_string(),
+            None => heading.clone(),
+        };
+        if !heading.is_empty() {
+            return Ok(Some((heading, title)));
+        }
+    }
+    Ok(None)
+}
+
+// The task of a problem link, like abc300_c for https://atcoder.jp/contests/abc300/tasks/abc300_c
+fn atcoder_task(link: &str) -> Option<String> {
+    let (_, task) = link.split_once("/tasks/")?;
+    let task = task.split(['/', '?', '#']).next()?;
+    (!task.is_empty()).then(|| task.to_ascii_lowercase())
+}
+
+// Like 1700a_alice_and_bob, the contest and index keep problems with the same title apart
+// Saved pages without a problem link only get the title, which starts with the index
+fn codeforces_test_name(problem_page_text: &str, link: &str) -> Result<String, String> {
+    let name_regex = handle_error!(
+        Regex::new(CODEFORCES_NAME_REGEX_STR),
+        format!("Failed to create regex from string - String is {}", CODEFORCES_NAME_REGEX_STR)
+    );
+    let name = handle_option!(
+        name_regex
+            .captures_iter(problem_page_text)
+            .map(|cap| {
+                let name = handle_option!(cap.name("name"), "Failed to get name of problem from problem page using regex");
+                Ok(name.as_str().trim().replace(" ", "_").replace("/n","_").replace(".","").to_ascii_lowercase())
+            })
+            .next(),
+        "Failed to infer name from Codeforces problem page, please leave a github issue and pass a name when adding the test to make it work for now"
+    )?;
+    let problem_regex = handle_error!(Regex::new(CODEFORCES_PROBLEM_LINK_REGEX_STR), "Failed to create regex for Codeforces problem links");
+    let Some(problem) = problem_regex.captures(link) else {
+        return Ok(name);
+    };
+    let index = problem["index"].to_ascii_lowercase();
+    // The title already starts with the index, like a_alice_and_bob
+    let title = name.strip_prefix(&index).and_then(|title| title.strip_prefix('_')).unwrap_or(&name);
+    Ok(format!("{}{}_{}", &problem["contest"], index, title))
+}
+
+fn codeforces_test_description(problem_page_text: &str) -> Result<String, String> {
+    let description_regex = handle_error!(
+        Regex::new(CODEFORCES_DESCRIPTION_REGEX_STR),
+        format!("Failed to create regex from string - String is {}", CODEFORCES_DESCRIPTION_REGEX_STR)
+    );
+    let description = handle_option!(
+        description_regex
+            .captures_iter(problem_page_text)
+            .map(|cap| {
+                let description = handle_option!(cap.name("contest_info"), "Failed to get description of problem from problem page using regex");
+                Ok(description.as_str().trim().to_string())
+            })
+            .next(),
+        "Failed to infer description from Codeforces problem page, please leave a github issue"
+    )?;
+    let name_regex = handle_error!(
+        Regex::new(CODEFORCES_NAME_REGEX_STR),
+        format!("Failed to create regex from string - String is {}", CODEFORCES_NAME_REGEX_STR)
+    );
+    let name = handle_option!(
+        name_regex
+            .captures_iter(problem_page_text)
+            .map(|cap| {
+                let name = handle_option!(cap.name("name"), "Failed to get name of problem from problem page using regex");
+                Ok(name.as_str().trim().to_string())
+            })
+            .next(),
+        "Failed to infer name for description from Codeforces problem page, please leave a github issue"
+    )?;
+    Ok(format!("{}: {} (Examples only)", description, name))
+}
+
+fn cses_problem_name(problem_page_text: &str) -> Result<String, String> {
+    let name_regex = handle_error!(
+        Regex::new(CSES_NAME_REGEX_STR),
+        format!("Failed to create regex from string - String is {}", CSES_NAME_REGEX_STR)
+    );
+    let name = handle_option!(
+        name_regex.captures(problem_page_text),
+        "Failed to infer name from CSES problem page, please leave a github issue and pass a name when adding the test to make it work for now"
+    );
+    Ok(name["name"].trim().to_string())
+}
+
+fn kattis_test_description(problem_page_text: &str) -> Result<String, String> {
+    let name_regex = handle_error!(
+        Regex::new(KATTIS_NAME_REGEX_STR),
+        format!("Failed to create regex from string - String is {}", KATTIS_NAME_REGEX_STR)
+    );
+    let name = handle_option!(
+        name_regex.captures(problem_page_text),
+        "Failed to infer description from Kattis problem page, please leave a github issue"
+    );
+    let tag_regex = handle_error!(Regex::new("<[^>]*>"), "Failed to create regex for HTML tags");
+    let name = tag_regex.replace_all(&name["name"], "");
+    Ok(format!("Kattis: {} (Examples only)", name.trim()))
+}
+
+fn ojuz_test_description(problem_page_text: &str) -> Result<String, String> {
+    let name_regex = handle_error!(
+        Regex::new(OJUZ_NAME_REGEX_STR),
+        format!("Failed to create regex from string - String is {}", OJUZ_NAME_REGEX_STR)
+    );
+    let name = handle_option!(
+        name_regex.captures(problem_page_text),
+        "Failed to infer description from oj.uz problem page, please leave a github issue"
+    );
+    Ok(format!("oj.uz: {}", name["name"].trim()))
+}
+
+fn ojuz_attachment(problem_page_text: &str) -> Result<Option<String>, String> {
+    let attachment_regex = handle_error!(Regex::new(OJUZ_ATTACHMENT_REGEX_STR), "Failed to create regex for attachments");
+    Ok(attachment_regex.captures(problem_page_text).map(|attachment| attachment["link"].to_string()))
+}
+
+// Inputs and outputs matched by the example regex of a site
+fn page_examples(problem_page_text: &str, example_regex: &str) -> Result<Vec<(String, String)>, String> {
+    let example_regex = handle_error!(
+        Regex::new(example_regex),
+        format!("Failed to create regex from string - String is {}", example_regex)
+    );
+    Ok(example_regex
+        .captures_iter(problem_page_text)
+        .map(|cap| (decode_html(&cap["input"]), decode_html(&cap["output"])))
+        .collect())
+}
+
+// Time limit in ms and memory limit in MB, None if the page doesn't have them
+fn page_limits(problem_page_text: &str) -> Result<(Option<u64>, Option<u64>), String> {
+    let time_regex = handle_error!(
+        Regex::new(TIME_LIMIT_REGEX_STR),
+        format!("Failed to create regex from string - String is {}", TIME_LIMIT_REGEX_STR)
+    );
+    let memory_regex = handle_error!(
+        Regex::new(MEMORY_LIMIT_REGEX_STR),
+        format!("Failed to create regex from string - String is {}", MEMORY_LIMIT_REGEX_STR)
+    );
+    let time_limit_ms = time_regex.captures(problem_page_text).and_then(|cap| {
+        let time_limit = cap["time_limit"].parse::<f64>().ok()?;
+        let is_ms = cap["unit"].to_ascii_lowercase().starts_with("m");
+        Some(if is_ms { time_limit as u64 } else { (time_limit * 1000.0) as u64 })
+    });
+    let memory_limit_mb = memory_regex.captures(problem_page_text).and_then(|cap| {
+        let memory_limit = cap["memory_limit"].parse::<u64>().ok()?;
+        let is_gb = cap["unit"].to_ascii_lowercase().starts_with("g");
+        Some(if is_gb { memory_limit * 1024 } else { memory_limit })
+    });
+    Ok((time_limit_ms, memory_limit_mb))
+}
+
 impl AddArgs {
-    pub fn get_test_data(&self) -> Result<(String, PathBuf, Option<SubmissionData>, Option<String>), String> {
-        match (&self.input_type.link, &self.input_type.folder, &self.input_type.usaco_id) {
-            (Some(link), None, None) => self.data_from_link(link),
-            (None, Some(folder), None) => self.data_from_folder(folder),
-            (None, None, Some(id)) => self.data_from_usaco_id(id),
+    pub fn get_test_data(&self) -> Result<(String, PathBuf, Option<SubmissionData>, Option<String>), Error> {
+        let input_type = &self.input_type;
+        // clap doesn't enforce requires when the required argument conflicts with the given one
+        if self.output_file.is_some() && input_type.input_file.is_none() {
+            return Err("--output-file can only be used with --input-file".to_string().into());
+        }
+        match (
+            input_type.link.first(),
+            &input_type.folder,
+            &input_type.zip,
+            &input_type.input_file,
+            input_type.usaco_id.first(),
+            &input_type.html,
+        ) {
+            (Some(link), None, None, None, None, None) => self.data_from_link(link),
+            (None, Some(folder), None, None, None, None) => self.data_from_folder(folder),
+            (None, None, Some(zip), None, None, None) => self.data_from_zip(zip),
+            (None, None, None, Some(input_file), None, None) => self.data_from_files(input_file),
+            (None, None, None, None, Some(id), None) => self.data_from_usaco_id(id),
+            (None, None, None, None, None, Some(html)) => self.data_from_html(html),
             _ => Err(
-                "This means the clap crate has an issue, since it shouldn't allow more than one argument between link, folder, and usaco-problem-id"
-                    .to_string(),
+                "This means the clap crate has an issue, since it shouldn't allow more than one argument between link, folder, zip, input-file, usaco-problem-id, html, and package"
+                    .to_string()
+                    .into(),
             ),
         }
     }
-    fn data_from_link(&self, link: &String) -> Result<(String, PathBuf, Option<SubmissionData>, Option<String>), String> {
+    fn data_from_link(&self, link: &String) -> Result<(String, PathBuf, Option<SubmissionData>, Option<String>), Error> {
         let submission_data = SubmissionData::try_from_link(link);
         let submission_name = if self.name.is_some() {
             None
@@ -542,161 +985,1171 @@ impl AddArgs {
             .unwrap_or(&link.split("/").last().unwrap().split(".").next().unwrap().to_string().clone())
             .clone();
         let description = &self.description.as_ref().or(submission_description.as_ref()).cloned();
-        let name = name.clone();
+        let name = self.check_name(name)?;
         let description = description.clone();
         println!("Test Name: {}\nTest Description: {}", name, description.as_ref().unwrap_or(&"Nothing".to_string()));
 
-        if submission_data.is_some() && submission_data.as_ref().unwrap().submission_type != SubmissionType::USACO {
-            let data_path = handle_error!(
-                submission_data.as_ref().unwrap().get_data(),
-                format!(
+        // USACO and Kattis test data comes as a zip, the rest are written to a temp dir by get_data
+        let has_data_link = submission_data
+            .as_ref()
+            .is_some_and(|submission_data| matches!(submission_data.submission_type, SubmissionType::USACO | SubmissionType::KATTIS));
+        if submission_data.is_some() && !has_data_link {
+            let data_path = submission_data
+                .as_ref()
+                .unwrap()
+                .get_data(&self.get_input_extension(), &self.get_output_extension())
+                .context(format!(
                     "Failed to get data from link for submission type: {}",
-                    submission_data.unwrap().submission_type
-                )
-            );
+                    submission_data.as_ref().unwrap().submission_type
+                ))?;
             return Ok((name, data_path, submission_data, description));
         }
 
         let link = &if submission_data.is_some() {
-            handle_error!(submission_data.as_ref().unwrap().get_data_link(), "Failed to get link for test data")
+            submission_data.as_ref().unwrap().get_data_link().context("Failed to get link for test data")?
         } else {
             link.clone()
         };
 
-        let mut response = handle_error!(reqwest::blocking::get(link), "Failed to access link");
-        if response.status() != reqwest::StatusCode::OK {
-            return handle_error!(
-                Err(response.status()),
-                format!("Failed to access link, status code is not 200, link: {} ", link)
-            );
-        }
-
         println!("Test name is \"{}\"", name);
         if submission_data.is_some() {
             println!("Submission type is {}", submission_data.as_ref().unwrap().submission_type);
         } else {
-            println!("No submission type(USACO, Codeforces, and AtCoder are supported and should be inferred if given links to the problem page)");
-        }
-        let test_names = ProgramData::load_empty_tests().unwrap();
-        if test_names.contains_key(&name) {
-            return Err(format!("Test with name \"{}\" already exists", &name));
+            println!("No submission type(USACO, Codeforces, AtCoder, CSES, Kattis, and oj.uz are supported and should be inferred if given links to the problem page)");
         }
 
-        let mut bytes: Vec<u8> = vec![];
         println!("Downloading zip file...");
-        let amount_read = handle_error!(response.copy_to(&mut bytes), "Failed to read response");
-        let amount_read_mb = (amount_read as f64) / (1024_f64 * 1024_f64);
-        if amount_read_mb < 1.0 {
-            println!("Downloaded {:.2} KB successfully", amount_read / 1024);
-        } else {
-            println!("Downloaded {:.2} MB successfully", amount_read_mb);
+        let download = download_archive(link)?;
+        let temp_dir = TempDir::new().context("Failed to create temporary directory to store and extract zip")?;
+        extract_archive(download.path(), temp_dir.path())?;
+        if !self.dry_run && Config::get().context("Failed to load config")?.keep_source_archives {
+            let archive_path = data_dir::archive_path(&name)?;
+            let archive_dir = archive_path.parent().context("Archive path has no parent, this shouldn't happen")?;
+            fs::create_dir_all(archive_dir).context("Failed to create archive directory")?;
+            fs::copy(download.path(), &archive_path).context(format!("Failed to keep archive at {:?}", archive_path))?;
+            println!("Kept the archive at {:?}", archive_path);
         }
-        if amount_read < 4 {
-            return Err(String::from(
-                "Response is not a zip file. First four bytes don't match zip file signature(Less than 4 total bytes in response body).",
-            ));
-        }
-        let is_zip = bytes[0..=3] == ZIP_BYTES;
-        if !is_zip {
-            return Err(format!(
-                "Response is not a zip file. First four bytes in response body don't match zip file signature([{}])",
-                &ZIP_BYTES.iter().map(|b| format!("0x{:02x}", b)).collect::<Vec<String>>().join(", ")
-            ));
-        }
-
-        let temp_dir = handle_error!(TempDir::new(), "Failed to create temporary directory to store and extract zip");
-        let temp_zip_path = temp_dir.path().join("temp.zip");
-        let write_result = fs::write(&temp_zip_path, bytes);
-        handle_error!(write_result, "Failed to write zip file to temporary directory");
-
-        let zip_file = handle_error!(fs::File::open(&temp_zip_path), "Failed to open zip file");
-        let mut zip_archive = handle_error!(ZipArchive::new(zip_file), "Failed to read zip file");
-        handle_error!(zip_archive.extract(temp_dir.path()), "Failed to extract zip file");
+        // Removed right after extracting, instead of taking up space until the command finishes
+        drop(download);
         if let Some(submission_data) = submission_data.as_ref() {
             if submission_data.submission_type == SubmissionType::USACO {
-                submission_data.write_usaco_examples(temp_dir.path().to_path_buf(), &self.input_extension, &self.output_extension)?;
+                submission_data.write_usaco_examples(temp_dir.path().to_path_buf(), &self.get_input_extension(), &self.get_output_extension())?;
             }
         }
         Ok((name, temp_dir.into_path(), submission_data, description))
     }
-    fn data_from_folder(&self, folder: &PathBuf) -> Result<(String, PathBuf, Option<SubmissionData>, Option<String>), String> {
-        let folder = handle_error!(folder.canonicalize(), "Failed to get canonical(Absolute) path of folder");
+    fn data_from_folder(&self, folder: &PathBuf) -> Result<(String, PathBuf, Option<SubmissionData>, Option<String>), Error> {
+        let folder = paths::canonicalize(folder).context("Failed to get canonical(Absolute) path of folder")?;
         let name = if self.name.is_some() {
             self.name.as_ref().unwrap().clone()
         } else {
-            let name = handle_option!(folder.file_name(), "Can't get folder name from folder path, this shouldn't happen").to_str();
-            let name = handle_option!(name, "Invalid folder name, not valid utf-8").to_string();
+            let name = folder.file_name().context("Can't get folder name from folder path, this shouldn't happen")?.to_str();
+            let name = name.context("Invalid folder name, not valid utf-8")?.to_string();
             name
         };
-        let test_names = ProgramData::load_empty_tests().unwrap();
-        if test_names.contains_key(&name) {
-            return Err(format!("Test with name \"{}\" already exists", &name));
-        }
+        let name = self.check_name(&name)?;
         println!("Test name is \"{}\"", name);
         let description = if self.description.is_some() { self.description.clone() } else { None };
         Ok((name, folder, None, description))
     }
 
-    fn data_from_usaco_id(&self, id: &i32) -> Result<(String, PathBuf, Option<SubmissionData>, Option<String>), String> {
+    fn data_from_zip(&self, zip: &Path) -> Result<(String, PathBuf, Option<SubmissionData>, Option<String>), Error> {
+        let name = match &self.name {
+            Some(name) => name.clone(),
+            None => {
+                let name = zip.file_name().context("Can't get zip file name from path, this shouldn't happen")?.to_str();
+                // Everything before the first dot, so foo.tar.gz becomes foo
+                name.context("Invalid zip file name, not valid utf-8")?.split('.').next().unwrap().to_string()
+            }
+        };
+        let name = self.check_name(&name)?;
+        println!("Test name is \"{}\"", name);
+        let temp_dir = TempDir::new().context("Failed to create temporary directory to extract zip")?;
+        extract_archive(zip, temp_dir.path())?;
+        Ok((name, temp_dir.into_path(), None, self.description.clone()))
+    }
+
+    // A one case test, the files are copied into a temp dir as case 1
+    fn data_from_files(&self, input_file: &PathBuf) -> Result<(String, PathBuf, Option<SubmissionData>, Option<String>), Error> {
+        if self.output_file.is_none() && !self.allow_missing_outputs {
+            return Err("--output-file is required with --input-file, unless --allow-missing-outputs is given".to_string().into());
+        }
+        let name = match &self.name {
+            Some(name) => name.clone(),
+            None => {
+                let name = input_file.file_stem().context("Can't get input file name from path")?.to_str();
+                name.context("Invalid input file name, not valid utf-8")?.to_string()
+            }
+        };
+        let name = self.check_name(&name)?;
+        println!("Test name is \"{}\"", name);
+        let temp_dir = TempDir::new().context("Failed to create temporary directory to store test case")?;
+        fs::copy(input_file, temp_dir.path().join(format!("1.{}", self.get_input_extension())))
+            .context(format!("Failed to copy input file({:?})", input_file))?;
+        if let Some(output_file) = &self.output_file {
+            fs::copy(output_file, temp_dir.path().join(format!("1.{}", self.get_output_extension())))
+                .context(format!("Failed to copy output file({:?})", output_file))?;
+        }
+        Ok((name, temp_dir.into_path(), None, self.description.clone()))
+    }
+
+    fn data_from_usaco_id(&self, id: &i32) -> Result<(String, PathBuf, Option<SubmissionData>, Option<String>), Error> {
         let link = format!("{}{}", USACO_LINK_PREFIX, id);
         self.data_from_link(&link)
     }
 
+    // Runs the scrapers of the site on a saved problem page, only the examples on the page are added since test data is a download
+    fn data_from_html(&self, html: &Path) -> Result<(String, PathBuf, Option<SubmissionData>, Option<String>), Error> {
+        let submission_data = self.html_submission_data()?.context("No saved page given")?;
+        if submission_data.submission_type == SubmissionType::KATTIS {
+            return Err("Kattis samples are only in the samples zip, so Kattis problems can't be added from a saved page, use --link".to_string().into());
+        }
+        let name = match &self.name {
+            Some(name) => name.clone(),
+            None => match submission_data.get_test_name() {
+                Ok(name) => name,
+                Err(_) => {
+                    let name = html.file_stem().context("Can't get saved page name from path")?.to_str();
+                    name.context("Invalid saved page name, not valid utf-8")?.to_string()
+                }
+            },
+        };
+        let description = match &self.description {
+            Some(description) => Some(description.clone()),
+            None => submission_data.get_test_description().ok(),
+        };
+        let name = self.check_name(&name)?;
+        println!("Test Name: {}\nTest Description: {}", name, description.as_ref().unwrap_or(&"Nothing".to_string()));
+        println!("Submission type is {}", submission_data.submission_type);
+        let data_path = if submission_data.submission_type == SubmissionType::USACO {
+            let temp_dir = TempDir::new().context("Failed to create temporary directory to store examples")?;
+            submission_data.write_usaco_examples(temp_dir.path().to_path_buf(), &self.get_input_extension(), &self.get_output_extension())?;
+            temp_dir.into_path()
+        } else {
+            submission_data
+                .get_data(&self.get_input_extension(), &self.get_output_extension())
+                .context(format!("Failed to get examples from saved {} page", submission_data.submission_type))?
+        };
+        Ok((name, data_path, Some(submission_data), description))
+    }
+
+    // The site comes from --site, or from --page-link like it would for --link
+    fn html_submission_data(&self) -> Result<Option<SubmissionData>, Error> {
+        let Some(html) = &self.input_type.html else {
+            return Ok(None);
+        };
+        let page_link_data = self.page_link.as_ref().and_then(SubmissionData::try_from_link);
+        let site = match (&self.site, &page_link_data) {
+            (Some(site), _) => site.clone(),
+            (None, Some(page_link_data)) => page_link_data.submission_type.to_string().to_ascii_lowercase(),
+            (None, None) => {
+                return Err(format!("--site is needed with --html to know how to read the page, it can be {}", SITES.join(", ")).into())
+            }
+        };
+        let page_html = fs::read(html).context(format!("Failed to read saved page({:?})", html))?;
+        let link = match &self.page_link {
+            Some(page_link) => page_link.clone(),
+            None => {
+                let html = paths::canonicalize(html).context("Failed to get canonical(Absolute) path of saved page")?;
+                format!("file://{}", html.to_string_lossy())
+            }
+        };
+        Ok(Some(SubmissionData::from_html(&site, link, String::from_utf8_lossy(&page_html).to_string())?))
+    }
+
+    // The output extension to find cases with, Kattis uses .ans unless an extension is given
+    pub fn get_output_extension(&self) -> String {
+        let is_kattis = self
+            .input_type
+            .link
+            .first()
+            .and_then(SubmissionData::try_from_link)
+            .is_some_and(|submission_data| submission_data.submission_type == SubmissionType::KATTIS);
+        match &self.output_extension {
+            Some(output_extension) => output_extension.clone(),
+            None if is_kattis => KATTIS_OUTPUT_EXTENSION.to_string(),
+            None => Config::get().map_or(DEFAULT_OUTPUT_EXTENSION.to_string(), |config| config.default_output_extension),
+        }
+    }
+
+    // The extension in the config is used when none is given
+    pub fn get_input_extension(&self) -> String {
+        match &self.input_extension {
+            Some(input_extension) => input_extension.clone(),
+            None => Config::get().map_or(DEFAULT_INPUT_EXTENSION.to_string(), |config| config.default_input_extension),
+        }
+    }
+
+    pub fn get_scan_options(&self) -> Result<ScanOptions, String> {
+        let max_case_size_mb = handle_error!(Config::get(), "Failed to load config").max_case_size_mb;
+        Ok(ScanOptions {
+            allow_missing_outputs: self.allow_missing_outputs,
+            max_case_size: if self.no_size_limit || max_case_size_mb == 0 { None } else { Some(max_case_size_mb * 1024 * 1024) },
+        })
+    }
+
+    // Extensions given on the command line always win, otherwise the folder is checked for common conventions
+    pub fn get_case_naming(&self, folder: &Path) -> CaseNaming {
+        let default = CaseNaming::EXTENSIONS(self.get_input_extension(), self.get_output_extension());
+        let is_explicit = self.input_extension.is_some() || self.output_extension.is_some();
+        if let Some(style) = &self.naming {
+            let conventions = CaseNaming::conventions(Some(style));
+            if is_explicit {
+                // The given extensions are used in the first convention of the style, like input/output for prefixes
+                return match conventions.first() {
+                    Some(CaseNaming::PREFIXES(input, output)) => CaseNaming::PREFIXES(
+                        self.input_extension.clone().unwrap_or(input.clone()),
+                        self.output_extension.clone().unwrap_or(output.clone()),
+                    ),
+                    Some(CaseNaming::NUMBERED(input, output)) => CaseNaming::NUMBERED(
+                        self.input_extension.clone().unwrap_or(input.clone()),
+                        self.output_extension.clone().unwrap_or(output.clone()),
+                    ),
+                    _ => default,
+                };
+            }
+            return CaseNaming::detect(folder, Some(style)).unwrap_or(conventions[0].clone());
+        }
+        if is_explicit {
+            return default;
+        }
+        match CaseNaming::detect(folder, None) {
+            Some(naming) if naming != default => {
+                println!("Found test cases named like {}", naming);
+                naming
+            }
+            _ => default,
+        }
+    }
+
+    // Names can only be reused with --update, which merges into the existing test
+    // Names made from problem titles or file names have characters that can't be in a directory name replaced
+    // Returns the name the test is added with
+    fn check_name(&self, name: &str) -> Result<String, Error> {
+        let name = if self.name.is_some() { name.to_string() } else { names::sanitize(name) };
+        names::validate(&name, "Test")?;
+        let test_names = ProgramData::load_empty_tests()?;
+        if test_names.contains_key(&name) && !self.update {
+            return Err(format!("Test with name \"{}\" already exists, use --update to add new cases to it", name).into());
+        }
+        if let Some(conflict) = names::find_conflict(&name, test_names.keys()) {
+            return Err(format!(
+                "Test name \"{}\" is the same as the existing test \"{}\" except for capitalization, which would be the same directory on Windows and macOS",
+                name, conflict
+            )
+            .into());
+        }
+        Ok(name)
+    }
+
     pub fn input_type_is_folder(&self) -> bool {
         self.input_type.folder.is_some()
     }
-    pub fn get_io(&self) -> Result<(IOType, IOType), String> {
+
+    pub fn input_type_is_package(&self) -> bool {
+        self.input_type.package.is_some()
+    }
+
+    // One AddArgs per link or USACO id, so each test is added with its own name and description inference
+    // Contest links are replaced by a link for each of their problems
+    pub fn split_inputs(&self) -> Result<Vec<AddArgs>, Error> {
+        let has_contest = self.input_type.link.iter().any(|link| Contest::is_contest_link(link));
+        if !self.problems.is_empty() && !has_contest {
+            return Err("--problems can only be used with Codeforces or AtCoder contest links".to_string().into());
+        }
+        let input_count = self.input_type.link.len() + self.input_type.usaco_id.len();
+        if input_count <= 1 && !has_contest {
+            return Ok(vec![self.clone()]);
+        }
+        if self.name.is_some() || self.description.is_some() {
+            return Err(Error::OTHER(
+                "--name and --description can't be used when adding more than one link or USACO id, since every test would get them".to_string(),
+            ));
+        }
+        let mut inputs = vec![];
+        for link in &self.input_type.link {
+            if !Contest::is_contest_link(link) {
+                let mut args = self.clone();
+                args.input_type.link = vec![link.clone()];
+                inputs.push(args);
+                continue;
+            }
+            println!("Getting problems of contest {}", link);
+            let contest = Contest::from_link(link)?;
+            for problem in contest.filter_problems(&self.problems)? {
+                let mut args = self.clone();
+                args.input_type.link = vec![problem.link.clone()];
+                args.name = Some(contest.test_name(&problem));
+                args.label = Some(format!("{} {}", contest.id, problem.label));
+                inputs.push(args);
+            }
+        }
+        for id in &self.input_type.usaco_id {
+            let mut args = self.clone();
+            args.input_type.usaco_id = vec![*id];
+            inputs.push(args);
+        }
+        Ok(inputs)
+    }
+
+    // Stored with the test, paths are made absolute so they still make sense from another directory
+    pub fn source(&self) -> Option<String> {
+        let input_type = &self.input_type;
+        if let Some(link) = input_type.link.first() {
+            return Some(link.clone());
+        }
+        if let Some(id) = input_type.usaco_id.first() {
+            return Some(format!("{}{}", USACO_LINK_PREFIX, id));
+        }
+        if let Some(page_link) = self.page_link.as_ref().filter(|_| input_type.html.is_some()) {
+            return Some(page_link.clone());
+        }
+        let path = [&input_type.folder, &input_type.zip, &input_type.input_file, &input_type.html, &input_type.package]
+            .into_iter()
+            .find_map(|path| path.as_ref())?;
+        let path = paths::canonicalize(path).unwrap_or(path.clone());
+        Some(path.to_string_lossy().to_string())
+    }
+
+    // The link or USACO id being added, to show progress when adding several
+    pub fn input_label(&self) -> String {
+        if let Some(label) = &self.label {
+            return label.clone();
+        }
+        match (self.input_type.link.first(), self.input_type.usaco_id.first()) {
+            (Some(link), _) => link.clone(),
+            (None, Some(id)) => format!("USACO id {}", id),
+            (None, None) => match &self.input_type.html {
+                Some(html) => html.to_string_lossy().to_string(),
+                None => "test".to_string(),
+            },
+        }
+    }
+
+    // Returns the test name, the temp dir to remove afterwards, the test, and the checker source if the package has one
+    pub fn test_from_package(&self) -> Result<(String, PathBuf, Test, Option<PathBuf>), Error> {
+        let package_path = self.input_type.package.as_ref().context("No package given")?;
+        let temp_dir = TempDir::new().context("Failed to create temporary directory to extract package")?;
+        let package_dir = if package_path.is_dir() {
+            package_path.clone()
+        } else {
+            let package_dir = temp_dir.path().join("package");
+            fs::create_dir(&package_dir).context("Failed to create temporary directory to extract package")?;
+            extract_archive(package_path, &package_dir)?;
+            package_dir
+        };
+        let package = Package::from_dir(&package_dir)?;
+        println!("Found {} package with {} tests", package.package_type, package.cases.len());
+
+        let stem = package_path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+        let name = self.name.clone().or(package.name.clone()).unwrap_or(stem);
+        let name = self.check_name(&name)?;
+        println!("Test name is \"{}\"", name);
+        let description = self.description.clone().or(package.description.clone());
+
+        let cases_dir = temp_dir.path().join("cases");
+        fs::create_dir(&cases_dir).context("Failed to create temporary directory for test cases")?;
+        for (case_name, input_path, answer_path) in &package.cases {
+            let case_input = cases_dir.join(format!("{}.{}", case_name, self.get_input_extension()));
+            let case_output = cases_dir.join(format!("{}.{}", case_name, self.get_output_extension()));
+            fs::copy(input_path, case_input).context(format!("Failed to copy test input({:?})", input_path))?;
+            fs::copy(answer_path, case_output).context(format!("Failed to copy test answer({:?})", answer_path))?;
+        }
+        let (input_io, output_io) = if self.io.is_some() { self.get_io()? } else { (package.input_io, package.output_io) };
+        let mut test = Test::from_folder(
+            cases_dir,
+            &CaseNaming::EXTENSIONS(self.get_input_extension(), self.get_output_extension()),
+            input_io,
+            output_io,
+            None,
+            description,
+            self.get_scan_options()?,
+        )?;
+        test.time_limit_ms = package.time_limit_ms;
+        test.memory_limit_mb = package.memory_limit_mb;
+        find_subtasks(&mut test, package.subtasks);
+        if let Some(time_limit_ms) = test.time_limit_ms {
+            println!("Time limit is {} ms", time_limit_ms);
+        }
+        if let Some(memory_limit_mb) = test.memory_limit_mb {
+            println!("Memory limit is {} MB", memory_limit_mb);
+        }
+        // Only Polygon checkers are testlib checkers, ICPC output validators are run differently
+        let checker = match &package.checker {
+            Some(checker) if package.package_type == PackageType::Polygon => {
+                test.checker = checker.file_name().map(PathBuf::from);
+                println!("Found checker {:?}, runs of the test use it", checker.file_name().unwrap_or_default());
+                Some(checker.clone())
+            }
+            Some(checker) => {
+                println!("Found output validator {:?}, it isn't used since only testlib checkers are supported", checker.file_name().unwrap_or_default());
+                None
+            }
+            None => None,
+        };
+        Ok((name, temp_dir.into_path(), test, checker))
+    }
+    pub fn get_io(&self) -> Result<(IOType, IOType), Error> {
         let mut input_io = IOType::STD;
         let mut output_io = IOType::STD;
         if let Some(io) = &self.io {
             match io.len() {
+                1 if is_std_io(&io[0]) => return Err("Leave out --io to use stdin and stdout".to_string().into()),
                 1 => {
-                    input_io = IOType::FILE(PathBuf::from(&io[0]).with_extension(&self.input_extension));
-                    output_io = IOType::FILE(PathBuf::from(&io[0]).with_extension(&self.output_extension));
+                    input_io = IOType::FILE(json_file::with_suffix(Path::new(&io[0]), &self.get_input_extension()));
+                    output_io = IOType::FILE(json_file::with_suffix(Path::new(&io[0]), &self.get_output_extension()));
                 }
                 2 => {
-                    input_io = IOType::FILE(PathBuf::from(&io[0]).with_extension(&self.input_extension));
-                    output_io = IOType::FILE(PathBuf::from(&io[1]).with_extension(&self.output_extension));
+                    input_io = io_type(&io[0], &self.get_input_extension(), true)?;
+                    output_io = io_type(&io[1], &self.get_output_extension(), false)?;
                 }
-                _ => return Err("More than 2 values for --io flag, should be 0-2 values".to_string()),
+                _ => return Err("More than 2 values for --io flag, should be 0-2 values".to_string().into()),
             };
         } else {
-            let submission_data = if let Some(id) = &self.input_type.usaco_id {
+            let submission_data = if let Some(id) = self.input_type.usaco_id.first() {
                 let link = format!("{}{}", USACO_LINK_PREFIX, id);
                 SubmissionData::try_from_link(&link)
-            } else if let Some(link) = &self.input_type.link {
+            } else if let Some(link) = self.input_type.link.first() {
                 SubmissionData::try_from_link(link)
             } else {
-                None
+                self.html_submission_data()?
             };
             if let Some(submission_data) = submission_data {
-                (input_io, output_io) = submission_data.get_io(&self.input_extension, &self.output_extension)?;
+                (input_io, output_io) = submission_data.get_io(&self.get_input_extension(), &self.get_output_extension())?;
             }
 
             // if link.is_some() {
             //     let link = link.unwrap();
             //     let file_name = get_problem_io(&link)?;
             //     if file_name != USACO_STANDARD_IO_STR {
-            //         input_io = IOType::FILE(PathBuf::from(&file_name).with_extension(&self.input_extension));
-            //         output_io = IOType::FILE(PathBuf::from(&file_name).with_extension(&self.output_extension));
+            //         input_io = IOType::FILE(PathBuf::from(&file_name).with_extension(self.get_input_extension()));
+            //         output_io = IOType::FILE(PathBuf::from(&file_name).with_extension(self.get_output_extension()));
             //     }
             // }
         }
-        println!("Test IO: {:?}, {:?}", input_io, input_io);
+        println!("Test IO: {}, {}", input_io.to_string(true), output_io.to_string(false));
 
         Ok((input_io, output_io))
     }
 }
 
-fn get_link_html(link: &String) -> Result<String, String> {
-    let problem_page = handle_error!(reqwest::blocking::get(link), format!("Failed to access problem link: {}", link));
-    if problem_page.status() != reqwest::StatusCode::OK {
+// The two headers at the top of a USACO problem page, like "USACO 2024 February Contest, Bronze" and "Problem 1. Palindrome Game"
+struct UsacoProblemTitle {
+    year: String,
+    competition: String,
+    divison: String,
+    number: String,
+    name: String,
+}
+
+fn usaco_problem_title(problem_page_text: &str) -> Result<UsacoProblemTitle, String> {
+    let name_regex = handle_error!(
+        Regex::new(USACO_PROBLEM_NAME_REGEX_STR),
+        format!("Failed to create regex from string - String is {}", USACO_PROBLEM_NAME_REGEX_STR)
+    );
+    let cap = handle_option!(
+        name_regex.captures(problem_page_text),
+        "Failed to infer name from USACO problem page, please leave a github issue and pass a name when adding the test to make it work for now"
+    );
+    let group = |name: &str| {
+        cap.name(name)
+            .map(|value| value.as_str().trim().replace("&amp;", "&").replace("&#39;", "'"))
+            .unwrap_or_default()
+    };
+    Ok(UsacoProblemTitle {
+        year: group("year"),
+        competition: group("competition"),
+        divison: group("divison"),
+        number: group("number"),
+        name: group("name"),
+    })
+}
+
+// Checks the signature of the archive(zip, tar.gz, or tar), then extracts it into dir
+pub fn extract_archive(archive: &Path, dir: &Path) -> Result<(), String> {
+    // Only the start of the file is needed to find the format, the archive itself is streamed from the file
+    let mut bytes = vec![];
+    let file = handle_error!(fs::File::open(archive), format!("Failed to open archive({:?})", archive));
+    handle_error!(file.take(SIGNATURE_BYTES).read_to_end(&mut bytes), format!("Failed to read archive({:?})", archive));
+    let file = handle_error!(fs::File::open(archive), format!("Failed to open archive({:?})", archive));
+    match archive_format(&bytes)? {
+        ArchiveFormat::ZIP => {
+            let mut zip_archive = handle_error!(ZipArchive::new(file), "Failed to read zip file");
+            handle_error!(zip_archive.extract(dir), "Failed to extract zip file");
+        }
+        ArchiveFormat::GZIP => {
+            let mut tar_archive = Archive::new(GzDecoder::new(file));
+            handle_error!(tar_archive.unpack(dir), "Failed to extract tar.gz file");
+        }
+        ArchiveFormat::TAR => {
+            let mut tar_archive = Archive::new(file);
+            handle_error!(tar_archive.unpack(dir), "Failed to extract tar file");
+        }
+    }
+    Ok(())
+}
+
+// bytes is the start of the file, up to SIGNATURE_BYTES long
+fn archive_format(bytes: &[u8]) -> Result<ArchiveFormat, String> {
+    if bytes.starts_with(&ZIP_BYTES) {
+        return Ok(ArchiveFormat::ZIP);
+    }
+    if bytes.starts_with(&GZIP_BYTES) {
+        return Ok(ArchiveFormat::GZIP);
+    }
+    if bytes.get(TAR_MAGIC_OFFSET..TAR_MAGIC_OFFSET + TAR_MAGIC.len()) == Some(TAR_MAGIC) {
+        return Ok(ArchiveFormat::TAR);
+    }
+    if bytes.starts_with(&RAR_BYTES) || bytes.starts_with(&SEVEN_ZIP_BYTES) {
+        let format = if bytes.starts_with(&RAR_BYTES) { "RAR" } else { "7z" };
+        return Err(format!(
+            "This is a {} archive, which isn't supported. Extract it yourself and use --folder, or convert it to a zip",
+            format
+        ));
+    }
+    Err(format!(
+        "Not a supported archive. Checked for zip([{}]) and gzip([{}]) signatures at the start, and \"ustar\" at byte {} for tar",
+        format_bytes(&ZIP_BYTES),
+        format_bytes(&GZIP_BYTES),
+        TAR_MAGIC_OFFSET
+    ))
+}
+
+// Streamed into a temp file, a link that doesn't give an archive(Like a login page) fails before the rest is downloaded
+fn download_archive(link: &str) -> Result<NamedTempFile, Error> {
+    let download = NamedTempFile::new().context("Failed to create temporary file to download into")?;
+    let amount_read = http::download(link, download.path(), SIGNATURE_BYTES as usize, |start| archive_format(start).map(|_| ()))?;
+    let amount_read_mb = (amount_read as f64) / (1024_f64 * 1024_f64);
+    if amount_read_mb < 1.0 {
+        println!("Downloaded {:.2} KB successfully", amount_read / 1024);
+    } else {
+        println!("Downloaded {:.2} MB successfully", amount_read_mb);
+    }
+    Ok(download)
+}
+
+// Prints how many cases were found and warnings about cases that look broken, which are errors with --strict
+pub fn validate_test(test: &Test, strict: bool) -> Result<(), String> {
+    let total_size = test.data_size() as usize;
+    println!("Found {} cases, {} in total", test.cases.len(), format_file_size(total_size));
+    let warnings = test.validate();
+    if strict && !warnings.is_empty() {
+        return Err(format!("Found problems with the test cases(Adding without --strict only warns about them):\n{}", warnings.join("\n")));
+    }
+    for warning in warnings {
+        eprintln!("{}: {}", color::yellow("WARNING"), warning);
+    }
+    Ok(())
+}
+
+// Subtasks come from the package(Polygon groups with points) or the case names, and can be changed with set-subtasks
+pub fn find_subtasks(test: &mut Test, package_subtasks: Vec<Subtask>) {
+    test.subtasks = if package_subtasks.is_empty() { Subtask::infer(&test.get_sorted_case_names()) } else { package_subtasks };
+    if !test.subtasks.is_empty() {
+        println!("Found {} subtasks: {}", test.subtasks.len(), Subtask::format_all(&test.subtasks));
+    }
+}
+
+// A row of the case table printed by --dry-run
+#[derive(Tabled, Debug)]
+struct DryRunCase<'a> {
+    #[tabled(rename = "Case Name")]
+    name: &'a String,
+    #[tabled(rename = "Input Size")]
+    input_size: String,
+    #[tabled(rename = "Output Size")]
+    output_size: String,
+}
+
+// Shows what add would save, the temporary files are removed afterwards like a normal add
+pub fn print_dry_run(test_name: &str, test: &Test) {
+    let (input_type, output_type) = test.get_io_types();
+    println!("Dry run, nothing was saved");
+    println!("Test Name: {}", test_name);
+    println!("Test Description: {}", test.description.as_deref().unwrap_or("Nothing"));
+    println!("Test IO: {}, {}", input_type, output_type);
+    let cases = test
+        .get_sorted_case_names()
+        .into_iter()
+        .map(|name| {
+            let case = &test.cases[name];
+            DryRunCase {
+                name,
+                input_size: format_file_size(case.input.size() as usize),
+                output_size: match &case.output {
+                    Some(output) => format!("{}{}", format_file_size(output.size() as usize), case.alternates_note()),
+                    None => "(none)".to_string(),
+                },
+            }
+        })
+        .collect::<Vec<DryRunCase>>();
+    println!("{}", Table::new(cases));
+}
+
+pub fn format_file_size(bytes: usize) -> String {
+    let kb = bytes as f64 / 1024_f64;
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if kb < 1024.0 {
+        format!("{:.1} KB", kb)
+    } else {
+        format!("{:.1} MB", kb / 1024_f64)
+    }
+}
+
+fn format_bytes(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("0x{:02x}", b)).collect::<Vec<String>>().join(", ")
+}
+
+fn is_std_io(value: &str) -> bool {
+    value == "stdin" || value == "stdout"
+}
+
+// A value of --io, stdin for the input and stdout for the output mean the program doesn't use a file for it
+fn io_type(value: &str, extension: &str, input: bool) -> Result<IOType, String> {
+    let (std_name, other) = if input { ("stdin", "stdout") } else { ("stdout", "stdin") };
+    if value == std_name {
+        return Ok(IOType::STD);
+    }
+    if value == other {
         return Err(format!(
-            "Failed to access link, status code is not 200 it is {}, link: {} ",
-            problem_page.status(),
-            link
+            "{} can't be the {} of --io, the input can be stdin and the output can be stdout",
+            other,
+            if input { "input" } else { "output" }
         ));
     }
-    let problem_page_text = handle_error!(problem_page.text(), "Failed to get HTML from problem page");
+    Ok(IOType::FILE(json_file::with_suffix(Path::new(value), extension)))
+}
+
+// file:// links are pages saved with --html, so commands using the stored link work with them too
+pub fn get_link_html(link: &String) -> Result<String, Error> {
+    if let Some(path) = link.strip_prefix("file://") {
+        let page = fs::read(path).context(format!("Failed to read saved problem page: {}", path))?;
+        return Ok(String::from_utf8_lossy(&page).to_string());
+    }
+    let problem_page_text = http::get_text(link).context(format!("Failed to access problem link: {}", link))?;
     Ok(problem_page_text)
 }
+
+// The name of the IO files without an extension, like cowjump for "file cowjump.in", None for standard IO
+// The file is the last word ending in .in, else the last word, since the text around it isn't always the same
+fn usaco_io_file(io_str: &str) -> Result<Option<String>, String> {
+    let lowercase = io_str.to_ascii_lowercase();
+    if USACO_STANDARD_IO_WORDS.iter().any(|words| lowercase.contains(words)) {
+        return Ok(None);
+    }
+    let words = io_str
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c: char| !c.is_ascii_alphanumeric() && c != '_' && c != '-' && c != '.').trim_end_matches('.'))
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<&str>>();
+    let is_input_file = |word: &&&str| word.to_ascii_lowercase().ends_with(".in");
+    let file = handle_option!(
+        words.iter().rev().find(is_input_file).or(words.last()),
+        format!("Failed to find the IO file of the problem in \"{}\"", io_str.trim())
+    );
+    let lowercase = file.to_ascii_lowercase();
+    let stem = match [".in", ".out"].iter().find(|extension| lowercase.ends_with(*extension)) {
+        Some(extension) => &file[..file.len() - extension.len()],
+        None => file,
+    };
+    Ok(Some(stem.to_string()))
+}
+
+// Pairs each SAMPLE INPUT with the SAMPLE OUTPUT after it, an input without an output is skipped with a warning
+fn usaco_examples(page: &str) -> Result<Vec<(String, String)>, String> {
+    let page = page.replace("\r\n", "\n");
+    let header_regex = handle_error!(Regex::new(USACO_SAMPLE_HEADER_REGEX_STR), "Failed to create regex for USACO sample headers");
+    let sample_regex = handle_error!(Regex::new(USACO_SAMPLE_REGEX_STR), "Failed to create regex for USACO samples");
+    let headers = header_regex.captures_iter(&page).collect::<Vec<_>>();
+    let mut examples = vec![];
+    let mut input = None;
+    for (i, header) in headers.iter().enumerate() {
+        // The sample has to come before the next header, otherwise a header without one would take the sample of the next
+        let start = header.get(0).unwrap().end();
+        let end = headers.get(i + 1).map_or(page.len(), |next| next.get(0).unwrap().start());
+        let Some(sample) = sample_regex.captures(&page[start..end]) else {
+            continue;
+        };
+        let sample = decode_html(&sample["sample"]);
+        if header["kind"].eq_ignore_ascii_case("input") {
+            if input.replace(sample).is_some() {
+                eprintln!("{}: Skipped a USACO sample input without a sample output after it", color::yellow("WARNING"));
+            }
+        } else if let Some(input) = input.take() {
+            examples.push((input, sample));
+        }
+    }
+    if input.is_some() {
+        eprintln!("{}: Skipped a USACO sample input without a sample output after it", color::yellow("WARNING"));
+    }
+    Ok(examples)
+}
+
+// Sample blocks escape characters like < and can have tags(Codeforces puts each line in a div), the examples should match what the program prints
+fn decode_html(text: &str) -> String {
+    let mut sample = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        sample.push_str(&rest[..start]);
+        // Some pages don't escape a < in a sample, like "1 < 2", so it is only a tag if a name or / comes right after it
+        if !rest[start + 1..].starts_with(|c: char| c.is_ascii_alphabetic() || c == '/' || c == '!') {
+            sample.push('<');
+            rest = &rest[start + 1..];
+            continue;
+        }
+        let Some(end) = rest[start..].find('>') else {
+            rest = &rest[start..];
+            break;
+        };
+        let tag = rest[start + 1..start + end].trim().to_ascii_lowercase();
+        if tag.starts_with("br") || tag == "/div" || tag == "/p" {
+            sample.push('\n');
+        }
+        rest = &rest[start + end + 1..];
+    }
+    sample.push_str(rest);
+    let sample = decode_entities(&sample).replace("\r\n", "\n").replace('\r', "\n");
+    let sample = sample.trim_start_matches('\n').to_string();
+    if sample.ends_with('\n') {
+        sample
+    } else {
+        sample + "\n"
+    }
+}
+
+// Named entities that show up in problems, and numeric ones like &#39; or &#x27;
+pub fn decode_entities(text: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest.find(';').filter(|end| *end <= MAX_ENTITY_LENGTH).map(|end| &rest[1..end]);
+        let character = entity.and_then(|entity| match entity {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            "le" => Some('≤'),
+            "ge" => Some('≥'),
+            "ne" => Some('≠'),
+            "times" => Some('×'),
+            _ => {
+                let number = entity.strip_prefix('#')?;
+                let code = match number.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => number.parse().ok()?,
+                };
+                char::from_u32(code)
+            }
+        });
+        match (entity, character) {
+            (Some(entity), Some(character)) => {
+                decoded.push(character);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn write_examples(dir: &Path, examples: &[(String, String)], input_extension: &String, output_extension: &String) -> Result<(), String> {
+    for (i, (input, output)) in examples.iter().enumerate() {
+        let input_path = dir.join(format!("example{}.{}", i + 1, input_extension));
+        let output_path = dir.join(format!("example{}.{}", i + 1, output_extension));
+        handle_error!(fs::write(&input_path, input), "Failed to write example input");
+        handle_error!(fs::write(&output_path, output), "Failed to write example output");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_data::CaseData;
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write;
+
+    fn tar_bytes(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(vec![]);
+        for (path, contents) in files {
+            let mut header = tar::Header::new_ustar();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            builder.append_data(&mut header, path, contents.as_bytes()).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    fn gzip(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn case_text(data: &CaseData) -> String {
+        String::from_utf8(data.read().unwrap().into_owned()).unwrap()
+    }
+
+    // Like the data of a judge, in a folder inside the archive
+    fn extracted_cases(file_name: &str, archive: Vec<u8>) -> Test {
+        let dir = tempfile::tempdir().unwrap();
+        let archive_path = dir.path().join(file_name);
+        fs::write(&archive_path, archive).unwrap();
+        let extract_dir = dir.path().join("extracted");
+        extract_archive(&archive_path, &extract_dir).unwrap();
+        let naming = CaseNaming::EXTENSIONS("in".to_string(), "out".to_string());
+        Test::from_folder(extract_dir, &naming, IOType::STD, IOType::STD, None, None, ScanOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn cases_are_found_in_tarballs() {
+        let files = [("data/1.in", "1 2\n"), ("data/1.out", "3\n"), ("data/2.in", "5 5\n"), ("data/2.out", "10\n")];
+        let archives = [
+            ("data.tar.gz", gzip(&tar_bytes(&files))),
+            ("data.tgz", gzip(&tar_bytes(&files))),
+            ("data.tar", tar_bytes(&files)),
+        ];
+        for (file_name, archive) in archives {
+            let test = extracted_cases(file_name, archive);
+            assert_eq!(test.get_sorted_case_names(), ["1", "2"], "{}", file_name);
+            assert_eq!(case_text(&test.cases["2"].input), "5 5\n");
+            assert_eq!(case_text(test.cases["2"].output.as_ref().unwrap()), "10\n");
+        }
+    }
+
+    #[test]
+    fn archive_format_from_magic_bytes() {
+        assert!(matches!(archive_format(&[0x50, 0x4b, 0x03, 0x04, 0x14]), Ok(ArchiveFormat::ZIP)));
+        assert!(matches!(archive_format(&gzip(b"cases")), Ok(ArchiveFormat::GZIP)));
+        assert!(matches!(archive_format(&tar_bytes(&[("1.in", "1\n")])), Ok(ArchiveFormat::TAR)));
+        // Fewer bytes than the tar magic is at, like a short download
+        assert!(archive_format(b"ustar").is_err());
+        let rar = archive_format(b"Rar!\x1a\x07\x00").unwrap_err();
+        assert!(rar.contains("RAR archive"), "{}", rar);
+        let page = archive_format(b"<!DOCTYPE html><html>Log in</html>").unwrap_err();
+        assert!(page.contains("Checked for zip([0x50, 0x4b, 0x03, 0x04]) and gzip([0x1f, 0x8b])") && page.contains("byte 257"), "{}", page);
+    }
+
+    fn usaco_page(contest: &str, problem: &str) -> String {
+        format!(
+            "<div class=\"panel\">\n<h2> USACO {} </h2>\n<h2> {} </h2>\n</div>\n<span id=\"probtext-text\">",
+            contest, problem
+        )
+    }
+
+    #[test]
+    fn usaco_names_from_any_year() {
+        let pages = [
+            ("2014 December Contest, Bronze", "Problem 1. Marathon", "marathon_bronze_dec14"),
+            ("2017 US Open Contest, Gold", "Problem 2. Bovine Genomics", "bovine_genomics_gold_open17"),
+            ("2020 February Contest, Silver", "Problem 3. Clock Tree", "clock_tree_silver_feb20"),
+            ("2024 February Contest, Bronze", "Problem 1. Palindrome Game", "palindrome_game_bronze_feb24"),
+            ("2023 December Contest, Silver", "Problem 2. Farmer John&#39;s Favorite Operation", "farmer_john_s_favorite_operation_silver_dec23"),
+        ];
+        for (contest, problem, name) in pages {
+            assert_eq!(usaco_test_name(&usaco_page(contest, problem)).unwrap(), name);
+        }
+        // Older pages have the headers on one line without spaces
+        let page = "<h2>USACO 2015 February Contest, Gold</h2><h2>Problem 3. Cow Hopscotch</h2>";
+        assert_eq!(usaco_test_name(page).unwrap(), "cow_hopscotch_gold_feb15");
+        assert!(usaco_test_name("<h2>Problem 1. Marathon</h2>").is_err());
+    }
+
+    #[test]
+    fn usaco_descriptions_from_any_year() {
+        let page = usaco_page("2017 US Open Contest, Platinum", "Problem 1. Art");
+        assert_eq!(usaco_test_description(&page).unwrap(), "USACO 2017 US Open Contest, Platinum: Problem 1. Art");
+        let page = usaco_page("2014 December Contest, Bronze", "Problem 3. Cow Jog &amp; Co");
+        assert_eq!(usaco_test_description(&page).unwrap(), "USACO 2014 December Contest, Bronze: Problem 3. Cow Jog & Co");
+    }
+
+    #[test]
+    fn usaco_io_files_from_input_format_lines() {
+        let formats = [
+            ("input arrives from the terminal / stdin", None),
+            ("Standard Input", None),
+            ("input arrives from stdin", None),
+            ("file name photo.in.", Some("photo")),
+            ("file cowjump.in", Some("cowjump")),
+            ("file name photo", Some("photo")),
+            ("file photo.in or something", Some("photo")),
+            ("file Photo.IN", Some("Photo")),
+            ("file \"cow_dance.in\"", Some("cow_dance")),
+        ];
+        for (format, file) in formats {
+            assert_eq!(usaco_io_file(format).unwrap().as_deref(), file, "{}", format);
+        }
+        assert!(usaco_io_file(" ").is_err());
+    }
+
+    #[test]
+    fn usaco_io_from_the_problem_page() {
+        let page = "<p>The first line has N.</p><h4>INPUT FORMAT (file cowjump.in):</h4>";
+        assert_eq!(usaco_io(page).unwrap().as_deref(), Some("cowjump"));
+        let page = "<h4>INPUT FORMAT (input arrives from the terminal / stdin):</h4>";
+        assert_eq!(usaco_io(page).unwrap(), None);
+        assert!(usaco_io("<h4>SAMPLE INPUT:</h4>").is_err());
+        // The extensions replace the .in of the page instead of being added after it
+        let page = "<h4>INPUT FORMAT (file name photo.in.):</h4>";
+        let submission_data = SubmissionData::from_html("usaco", "file:///usaco_io.html".to_string(), page.to_string()).unwrap();
+        let (input_io, output_io) = submission_data.get_io(&"in".to_string(), &"out".to_string()).unwrap();
+        assert!(matches!(input_io, IOType::FILE(path) if path == Path::new("photo.in")));
+        assert!(matches!(output_io, IOType::FILE(path) if path == Path::new("photo.out")));
+    }
+
+    #[test]
+    fn entities_are_decoded() {
+        assert_eq!(decode_entities("&lt;a&gt; &amp;&amp; &quot;b&quot;&nbsp;c&#39;s &#x41;&#X42;"), "<a> && \"b\" c's AB");
+        assert_eq!(decode_entities("1 &le; n &times; m &ne; 0"), "1 ≤ n × m ≠ 0");
+        // Text that only looks like an entity is kept
+        assert_eq!(decode_entities("R&D; a & b &unknown; &#xZZ; &amp"), "R&D; a & b &unknown; &#xZZ; &amp");
+        assert_eq!(decode_entities("&amp;lt;"), "&lt;");
+    }
+
+    #[test]
+    fn samples_are_decoded_like_the_program_prints_them() {
+        // Codeforces puts each line in a div
+        let sample = "<div class=\"test-example-line\">3 4</div><div class=\"test-example-line\">1 &lt; 2</div>";
+        assert_eq!(decode_html(sample), "3 4\n1 < 2\n");
+        assert_eq!(decode_html("\r\n1<br/>2<BR>3\r\n"), "1\n2\n3\n");
+        assert_eq!(decode_html("<p>YES</p>"), "YES\n");
+        // An unescaped < isn't a tag
+        assert_eq!(decode_html("1 < 2\n5 <= 6"), "1 < 2\n5 <= 6\n");
+        assert_eq!(decode_html("a <!-- note --> b\n"), "a  b\n");
+        assert_eq!(decode_html("x <b"), "x <b\n");
+    }
+
+    #[test]
+    fn codeforces_examples_from_the_page() {
+        let page = concat!(
+            "<div class=\"sample-test\"><div class=\"input\"><div class=\"title\">Input</div><pre>",
+            "<div class=\"test-example-line test-example-line-even\">2</div><div class=\"test-example-line\">1 &lt; 2</div></pre></div>",
+            "<div class=\"output\"><div class=\"title\">Output</div><pre>\nYES\n</pre></div>",
+            "<div class=\"input\"><div class=\"title\">Input</div><pre>1<br />5</pre></div>",
+            "<div class=\"output\"><div class=\"title\">Output</div><pre>NO</pre></div></div>",
+        );
+        let examples = page_examples(page, CODEFORCES_EXAMPLE_REGEX_STR).unwrap();
+        assert_eq!(
+            examples,
+            [("2\n1 < 2\n".to_string(), "YES\n".to_string()), ("1\n5\n".to_string(), "NO\n".to_string())]
+        );
+        assert!(page_examples("<div class=\"title\">A. Problem</div>", CODEFORCES_EXAMPLE_REGEX_STR).unwrap().is_empty());
+    }
+
+    // Like a page saved from the browser, the link is only stored so the scrapers use the page
+    fn saved_page(site: &str, link: &str, page: &str) -> SubmissionData {
+        SubmissionData::from_html(site, link.to_string(), page.to_string()).unwrap()
+    }
+
+    fn example_files(submission_data: &SubmissionData) -> Vec<(String, String)> {
+        let dir = submission_data.get_data(&"in".to_string(), &"out".to_string()).unwrap();
+        let mut names = fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().file_name().into_string().unwrap()).collect::<Vec<String>>();
+        names.sort();
+        let files = names.iter().map(|name| (name.clone(), fs::read_to_string(dir.join(name)).unwrap())).collect();
+        fs::remove_dir_all(dir).unwrap();
+        files
+    }
+
+    #[test]
+    fn codeforces_problem_from_a_saved_page() {
+        let page = concat!(
+            "<a style=\"color: black\" href=\"/contest/1700\">Codeforces Round #802 (Div. 2)</a>",
+            "<div class=\"header\"><div class=\"title\">A. Alice and Bob</div>",
+            "<div class=\"time-limit\"><div class=\"property-title\">time limit per test</div>2 seconds</div>",
+            "<div class=\"memory-limit\"><div class=\"property-title\">memory limit per test</div>256 megabytes</div></div>",
+            "<div class=\"input\"><pre>1 &amp; 2</pre></div><div class=\"output\"><pre>3</pre></div>",
+        );
+        let submission_data = saved_page("codeforces", "https://codeforces.com/contest/1700/problem/A", page);
+        assert_eq!(submission_data.get_test_name().unwrap(), "1700a_alice_and_bob");
+        assert_eq!(submission_data.get_test_description().unwrap(), "Codeforces Round #802 (Div. 2): A. Alice and Bob (Examples only)");
+        assert_eq!(submission_data.get_limits().unwrap(), (Some(2000), Some(256)));
+        assert!(matches!(submission_data.get_io(&"in".to_string(), &"out".to_string()).unwrap(), (IOType::STD, IOType::STD)));
+        assert_eq!(
+            example_files(&submission_data),
+            [("example1.in".to_string(), "1 & 2\n".to_string()), ("example1.out".to_string(), "3\n".to_string())]
+        );
+        // Without a problem link the name is only the title
+        let submission_data = saved_page("codeforces", "file:///codeforces_saved.html", page);
+        assert_eq!(submission_data.get_test_name().unwrap(), "a_alice_and_bob");
+    }
+
+    #[test]
+    fn atcoder_problem_from_a_saved_page() {
+        let page = concat!(
+            "<title>C - Sum of Pairs</title><a class=\"contest-title\" href=\"/contests/abc301\">AtCoder Beginner Contest 301</a>",
+            "<span class=\"h2\">C - Sum of Pairs</span><p>Time Limit: 2 sec / Memory Limit: 1024 MB</p>",
+            "<h3>Sample Input 1</h3><pre>2\r\n1 2\r\n</pre><h3>Sample Output 1</h3><pre>3\r\n</pre>",
+            "<h3>Sample Input 2</h3><pre>1\n5\n</pre><h3>Sample Output 2</h3><pre>5\n</pre>",
+        );
+        let submission_data = saved_page("atcoder", "https://atcoder.jp/contests/abc301/tasks/abc301_c", page);
+        assert_eq!(submission_data.get_test_name().unwrap(), "sum_of_pairs_abc301_c");
+        assert_eq!(submission_data.get_test_description().unwrap(), "AtCoder Beginner Contest 301: C - Sum of Pairs");
+        assert_eq!(submission_data.get_limits().unwrap(), (Some(2000), Some(1024)));
+        let files = example_files(&submission_data);
+        assert_eq!(files.len(), 4);
+        assert_eq!(files[0], ("example1.in".to_string(), "2\n1 2\n".to_string()));
+        assert_eq!(files[3], ("example2.out".to_string(), "5\n".to_string()));
+    }
+
+    #[test]
+    fn atcoder_titles_with_hyphens_and_old_layouts() {
+        let title = |page: &str| atcoder_title(page).unwrap().unwrap();
+        let heading = |heading: &str, title: &str| (heading.to_string(), title.to_string());
+        assert_eq!(title("<span class=\"h2\">\n\tC - K-th Element\n\t<a>Editorial</a></span>"), heading("C - K-th Element", "K-th Element"));
+        assert_eq!(title("<span class=\"h2\">H: Ex Problem</span>"), heading("H: Ex Problem", "Ex Problem"));
+        // Older pages only have the title of the page, which can end with the name of the contest
+        assert_eq!(
+            title("<title>A - Iroha&#39;s Obsession - AtCoder Regular Contest 058</title>"),
+            heading("A - Iroha's Obsession", "Iroha's Obsession")
+        );
+        assert_eq!(title("<title>Digits | AtCoder</title>"), heading("Digits", "Digits"));
+        assert_eq!(atcoder_title("<h1>Problem</h1>").unwrap(), None);
+        assert_eq!(atcoder_task("https://atcoder.jp/contests/abc300/tasks/abc300_c?lang=en").as_deref(), Some("abc300_c"));
+        assert_eq!(atcoder_task("https://atcoder.jp/contests/abc300/tasks/"), None);
+    }
+
+    #[test]
+    fn atcoder_names_and_descriptions_of_every_contest() {
+        let pages = [
+            (
+                "https://atcoder.jp/contests/abc300/tasks/abc300_c",
+                "<a class=\"contest-title\" href=\"/contests/abc300\">AtCoder Beginner Contest 300</a><span class=\"h2\">C - K-th Element</span>",
+                "k_th_element_abc300_c",
+                "AtCoder Beginner Contest 300: C - K-th Element",
+            ),
+            (
+                "https://atcoder.jp/contests/arc150/tasks/arc150_d",
+                "<a class=\"contest-title\" href=\"/contests/arc150\">AtCoder Regular Contest 150</a><span class=\"h2\">D - Sum (Hard) - More</span>",
+                "sum_hard_more_arc150_d",
+                "AtCoder Regular Contest 150: D - Sum (Hard) - More",
+            ),
+            (
+                "https://atcoder.jp/contests/agc060/tasks/agc060_h",
+                "<a class=\"contest-title\" href=\"/contests/agc060\">AtCoder Grand Contest 060</a><span class=\"h2\">H: Ex Problem</span>",
+                "ex_problem_agc060_h",
+                "AtCoder Grand Contest 060: H: Ex Problem",
+            ),
+            // Before 2019 the header has no contest link, so the contest is from the link
+            (
+                "https://atcoder.jp/contests/arc058/tasks/arc058_a",
+                "<title>A - Iroha&#39;s Obsession</title>",
+                "iroha_s_obsession_arc058_a",
+                "ARC058: A - Iroha's Obsession",
+            ),
+        ];
+        for (link, page, name, description) in pages {
+            let submission_data = saved_page("atcoder", link, page);
+            assert_eq!(submission_data.get_test_name().unwrap(), name);
+            assert_eq!(submission_data.get_test_description().unwrap(), description);
+        }
+        // A saved page without a task link is named by the title only, a downloaded one needs the task
+        let submission_data = saved_page("atcoder", "file:///atcoder_saved.html", "<span class=\"h2\">C - K-th Element</span>");
+        assert_eq!(submission_data.get_test_name().unwrap(), "k_th_element");
+        assert!(submission_data.get_test_description().is_err());
+        assert!(atcoder_test_description("<p>No title</p>", "https://atcoder.jp/contests/abc300/tasks/abc300_c").is_err());
+    }
+
+    #[test]
+    fn cses_problem_from_a_saved_page() {
+        let page = concat!(
+            "<title>CSES - Weird Algorithm</title><ul><li><b>Time limit:</b> 1.00 s</li><li><b>Memory limit:</b> 512 MB</li></ul>",
+            "<p>Input:</p>\n<pre>3\n</pre>\n<p>Output:</p>\n<pre>3 10 5 16 8 4 2 1\n</pre>",
+        );
+        let submission_data = saved_page("cses", "file:///cses_saved.html", page);
+        assert_eq!(submission_data.get_test_name().unwrap(), "weird_algorithm");
+        assert_eq!(submission_data.get_test_description().unwrap(), "CSES Problem Set: Weird Algorithm (Examples only)");
+        assert_eq!(submission_data.get_limits().unwrap(), (Some(1000), Some(512)));
+        assert_eq!(example_files(&submission_data)[1], ("example1.out".to_string(), "3 10 5 16 8 4 2 1\n".to_string()));
+        // A page without examples is an error instead of an empty test
+        let submission_data = saved_page("cses", "file:///cses_no_examples.html", "<title>CSES - Weird Algorithm</title>");
+        assert!(submission_data.get_data(&"in".to_string(), &"out".to_string()).is_err());
+    }
+
+    #[test]
+    fn saved_pages_of_unknown_sites_are_errors() {
+        // --page-link gives the lowercase name of the submission type, so every one of them has to be a site
+        for site in SITES {
+            let submission_data = SubmissionData::from_html(site, "file:///page.html".to_string(), String::new()).unwrap();
+            assert_eq!(submission_data.submission_type.to_string().to_ascii_lowercase(), site);
+        }
+        let error = SubmissionData::from_html("codeforces.com", "file:///page.html".to_string(), String::new()).unwrap_err();
+        assert_eq!(error.to_string(), "Unknown site \"codeforces.com\", it can be usaco, codeforces, atcoder, cses, kattis, oj.uz");
+    }
+
+    #[test]
+    fn data_of_the_wrong_kind_is_an_error() {
+        let submission_data = saved_page("codeforces", "file:///codeforces_data_link.html", "");
+        let error = submission_data.get_data_link().unwrap_err();
+        assert_eq!(error.to_string(), "Codeforces has no data link, the examples are read from the problem page");
+        let submission_data = saved_page("kattis", "https://open.kattis.com/problems/hello", "");
+        assert!(submission_data.get_data(&"in".to_string(), &"ans".to_string()).unwrap_err().to_string().contains("test data zip"));
+        assert!(saved_page("usaco", "file:///usaco_data.html", "").get_data(&"in".to_string(), &"out".to_string()).is_err());
+    }
+
+    fn usaco_sample(kind: &str, sample: &str) -> String {
+        format!("<h4>SAMPLE {}:</h4>\r\n<pre class=\"in\">\r\n{}</pre>\r\n", kind, sample)
+    }
+
+    #[test]
+    fn usaco_examples_pair_inputs_with_outputs() {
+        let one = usaco_sample("INPUT", "3\r\n1 &lt; 2\r\n") + &usaco_sample("OUTPUT", "YES\r\n");
+        assert_eq!(usaco_examples(&one).unwrap(), [("3\n1 < 2\n".to_string(), "YES\n".to_string())]);
+        // Newer pages number the samples and have explanations between them
+        let mut three = String::new();
+        for i in 1..=3 {
+            three += &usaco_sample(&format!("INPUT {}", i), &format!("{} 2 < 5 &amp; 1\n", i));
+            three += &usaco_sample(&format!("OUTPUT {}", i), &format!("{}\n", i * 10));
+            three += "<p>SCORING: the &quot;answer&quot; is above.</p>";
+        }
+        let examples = usaco_examples(&three).unwrap();
+        assert_eq!(examples.len(), 3);
+        assert_eq!(examples[1], ("2 2 < 5 & 1\n".to_string(), "20\n".to_string()));
+        // An input without an output is skipped instead of taking the next output
+        let two = usaco_sample("INPUT", "1\n") + &usaco_sample("INPUT", "2\n") + &usaco_sample("OUTPUT", "4\n");
+        assert_eq!(usaco_examples(&two).unwrap(), [("2\n".to_string(), "4\n".to_string())]);
+        assert!(usaco_examples("<h4>INPUT FORMAT (file cowjump.in):</h4>").unwrap().is_empty());
+    }
+
+    #[test]
+    fn usaco_examples_are_written_to_the_data_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let (input_extension, output_extension) = ("in".to_string(), "ans".to_string());
+        let page = usaco_sample("INPUT", "1 2\n") + &usaco_sample("OUTPUT", "3\n") + &usaco_sample("INPUT", "4 5\n") + &usaco_sample("OUTPUT", "9\n");
+        let submission_data = SubmissionData::from_html("usaco", "file:///usaco_examples.html".to_string(), page).unwrap();
+        submission_data.write_usaco_examples(dir.path().to_path_buf(), &input_extension, &output_extension).unwrap();
+        assert_eq!(fs::read_to_string(dir.path().join("example1.in")).unwrap(), "1 2\n");
+        assert_eq!(fs::read_to_string(dir.path().join("example2.ans")).unwrap(), "9\n");
+        // A page without samples still adds the test data, just without examples
+        let dir = tempfile::tempdir().unwrap();
+        let page = "<h4>INPUT FORMAT (file cowjump.in):</h4>".to_string();
+        let submission_data = SubmissionData::from_html("usaco", "file:///usaco_no_examples.html".to_string(), page).unwrap();
+        submission_data.write_usaco_examples(dir.path().to_path_buf(), &input_extension, &output_extension).unwrap();
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn io_type_of_files_and_std() {
+        assert!(matches!(io_type("stdin", "in", true), Ok(IOType::STD)));
+        assert!(matches!(io_type("stdout", "out", false), Ok(IOType::STD)));
+        assert!(matches!(io_type("problem", "in", true), Ok(IOType::FILE(path)) if path == Path::new("problem.in")));
+        assert!(matches!(io_type("answer", "out", false), Ok(IOType::FILE(path)) if path == Path::new("answer.out")));
+        // A name with a dot keeps it, instead of the extension replacing the part after the dot
+        assert!(matches!(io_type("a.b", "in", true), Ok(IOType::FILE(path)) if path == Path::new("a.b.in")));
+    }
+
+    #[test]
+    fn io_type_rejects_std_on_the_wrong_side() {
+        assert!(io_type("stdout", "in", true).is_err());
+        assert!(io_type("stdin", "out", false).is_err());
+        assert!(is_std_io("stdin") && is_std_io("stdout") && !is_std_io("problem"));
+    }
+}
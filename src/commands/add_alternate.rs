@@ -0,0 +1,61 @@
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use clap::Args;
+
+use crate::{
+    data_dir,
+    error::{Context, Error},
+    test_data::{CaseData, Test},
+};
+
+#[derive(Debug, Args)]
+pub struct AddAlternateArgs {
+    #[arg(help = "The name of the test")]
+    test_name: String,
+
+    #[arg(help = "The case the output is accepted for")]
+    case_name: String,
+
+    #[arg(help = "Another correct output of the case, runs pass if the output matches it or the expected output")]
+    output_file: PathBuf,
+}
+
+impl AddAlternateArgs {
+    pub fn run(&self, tests: &mut HashMap<String, Test>) -> Result<(), Error> {
+        let test = match tests.get_mut(&self.test_name) {
+            Some(test) => test,
+            None => return Err(Test::not_found(&self.test_name, tests)),
+        };
+        let test_dir = data_dir::get()?.join("tests").join(&self.test_name);
+        test.fill_cases(test_dir).context("Failed to load cases of the test")?;
+        let compress = test.is_compressed();
+        let Some(case) = test.cases.get_mut(&self.case_name) else {
+            return Err(test.case_not_found(&self.case_name).into());
+        };
+        let Some(output_path) = case.output.as_ref().and_then(|output| output.path()).map(|path| path.to_path_buf()) else {
+            return Err(format!("Case \"{}\" has no expected output, so it isn't judged", self.case_name).into());
+        };
+        let output = fs::read(&self.output_file).context(format!("Failed to read {:?}", self.output_file))?;
+        if case.get_output()?.is_some_and(|expected| expected.trim_ascii() == output.trim_ascii()) {
+            return Err(format!("{:?} is the same as the expected output of case \"{}\"", self.output_file, self.case_name).into());
+        }
+        if let Some(alternate) = case.matching_alternate(&output)? {
+            return Err(format!(
+                "{:?} is the same as alternate output {} of case \"{}\"",
+                self.output_file, alternate, self.case_name
+            )
+            .into());
+        }
+        case.alternates.push(CaseData::Bytes(output));
+        case.write_alternates(&output_path, compress, &self.case_name)?;
+        println!(
+            "Case \"{}\" of \"{}\" has {} accepted outputs now",
+            self.case_name,
+            self.test_name,
+            case.alternates.len() + 1
+        );
+        test.metadata.data_size_bytes = Some(test.data_size());
+        test.cases.clear();
+        Ok(())
+    }
+}
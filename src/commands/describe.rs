@@ -0,0 +1,37 @@
+use std::collections::HashMap;
+
+use clap::Args;
+
+use crate::{error::Error, test_data::Test};
+
+#[derive(Args, Debug)]
+pub struct DescribeArgs {
+    #[arg(help = "The name of the test")]
+    test_name: String,
+
+    #[arg(help = "The new description, an empty string removes it. Prints the current description if not given")]
+    description: Option<String>,
+}
+
+impl DescribeArgs {
+    // Returns if the description changed, so the tests only have to be written then
+    pub fn run(&self, tests: &mut HashMap<String, Test>) -> Result<bool, Error> {
+        let test = match tests.get_mut(&self.test_name) {
+            Some(test) => test,
+            None => return Err(Test::not_found(&self.test_name, tests)),
+        };
+        let Some(description) = &self.description else {
+            println!("{}", test.description.as_deref().unwrap_or("Test has no description"));
+            return Ok(false);
+        };
+        let description = description.trim();
+        if description.is_empty() {
+            test.description = None;
+            println!("Removed description of \"{}\"", self.test_name);
+        } else {
+            test.description = Some(description.to_string());
+            println!("Set description of \"{}\" to \"{}\"", self.test_name, description);
+        }
+        Ok(true)
+    }
+}
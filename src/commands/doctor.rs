@@ -0,0 +1,205 @@
+use std::{
+    collections::HashMap,
+    io::{self, IsTerminal, Write},
+    path::Path,
+};
+
+use clap::Args;
+
+use crate::{
+    color,
+    commands::{add::format_file_size, list::dir_size},
+    data_dir,
+    error::{Context, Error},
+    paths,
+    test_data::Test,
+};
+
+#[derive(Args, Debug)]
+pub struct DoctorArgs {
+    #[arg(long, help = "Remove tests whose directory is missing")]
+    prune: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "delete_unknown",
+        help = "Add directories in tests/ that no test uses as tests(With stdin and stdout IO, since it can't be known)"
+    )]
+    register: bool,
+
+    #[arg(long, help = "Delete directories in tests/ that no test uses")]
+    delete_unknown: bool,
+
+    #[arg(short, long, requires = "delete_unknown", help = "Don't ask before deleting the directories")]
+    yes: bool,
+}
+
+// remove renames a test's directory to .<name>.removing before deleting it, so an interrupted remove leaves it behind
+fn is_removal_leftover(name: &str) -> bool {
+    name.starts_with('.') && name.ends_with(".removing")
+}
+
+impl DoctorArgs {
+    // Returns if any tests were added or removed, so test.json only has to be written then
+    pub fn run(&self, tests: &mut HashMap<String, Test>) -> Result<bool, Error> {
+        self.check(tests, &data_dir::get()?.join("tests"))
+    }
+
+    fn check(&self, tests: &mut HashMap<String, Test>, tests_dir: &Path) -> Result<bool, Error> {
+        let mut changed = false;
+        let mut dangling = tests
+            .keys()
+            .filter(|name| !tests_dir.join(name).is_dir())
+            .cloned()
+            .collect::<Vec<String>>();
+        dangling.sort();
+        for name in &dangling {
+            if self.prune {
+                tests.remove(name);
+                changed = true;
+                println!("Removed \"{}\", its directory is missing", name);
+            } else {
+                println!("{}: The directory of \"{}\" is missing", color::yellow("WARNING"), name);
+            }
+        }
+        let mut unknown = vec![];
+        let mut leftovers = vec![];
+        if tests_dir.is_dir() {
+            let entries = tests_dir.read_dir().context(format!("Failed to read {:?}", tests_dir))?;
+            for entry in entries {
+                let entry = entry.context(format!("Failed to read entry in {:?}", tests_dir))?;
+                let name = entry.file_name().to_string_lossy().to_string();
+                if !entry.path().is_dir() || tests.contains_key(&name) {
+                    continue;
+                }
+                if is_removal_leftover(&name) {
+                    leftovers.push(name);
+                } else {
+                    unknown.push(name);
+                }
+            }
+        }
+        // The test was already removed from test.json, so these are never registered
+        leftovers.sort();
+        for name in &leftovers {
+            let dir = tests_dir.join(name);
+            if self.register || self.delete_unknown {
+                paths::remove_dir_all(&dir).context(format!("Failed to delete {:?}", dir))?;
+                println!("Deleted {:?}, left by a remove that was interrupted", dir);
+            } else {
+                println!("{}: {:?} was left by a remove that was interrupted", color::yellow("WARNING"), dir);
+            }
+        }
+        unknown.sort();
+        if self.delete_unknown && !unknown.is_empty() && !self.confirm_delete(&unknown, tests_dir)? {
+            println!("No directories were deleted");
+            return Ok(changed);
+        }
+        for name in &unknown {
+            let dir = tests_dir.join(name);
+            if self.delete_unknown {
+                paths::remove_dir_all(&dir).context(format!("Failed to delete {:?}", dir))?;
+                println!("Deleted {:?}", dir);
+            } else if self.register {
+                // A directory without cases is reported, but kept in case it has something worth saving
+                match Test::from_stored_dir(&dir) {
+                    Ok(mut test) => {
+                        println!("Registered \"{}\" with {} cases", name, test.cases.len());
+                        test.cases.clear();
+                        tests.insert(name.clone(), test);
+                        changed = true;
+                    }
+                    Err(e) => eprintln!("{}: Failed to register {:?}: {}", color::red("ERROR"), dir, e),
+                }
+            } else {
+                println!("{}: {:?} isn't used by any test", color::yellow("WARNING"), dir);
+            }
+        }
+        if dangling.is_empty() && unknown.is_empty() && leftovers.is_empty() {
+            println!("No problems found");
+        }
+        if !dangling.is_empty() && !self.prune {
+            println!("Run with --prune to remove the tests without a directory");
+        }
+        if (!unknown.is_empty() || !leftovers.is_empty()) && !self.register && !self.delete_unknown {
+            println!("Run with --register to add the unused directories as tests, or --delete-unknown to delete them");
+        }
+        Ok(changed)
+    }
+
+    // Like remove, scripts have to pass --yes since the directories aren't in test.json to get back
+    fn confirm_delete(&self, unknown: &[String], tests_dir: &Path) -> Result<bool, Error> {
+        if self.yes {
+            return Ok(true);
+        }
+        if !io::stdin().is_terminal() {
+            return Err(format!("{} directories aren't used by any test, pass --yes to delete them", unknown.len()).into());
+        }
+        let bytes = unknown.iter().map(|name| dir_size(&tests_dir.join(name))).sum::<u64>();
+        eprintln!("{}", unknown.join("\n"));
+        eprint!("This will delete {} directories ({}). Continue? [y/N]: ", unknown.len(), format_file_size(bytes as usize));
+        io::stderr().flush().context("Failed to flush stderr")?;
+        let mut line = String::new();
+        io::stdin().read_line(&mut line).context("Failed to read from stdin")?;
+        Ok(line.trim().eq_ignore_ascii_case("y"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_data::TestCase;
+    use std::fs;
+
+    fn args(prune: bool, register: bool, delete_unknown: bool) -> DoctorArgs {
+        DoctorArgs {
+            prune,
+            register,
+            delete_unknown,
+            yes: true,
+        }
+    }
+
+    // "stored" has its directory, "missing" doesn't, "orphan" isn't a test, and ".gone.removing" was left by remove
+    fn setup() -> (tempfile::TempDir, HashMap<String, Test>) {
+        let tests_dir = tempfile::tempdir().unwrap();
+        for dir in ["stored", "orphan", ".gone.removing"] {
+            fs::create_dir_all(tests_dir.path().join(dir)).unwrap();
+            fs::write(tests_dir.path().join(dir).join("1.in"), "1\n").unwrap();
+            fs::write(tests_dir.path().join(dir).join("1.out"), "1\n").unwrap();
+        }
+        let tests = ["stored", "missing"]
+            .into_iter()
+            .map(|name| (name.to_string(), Test::adhoc(TestCase::new(vec![], None))))
+            .collect();
+        (tests_dir, tests)
+    }
+
+    #[test]
+    fn only_warns_without_flags() {
+        let (tests_dir, mut tests) = setup();
+        assert!(!args(false, false, false).check(&mut tests, tests_dir.path()).unwrap());
+        assert_eq!(tests.len(), 2);
+        assert!(tests_dir.path().join("orphan").is_dir() && tests_dir.path().join(".gone.removing").is_dir());
+    }
+
+    #[test]
+    fn register_skips_removal_leftovers() {
+        let (tests_dir, mut tests) = setup();
+        assert!(args(true, true, false).check(&mut tests, tests_dir.path()).unwrap());
+        let mut names = tests.keys().map(String::as_str).collect::<Vec<&str>>();
+        names.sort();
+        assert_eq!(names, ["orphan", "stored"]);
+        assert!(tests_dir.path().join("orphan").is_dir());
+        assert!(!tests_dir.path().join(".gone.removing").exists());
+    }
+
+    #[test]
+    fn delete_unknown_removes_unused_dirs() {
+        let (tests_dir, mut tests) = setup();
+        assert!(!args(false, false, true).check(&mut tests, tests_dir.path()).unwrap());
+        assert_eq!(tests.len(), 2);
+        assert!(tests_dir.path().join("stored").is_dir());
+        assert!(!tests_dir.path().join("orphan").exists() && !tests_dir.path().join(".gone.removing").exists());
+    }
+}
@@ -0,0 +1,91 @@
+use std::{fs, path::Path};
+
+use clap::Args;
+
+use crate::{handle_error, paths};
+
+#[derive(Debug, Args)]
+pub struct DuplicateArgs {
+    #[arg(help = "The name of the test to copy")]
+    pub(crate) test_name: String,
+    #[arg(help = "The name of the new copy of the test")]
+    pub(crate) new_name: String,
+}
+
+// Copied into <to>.partial and moved to to once every file is copied, so a failed copy doesn't leave a directory without a test
+pub fn copy_test_dir(from: &Path, to: &Path) -> Result<(u64, u64), String> {
+    let mut partial_name = to.file_name().unwrap_or_default().to_os_string();
+    partial_name.push(".partial");
+    let partial = to.with_file_name(partial_name);
+    // Left by a copy that was killed partway through
+    if partial.exists() {
+        handle_error!(paths::remove_dir_all(&partial), format!("Failed to remove {:?}", partial));
+    }
+    let copied = copy_dir(from, &partial).and_then(|copied| {
+        handle_error!(fs::rename(&partial, to), format!("Failed to move {:?} to {:?}", partial, to));
+        Ok(copied)
+    });
+    if copied.is_err() {
+        let _ = paths::remove_dir_all(&partial);
+    }
+    copied
+}
+
+// Copies every file in from into to(Recursively), returns the amount of files and bytes copied
+pub fn copy_dir(from: &Path, to: &Path) -> Result<(u64, u64), String> {
+    handle_error!(fs::create_dir_all(to), format!("Failed to create directory {:?}", to));
+    let entries = handle_error!(from.read_dir(), format!("Failed to read directory {:?}", from));
+    let mut files = 0;
+    let mut bytes = 0;
+    for entry in entries {
+        let entry = handle_error!(entry, format!("Failed to read entry in directory {:?}", from));
+        let path = entry.path();
+        let new_path = to.join(entry.file_name());
+        if path.is_dir() {
+            let (dir_files, dir_bytes) = copy_dir(&path, &new_path)?;
+            files += dir_files;
+            bytes += dir_bytes;
+        } else {
+            bytes += handle_error!(fs::copy(&path, &new_path), format!("Failed to copy file {:?}", path));
+            files += 1;
+        }
+    }
+    Ok((files, bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copies_are_moved_into_place_when_done() {
+        let dir = tempfile::tempdir().unwrap();
+        let (from, to) = (dir.path().join("a"), dir.path().join("b"));
+        fs::create_dir_all(from.join("checker")).unwrap();
+        fs::write(from.join("1.in"), "1 2\n").unwrap();
+        fs::write(from.join("checker").join("check.cpp"), "int main() {}").unwrap();
+        assert_eq!(copy_test_dir(&from, &to).unwrap(), (2, 17));
+        assert_eq!(fs::read_to_string(to.join("checker").join("check.cpp")).unwrap(), "int main() {}");
+        assert!(!dir.path().join("b.partial").exists());
+    }
+
+    #[test]
+    fn failed_copies_leave_nothing_behind() {
+        let dir = tempfile::tempdir().unwrap();
+        let (from, to) = (dir.path().join("a"), dir.path().join("b"));
+        // Like a copy that was killed before
+        fs::create_dir(dir.path().join("b.partial")).unwrap();
+        fs::write(dir.path().join("b.partial").join("stale.in"), "stale").unwrap();
+        assert!(copy_test_dir(&from, &to).is_err());
+        assert!(!to.exists() && !dir.path().join("b.partial").exists());
+
+        // The copy can't be moved over a directory that isn't empty
+        fs::create_dir_all(&from).unwrap();
+        fs::write(from.join("1.in"), "1\n").unwrap();
+        fs::create_dir(&to).unwrap();
+        fs::write(to.join("other.in"), "other").unwrap();
+        assert!(copy_test_dir(&from, &to).is_err());
+        assert!(!dir.path().join("b.partial").exists());
+        assert_eq!(fs::read_dir(&to).unwrap().count(), 1);
+    }
+}
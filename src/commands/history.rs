@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+
+use clap::Args;
+use tabled::{Table, Tabled};
+
+use crate::{
+    data_dir,
+    error::{Context, Error},
+    run_history::RunHistory,
+    test_data::Test,
+};
+
+#[derive(Debug, Args)]
+pub struct HistoryArgs {
+    #[arg(help = "The name of the test to show past runs for")]
+    test: String,
+
+    #[arg(short, long, help = "Only show the newest N runs")]
+    limit: Option<usize>,
+
+    #[arg(short, long, help = "Show the results of every case for a single run(The number in the Run column)")]
+    run: Option<u32>,
+
+    #[arg(short, long, requires = "run", help = "Show the stored details for a single case of the run given with --run")]
+    case: Option<String>,
+}
+
+#[derive(Tabled, Debug)]
+struct RunTable {
+    #[tabled(rename = "Run")]
+    id: u32,
+    #[tabled(rename = "Date")]
+    date: String,
+    #[tabled(rename = "File")]
+    file: String,
+    #[tabled(rename = "Passed")]
+    passed: String,
+    #[tabled(rename = "Slowest Case")]
+    slowest: String,
+    #[tabled(rename = "Verdict")]
+    verdict: String,
+}
+
+#[derive(Tabled, Debug)]
+struct RunCaseTable {
+    #[tabled(rename = "Case Name")]
+    name: String,
+    #[tabled(rename = "Verdict")]
+    verdict: String,
+    #[tabled(rename = "Time")]
+    time: String,
+}
+
+impl HistoryArgs {
+    pub fn run(&self, tests: &HashMap<String, Test>) -> Result<(), Error> {
+        if !tests.contains_key(&self.test) {
+            return Err(Test::not_found(&self.test, tests));
+        }
+        let data_dir = data_dir::get()?;
+        let test_dir = data_dir.join("tests").join(&self.test);
+        let history = RunHistory::load(&test_dir)?;
+        if history.is_empty() {
+            println!("Test \"{}\" hasn't been run yet", self.test);
+            return Ok(());
+        }
+        if let Some(run_id) = self.run {
+            let run = history
+                .get_run(run_id)
+                .context(format!("Run {} isn't in the history of test \"{}\"(Old runs are removed)", run_id, self.test))?;
+            if let Some(case_name) = &self.case {
+                let case = run
+                    .cases
+                    .iter()
+                    .find(|case| &case.name == case_name)
+                    .context(format!("Test case with name \"{}\" wasn't run in run {}", case_name, run_id))?;
+                println!("Run {} ({}), case {}", run.id, run.timestamp.format("%Y-%m-%d %H:%M:%S"), case.name);
+                println!("Verdict: {}", case.verdict);
+                println!("Time: {} milliseconds", case.time_ms);
+                if let Some(details) = &case.details {
+                    println!("{}", details);
+                }
+                if let Some(output) = &case.output {
+                    println!("Program output:");
+                    println!("{}", output.lines().map(|l| format!("\t{}", l)).collect::<Vec<String>>().join("\n"));
+                }
+                return Ok(());
+            }
+            println!(
+                "Run {} ({}) of {}: {}/{} passed",
+                run.id,
+                run.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                run.file.to_string_lossy(),
+                run.passed(),
+                run.cases.len()
+            );
+            let case_tables = run
+                .cases
+                .iter()
+                .map(|case| RunCaseTable {
+                    name: case.name.clone(),
+                    verdict: case.verdict.to_string(),
+                    time: format!("{} ms", case.time_ms),
+                })
+                .collect::<Vec<RunCaseTable>>();
+            println!("{}", Table::new(case_tables));
+            return Ok(());
+        }
+        let run_tables = history
+            .newest_first()
+            .take(self.limit.unwrap_or(usize::MAX))
+            .map(|run| RunTable {
+                id: run.id,
+                date: run.timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
+                file: run.file.to_string_lossy().to_string(),
+                passed: format!("{}/{}", run.passed(), run.cases.len()),
+                slowest: match run.slowest() {
+                    Some(case) => format!("{} ({} ms)", case.name, case.time_ms),
+                    None => "None".to_string(),
+                },
+                verdict: run.verdict().to_string(),
+            })
+            .collect::<Vec<RunTable>>();
+        println!("{}", Table::new(run_tables));
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use std::{
+    collections::HashMap,
+    env,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use clap::Args;
+
+use crate::{
+    error::{Context, Error},
+    handle_error,
+    project_config::{ProjectConfig, PROJECT_CONFIG_FILE_NAME},
+    test_data::Test,
+};
+
+#[derive(Debug, Args)]
+pub struct InitArgs {
+    #[arg(help = "The name of the test `cp-tester run` should default to, if not given you will be asked to choose one")]
+    pub test: Option<String>,
+
+    #[arg(short, long, help = "The file `cp-tester run` should default to(Relative to the current directory)")]
+    pub file: Option<PathBuf>,
+
+    #[arg(long, value_parser=["20","17","14","11"], help = "The C++ version `cp-tester run` should default to")]
+    pub cpp_ver: Option<String>,
+}
+
+impl InitArgs {
+    pub fn run(&self, tests: &HashMap<String, Test>) -> Result<(), Error> {
+        let test = match &self.test {
+            Some(test) => {
+                if !tests.contains_key(test) {
+                    return Err(Test::not_found(test, tests));
+                }
+                test.clone()
+            }
+            None => choose_test(tests)?,
+        };
+        let file = match &self.file {
+            Some(file) => Some(file.clone()),
+            None => {
+                let file = prompt("Solution file(Leave empty to pass --file when running): ")?;
+                if file.is_empty() {
+                    None
+                } else {
+                    Some(PathBuf::from(file))
+                }
+            }
+        };
+        let current_dir = env::current_dir().context("Failed to get current directory")?;
+        if current_dir.join(PROJECT_CONFIG_FILE_NAME).exists() {
+            println!("Overwriting existing {}", PROJECT_CONFIG_FILE_NAME);
+        }
+        let project_config = ProjectConfig {
+            test: Some(test),
+            file,
+            cpp_ver: self.cpp_ver.clone(),
+            dir: current_dir.clone(),
+        };
+        let path = project_config.write(&current_dir)?;
+        println!("Wrote project config to {:?}, `cp-tester run` in this directory will now use it", path);
+        Ok(())
+    }
+}
+
+fn choose_test(tests: &HashMap<String, Test>) -> Result<String, String> {
+    if tests.is_empty() {
+        return Err("There are no tests to choose from, add one first".to_string());
+    }
+    let mut test_names = tests.keys().collect::<Vec<&String>>();
+    test_names.sort();
+    for (i, name) in test_names.iter().enumerate() {
+        println!("{}: {}", i + 1, name);
+    }
+    let choice = prompt("Choose a test(Number or name): ")?;
+    if let Ok(index) = choice.parse::<usize>() {
+        if index >= 1 && index <= test_names.len() {
+            return Ok(test_names[index - 1].clone());
+        }
+    }
+    if tests.contains_key(&choice) {
+        return Ok(choice);
+    }
+    Err(format!("\"{}\" is not a test number or test name", choice))
+}
+
+fn prompt(message: &str) -> Result<String, String> {
+    print!("{}", message);
+    handle_error!(io::stdout().flush(), "Failed to flush stdout");
+    let mut line = String::new();
+    handle_error!(io::stdin().read_line(&mut line), "Failed to read from stdin");
+    Ok(line.trim().to_string())
+}
@@ -1,12 +1,27 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fs, path::Path};
 
-use clap::{Args, Subcommand};
+use chrono::{DateTime, Local, TimeDelta};
+use clap::{Args, Subcommand, ValueEnum};
+use serde::Serialize;
 use tabled::{
     settings::{locator::ByColumnName, Disable},
     Table, Tabled,
 };
 
-use crate::{handle_option, test_data::Test, DEFAULT_FOLDER_NAME};
+use crate::{
+    commands::{
+        add::{format_file_size, SubmissionType},
+        note,
+    },
+    color,
+    config::Config,
+    data_dir,
+    error::{Context, Error},
+    handle_error, handle_option,
+    names::NameFilter,
+    run_history::RunHistory,
+    test_data::{Test, COMPRESSED_EXTENSION},
+};
 
 //list command just lists all test cases, sort by name
 //list test command lists all test cases for a specific test, sort by test_case name, --show-input, --show-output, both true by default --cases to specify a test case or multiple test cases
@@ -16,15 +31,84 @@ pub struct ListArgs {
     #[command(subcommand)]
     pub command: Option<ListCommands>,
 
-    #[arg(long, help = "Show input and output types, as well as file names(If applicable), for each test")]
+    #[arg(long, help = "Show input and output types, as well as file names(If applicable), and the time and memory limits for each test")]
     show_io: bool,
 
     #[arg(
         short,
         long,
-        help = "Pass a submission type (usaco, codeforces, or atcoder) and only tests with that submisison type will be listed"
+        value_enum,
+        ignore_case = true,
+        help = "Only list tests with this submission type, none lists the tests added from folders or packages"
     )]
-    submission_type: Option<String>,
+    submission_type: Option<SubmissionTypeFilter>,
+
+    #[arg(long, help = "Show the problem link for each test(If it has one)")]
+    link: bool,
+
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "Only list tests that have this tag, can be passed multiple times(Or comma separated) to only list tests that have all of the tags"
+    )]
+    tag: Vec<String>,
+
+    #[arg(
+        long,
+        help = "Show when each test was added, where it was added from, the size of its cases, how much space its directory takes, its largest case, and if the archive it was downloaded from was kept"
+    )]
+    details: bool,
+
+    #[arg(
+        long,
+        value_parser = ["name", "added", "cases", "size", "type"],
+        default_value = "name",
+        help = "Sort tests by name, when they were added(Newest first), amount of cases or size of their directory(Largest first), or submission type, ties are sorted by name"
+    )]
+    sort: String,
+
+    #[arg(long, help = "Reverse the order of --sort, ties are still sorted by name")]
+    reverse: bool,
+
+    #[arg(long, help = "Print the tests as a JSON array instead of a table, for editor plugins and scripts")]
+    json: bool,
+
+    #[arg(long, help = "Show the amount of cases and the size of the directory of each test, only the file metadata is read")]
+    show_stats: bool,
+
+    #[arg(long, help = "Only list tests whose name contains this(Case-insensitive), or matches it if it is a glob with * or ?")]
+    filter: Option<String>,
+
+    #[arg(long, help = "Only list tests whose name matches this regex")]
+    regex: Option<String>,
+
+    #[arg(long, help = "Only list tests whose last run had failed or timed out cases")]
+    failed_only: bool,
+
+    #[arg(
+        long,
+        value_parser = parse_age,
+        help = "Only list tests added more than this long ago, like 90d, 12h or 2w(Tests added before dates were recorded are left out)"
+    )]
+    older_than: Option<TimeDelta>,
+
+    #[arg(
+        long,
+        global = true,
+        value_parser = ["table", "csv", "markdown"],
+        default_value = "table",
+        conflicts_with = "json",
+        help = "Print the tests or cases as a table, as CSV, or as a GitHub Markdown table, with the same columns as the table"
+    )]
+    format: String,
+
+    #[arg(
+        long,
+        global = true,
+        conflicts_with_all = ["json", "format"],
+        help = "Print only the names of the tests or cases, one per line, for scripts and shell completion"
+    )]
+    names_only: bool,
 }
 
 #[derive(Tabled, Debug)]
@@ -35,35 +119,97 @@ struct TestTable {
     description: String,
     #[tabled(rename = "Submission Type")]
     submission_type: String,
+    #[tabled(rename = "Tags")]
+    tags: String,
+    #[tabled(rename = "Notes")]
+    notes: String,
+    #[tabled(rename = "Last Result")]
+    last_result: String,
+    #[tabled(rename = "Cases")]
+    cases: String,
+    #[tabled(rename = "Size")]
+    size: String,
     #[tabled(rename = "Input Type")]
     input_type: String,
     #[tabled(rename = "Output Type")]
     output_type: String,
+    #[tabled(rename = "Time Limit")]
+    time_limit: String,
+    #[tabled(rename = "Memory Limit")]
+    memory_limit: String,
+    #[tabled(rename = "Link")]
+    link: String,
+    #[tabled(rename = "Added")]
+    added: String,
+    #[tabled(rename = "Source")]
+    source: String,
+    #[tabled(rename = "Data Size")]
+    data_size: String,
+    #[tabled(rename = "Disk Size")]
+    disk_size: String,
+    #[tabled(rename = "Largest Case")]
+    largest_case: String,
+    #[tabled(rename = "Archive")]
+    archive: String,
 }
 
 #[derive(Tabled, Debug)]
-struct CaseTable<'a> {
+struct CaseTable {
     #[tabled(rename = "Case Name")]
     case_name: String,
     #[tabled(rename = "Input File(In Test Folder)")]
     input_file: String,
     #[tabled(rename = "Output File(In Test Folder)")]
     output_file: String,
+    // From the directory listing, so the files aren't read, sizes of compressed cases are of the uncompressed data
+    #[tabled(rename = "Input Size(Bytes)")]
+    input_size: u64,
+    #[tabled(rename = "Output Size(Bytes)")]
+    output_size: String,
+    #[tabled(rename = "Last Verdict")]
+    last_verdict: String,
     #[tabled(rename = "Input")]
-    input: &'a str,
+    input: String,
     #[tabled(rename = "Output")]
-    output: &'a str,
+    output: String,
+}
+
+// Schema of --json output, fields should only be added, not renamed or removed
+#[derive(Serialize, Debug)]
+struct TestJson {
+    name: String,
+    description: Option<String>,
+    submission_type: Option<String>,
+    link: Option<String>,
+    input_io: String,
+    output_io: String,
+    // None if the cases couldn't be found
+    case_count: Option<usize>,
+    tags: Vec<String>,
+    added_at: Option<DateTime<Local>>,
+}
+
+#[derive(Serialize, Debug)]
+struct CaseJson {
+    name: String,
+    input_len: u64,
+    // None for cases without an expected output
+    output_len: Option<u64>,
+    // Other accepted outputs, see `cp-tester add-alternate`
+    alternate_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    input: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output: Option<String>,
 }
 
 impl TestTable {
-    pub fn from_tests(tests: &HashMap<String, Test>, submission_type: &Option<String>) -> Vec<TestTable> {
+    // The directories are only measured for --details and --show-stats
+    pub fn from_tests(selected: &[(&String, &Test)], details: bool, show_stats: bool) -> Result<Vec<TestTable>, String> {
+        let data_dir = data_dir::get()?;
+        let tests_dir = data_dir.join("tests");
         let mut table_data = vec![];
-        for (name, test) in tests {
-            if let Some(submission_type) = submission_type {
-                if submission_type != &test.get_submission_type() {
-                    continue;
-                }
-            }
+        for &(name, test) in selected {
             let (input_type, output_type) = test.get_io_types();
             table_data.push(TestTable {
                 name: name.clone(),
@@ -74,38 +220,116 @@ impl TestTable {
                         None => "None".to_string(),
                     }
                 },
+                tags: if test.tags.is_empty() { "None".to_string() } else { test.tags.join(", ") },
+                notes: if note::has_notes(name)? { "Yes" } else { "No" }.to_string(),
+                last_result: last_result(&tests_dir.join(name), name),
+                cases: if show_stats { count_cases(test, &tests_dir.join(name)).to_string() } else { String::new() },
+                size: if show_stats { format_file_size(dir_size(&tests_dir.join(name)) as usize) } else { String::new() },
                 input_type,
                 output_type,
+                time_limit: test.time_limit_ms.map_or("None".to_string(), |time_limit| format!("{} ms", time_limit)),
+                memory_limit: test.memory_limit_mb.map_or("None".to_string(), |memory_limit| format!("{} MB", memory_limit)),
+                link: match &test.submission_data {
+                    Some(submission_data) => submission_data.link.clone(),
+                    None => "None".to_string(),
+                },
+                added: test
+                    .metadata
+                    .added_at
+                    .map_or("Unknown".to_string(), |added_at| added_at.format("%Y-%m-%d %H:%M").to_string()),
+                source: test.metadata.source.clone().unwrap_or("Unknown".to_string()),
+                data_size: test
+                    .metadata
+                    .data_size_bytes
+                    .map_or("Unknown".to_string(), |data_size| format_file_size(data_size as usize)),
+                archive: if data_dir::archive_path(name)?.is_file() { "Kept" } else { "None" }.to_string(),
+                disk_size: if details { format_file_size(dir_size(&tests_dir.join(name)) as usize) } else { String::new() },
+                largest_case: if details { load_largest_case(test, &tests_dir.join(name)) } else { String::new() },
+            });
+        }
+        Ok(table_data)
+    }
+}
+
+// The cases given with --cases, or all of them
+fn selected_case_names<'a>(test: &'a Test, case_names: &'a [String]) -> Result<Vec<&'a String>, String> {
+    let all_cases = test.get_sorted_case_names();
+    if case_names.is_empty() {
+        return Ok(all_cases);
+    }
+    if let Some(case_name) = case_names.iter().find(|case_name| !all_cases.contains(case_name)) {
+        return Err(test.case_not_found(case_name));
+    }
+    Ok(case_names.iter().collect())
+}
+
+impl TestJson {
+    // Only the file names of the cases are read to count them
+    fn from_tests(selected: &[(&String, &Test)]) -> Result<Vec<TestJson>, String> {
+        let tests_dir = data_dir::get()?.join("tests");
+        let mut json_data = vec![];
+        for &(name, test) in selected {
+            let mut loaded = test.clone();
+            let case_count = loaded.fill_cases(tests_dir.join(name)).ok().map(|_| loaded.cases.len());
+            let (input_io, output_io) = test.get_io_types();
+            json_data.push(TestJson {
+                name: name.clone(),
+                description: test.description.clone(),
+                submission_type: test.submission_data.as_ref().map(|submission_data| submission_data.submission_type.to_string()),
+                link: test.submission_data.as_ref().map(|submission_data| submission_data.link.clone()),
+                input_io,
+                output_io,
+                case_count,
+                tags: test.tags.clone(),
+                added_at: test.metadata.added_at,
             });
         }
-        table_data.sort_by_key(|x| x.name.clone());
-        table_data
+        Ok(json_data)
     }
 }
 
-impl<'b> CaseTable<'_> {
-    pub fn from_test<'a>(test: &'a Test, case_names: &Vec<String>) -> Result<Vec<CaseTable<'a>>,String> {
-        let all_cases = test.get_sorted_case_names();
+impl CaseJson {
+    // Case data is only read for the contents that are shown
+    fn from_test(test: &Test, case_names: &[String], show_input: bool, show_output: bool) -> Result<Vec<CaseJson>, String> {
+        let mut json_data = vec![];
+        for name in selected_case_names(test, case_names)? {
+            let case = &test.cases[name];
+            json_data.push(CaseJson {
+                name: name.clone(),
+                input_len: case.input.size(),
+                output_len: case.output.as_ref().map(|output| output.size()),
+                alternate_count: case.alternates.len(),
+                input: if show_input { Some(case.display_input()?) } else { None },
+                output: if show_output { case.get_output()?.map(|output| String::from_utf8_lossy(&output).to_string()) } else { None },
+            });
+        }
+        Ok(json_data)
+    }
+}
+
+impl CaseTable {
+    // Case data is only read for the columns that are shown
+    pub fn from_test(test: &Test, test_dir: &Path, args: &ListTestArgs, preview_lines: Option<usize>) -> Result<Vec<CaseTable>, String> {
+        let history = load_history(test_dir, &test_dir.file_name().unwrap_or_default().to_string_lossy());
+        let last_verdicts = history.as_ref().map(RunHistory::last_verdicts);
         let mut table_data = vec![];
-        let mut temp_case_names = vec![];
-        if case_names.is_empty() {
-            temp_case_names.extend(&all_cases)
-        } else {
-            for case in case_names {
-                temp_case_names.push(case);
-            }
-        };
-        let case_names = temp_case_names;
-        for case_name in case_names {
-            if !all_cases.contains(&case_name) {
-                return Err(format!("Test case with name \"{}\" does not exist", case_name));
-            }
+        for case_name in selected_case_names(test, args.cases.as_deref().unwrap_or_default())? {
+            let case = test.cases.get(case_name).unwrap();
+            let (input_file, output_file) = test.case_file_names(case_name, test.is_compressed());
+            // Cases added with --allow-missing-outputs have no output file
+            let output_file = if case.output.is_some() { format!("{}{}", output_file, case.alternates_note()) } else { "(none)".to_string() };
             table_data.push(CaseTable {
                 case_name: case_name.clone(),
-                input_file: format!("{}.{}", case_name, test.input_extension),
-                output_file: format!("{}.{}", case_name, test.output_extension),
-                input: &test.cases.get(case_name).unwrap().input,
-                output: &test.cases.get(case_name).unwrap().output
+                input_file,
+                output_file,
+                input_size: case.input.size(),
+                output_size: case.output.as_ref().map_or("(none)".to_string(), |output| output.size().to_string()),
+                last_verdict: match &last_verdicts {
+                    Some(last_verdicts) => last_verdicts.get(case_name.as_str()).map_or("never run".to_string(), |verdict| verdict.to_string()),
+                    None => "unknown".to_string(),
+                },
+                input: if args.show_input { case.preview_input(preview_lines)? } else { String::new() },
+                output: if args.show_output { case.preview_output(preview_lines)? } else { String::new() },
             });
         }
         Ok(table_data)
@@ -137,42 +361,416 @@ pub struct ListTestArgs {
         help = "The name of the test case to list. \nIf multiple test cases are specified(Use a comma between cases), all of them will be listed. \nIf not specified, all test cases will be listed"
     )]
     cases: Option<Vec<String>>,
+
+    #[arg(long, help = "Print the cases as a JSON array instead of a table, with their contents if --show-input or --show-output are given")]
+    json: bool,
+
+    #[arg(long, help = "Show whole inputs and outputs instead of their first lines(Set with `cp-tester config set-preview-lines`)")]
+    full: bool,
 }
 
 impl ListArgs {
-    pub fn run(&self, tests: &mut HashMap<String, Test>) -> Result<(), String> {
+    pub fn run(&self, tests: &mut HashMap<String, Test>) -> Result<(), Error> {
+        if self.json && self.command.is_none() {
+            let name_filter = NameFilter::new(self.filter.as_deref(), self.regex.as_deref())?;
+            return print_json(&TestJson::from_tests(&self.select_tests(tests, &name_filter))?);
+        }
+        if self.names_only {
+            return self.print_names(tests);
+        }
         if tests.is_empty() {
-            return Err("There are no tests to list".to_string());
+            return Err("There are no tests to list".to_string().into());
         }
         match &self.command {
             Some(ListCommands::TEST(args)) => {
                 let test = match tests.get_mut(&args.test) {
                     Some(test) => test,
-                    None => return Err(format!("Test with name \"{}\" does not exist", &args.test)),
+                    None => return Err(Test::not_found(&args.test, tests)),
                 };
-                let data_dir = handle_option!(
-                    dirs::data_local_dir(),
-                    "Failed to get data directory, not sure why this should happen, look into dirs::data_local_dir() to find more about error"
-                );
-                let test_dir = data_dir.join(DEFAULT_FOLDER_NAME).join("tests").join(&args.test);
-                test.fill_cases(test_dir)?;
-                let case_tables = CaseTable::from_test(test, args.cases.as_ref().unwrap_or(&vec![]))?;
-                let mut case_table = Table::new(case_tables);
+                let data_dir = data_dir::get()?;
+                let test_dir = data_dir.join("tests").join(&args.test);
+                // Only the file sizes(And the headers of compressed files) are read here, the contents are only read for --show-input and --show-output
+                test.fill_cases(test_dir.clone())?;
+                if args.json || self.json {
+                    return print_json(&CaseJson::from_test(test, args.cases.as_ref().unwrap_or(&vec![]), args.show_input, args.show_output)?);
+                }
+                let preview_lines = Config::get().context("Failed to load config")?.preview_lines(args.full);
+                let case_tables = CaseTable::from_test(test, &test_dir, args, preview_lines)?;
+                let mut hidden = vec![];
                 if !args.show_input {
-                    case_table.with(Disable::column(ByColumnName::new("Input")));
+                    hidden.push("Input");
                 }
                 if !args.show_output {
-                    case_table.with(Disable::column(ByColumnName::new("Output")));
+                    hidden.push("Output");
+                }
+                print_rows(&case_tables, &hidden, &self.format);
+                if self.format == "table" {
+                    println!("{} cases, largest is {}", test.cases.len(), largest_case(test));
                 }
-                println!("{case_table}");
                 Ok(())
             }
             None => {
-                let test_tables = TestTable::from_tests(tests, &self.submission_type);
-                let test_table = Table::new(test_tables);
-                println!("{test_table}");
+                let name_filter = NameFilter::new(self.filter.as_deref(), self.regex.as_deref())?;
+                let selected = self.select_tests(tests, &name_filter);
+                if let (true, Some(description)) = (selected.is_empty(), name_filter.description()) {
+                    println!("No tests match '{}'", description);
+                    return Ok(());
+                }
+                let test_tables = TestTable::from_tests(&selected, self.details, self.show_stats)?;
+                let mut hidden = vec![];
+                if !self.link {
+                    hidden.push("Link");
+                }
+                if !self.show_io {
+                    hidden.extend(["Time Limit", "Memory Limit"]);
+                }
+                if !self.show_stats {
+                    hidden.extend(["Cases", "Size"]);
+                }
+                if !self.details {
+                    hidden.extend(["Added", "Source", "Data Size", "Disk Size", "Largest Case", "Archive"]);
+                }
+                print_rows(&test_tables, &hidden, &self.format);
                 Ok(())
             }
         }
     }
-}
\ No newline at end of file
+
+    // Nothing but the names is printed, and no tests isn't an error, so the output can be used as arguments
+    fn print_names(&self, tests: &mut HashMap<String, Test>) -> Result<(), Error> {
+        match &self.command {
+            Some(ListCommands::TEST(args)) => {
+                let test = match tests.get_mut(&args.test) {
+                    Some(test) => test,
+                    None => return Err(Test::not_found(&args.test, tests)),
+                };
+                test.fill_cases(data_dir::get()?.join("tests").join(&args.test))?;
+                for case_name in selected_case_names(test, args.cases.as_deref().unwrap_or_default())? {
+                    println!("{}", case_name);
+                }
+            }
+            None => {
+                let name_filter = NameFilter::new(self.filter.as_deref(), self.regex.as_deref())?;
+                for (name, _) in self.select_tests(tests, &name_filter) {
+                    println!("{}", name);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // The tests that pass the filters, in the order they are listed
+    fn select_tests<'a>(&self, tests: &'a HashMap<String, Test>, name_filter: &NameFilter) -> Vec<(&'a String, &'a Test)> {
+        // Tests added before dates were recorded, and tests without a submission type, count as the smallest
+        let tests_dir = data_dir::get().map(|data_dir| data_dir.join("tests")).unwrap_or_default();
+        let mut keyed = tests
+            .iter()
+            .filter(|(_, test)| self.submission_type.is_none_or(|submission_type| submission_type.matches(test)))
+            .filter(|(_, test)| self.older_than.is_none_or(|age| is_older_than(test, age)))
+            .filter(|(_, test)| test.has_tags(&self.tag))
+            .filter(|(name, _)| name_filter.matches(name))
+            .filter(|(name, _)| !self.failed_only || last_run_failed(&tests_dir.join(name)))
+            .map(|(name, test)| {
+                let key = match self.sort.as_str() {
+                    "added" => SortKey::Number(test.metadata.added_at.map_or(i64::MIN, |added_at| added_at.timestamp())),
+                    "cases" => SortKey::Number(count_cases(test, &tests_dir.join(name)) as i64),
+                    "size" => SortKey::Number(dir_size(&tests_dir.join(name)) as i64),
+                    "type" => SortKey::Text(test.submission_data.as_ref().map(|submission_data| submission_data.submission_type.to_string())),
+                    _ => SortKey::Text(Some(name.clone())),
+                };
+                (key, (name, test))
+            })
+            .collect::<Vec<(SortKey, (&String, &Test))>>();
+        // The numbers are largest first, like the newest tests
+        let descending = matches!(self.sort.as_str(), "added" | "cases" | "size");
+        keyed.sort_by(|(key_a, (name_a, _)), (key_b, (name_b, _))| {
+            let order = if descending { key_b.cmp(key_a) } else { key_a.cmp(key_b) };
+            let order = if self.reverse { order.reverse() } else { order };
+            order.then_with(|| name_a.cmp(name_b))
+        });
+        keyed.into_iter().map(|(_, test)| test).collect()
+    }
+}
+
+// A history that can't be read is shown as unknown with a warning, so one broken file doesn't stop the whole list
+fn load_history(test_dir: &Path, test_name: &str) -> Option<RunHistory> {
+    match RunHistory::load(test_dir) {
+        Ok(history) => Some(history),
+        Err(error) => {
+            eprintln!("{}: Failed to read the run history of \"{}\": {}", color::yellow("WARNING"), test_name, error);
+            None
+        }
+    }
+}
+
+fn last_result(test_dir: &Path, test_name: &str) -> String {
+    let Some(history) = load_history(test_dir, test_name) else {
+        return "unknown".to_string();
+    };
+    match history.last_run() {
+        Some(run) => format!("{}/{} passed · {}", run.passed(), run.cases.len(), time_ago(&run.timestamp)),
+        None => "never run".to_string(),
+    }
+}
+
+// A history that can't be read counts as never run
+fn last_run_failed(test_dir: &Path) -> bool {
+    RunHistory::load(test_dir).is_ok_and(|history| history.last_run().is_some_and(|run| run.verdict().is_failure()))
+}
+
+// A number of minutes(m), hours(h), days(d) or weeks(w), like 90d
+pub fn parse_age(age: &str) -> Result<TimeDelta, String> {
+    let unit_start = age.find(|c: char| !c.is_ascii_digit()).unwrap_or(age.len());
+    let (amount, unit) = age.split_at(unit_start);
+    let amount = handle_error!(amount.parse::<i64>(), format!("Invalid age \"{}\", use a number followed by m, h, d or w, like 90d", age));
+    let age = match unit {
+        "m" => TimeDelta::try_minutes(amount),
+        "h" => TimeDelta::try_hours(amount),
+        "d" => TimeDelta::try_days(amount),
+        "w" => TimeDelta::try_weeks(amount),
+        _ => return Err(format!("Invalid unit \"{}\" in age, use m, h, d or w, like 90d", unit)),
+    };
+    Ok(handle_option!(age, "Age is too large"))
+}
+
+// Tests without a date aren't older than anything, so they aren't removed by accident
+pub fn is_older_than(test: &Test, age: TimeDelta) -> bool {
+    test.metadata.added_at.is_some_and(|added_at| Local::now().signed_duration_since(added_at) > age)
+}
+
+fn time_ago(timestamp: &DateTime<Local>) -> String {
+    let elapsed = Local::now().signed_duration_since(timestamp);
+    if elapsed.num_days() > 0 {
+        format!("{}d ago", elapsed.num_days())
+    } else if elapsed.num_hours() > 0 {
+        format!("{}h ago", elapsed.num_hours())
+    } else if elapsed.num_minutes() > 0 {
+        format!("{}m ago", elapsed.num_minutes())
+    } else {
+        "just now".to_string()
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+pub enum SubmissionTypeFilter {
+    USACO,
+    CODEFORCES,
+    ATCODER,
+    KATTIS,
+    CSES,
+    OJUZ,
+    NONE,
+}
+
+impl SubmissionTypeFilter {
+    pub fn matches(self, test: &Test) -> bool {
+        self.submission_type().as_ref() == test.submission_data.as_ref().map(|submission_data| &submission_data.submission_type)
+    }
+
+    // None for tests without a submission type
+    fn submission_type(self) -> Option<SubmissionType> {
+        match self {
+            SubmissionTypeFilter::USACO => Some(SubmissionType::USACO),
+            SubmissionTypeFilter::CODEFORCES => Some(SubmissionType::CODEFORCES),
+            SubmissionTypeFilter::ATCODER => Some(SubmissionType::ATCODER),
+            SubmissionTypeFilter::KATTIS => Some(SubmissionType::KATTIS),
+            SubmissionTypeFilter::CSES => Some(SubmissionType::CSES),
+            SubmissionTypeFilter::OJUZ => Some(SubmissionType::OJUZ),
+            SubmissionTypeFilter::NONE => None,
+        }
+    }
+}
+
+// Hidden columns are left out of every format
+fn print_rows<T: Tabled>(rows: &[T], hidden: &[&str], format: &str) {
+    if format == "table" {
+        let mut table = Table::new(rows);
+        for column in hidden {
+            table.with(Disable::column(ByColumnName::new(*column)));
+        }
+        println!("{table}");
+        return;
+    }
+    let headers = T::headers();
+    let shown = headers
+        .iter()
+        .enumerate()
+        .filter(|(_, header)| !hidden.contains(&header.as_ref()))
+        .map(|(i, _)| i)
+        .collect::<Vec<usize>>();
+    let lines = [shown.iter().map(|i| headers[*i].to_string()).collect::<Vec<String>>()]
+        .into_iter()
+        .chain(rows.iter().map(|row| {
+            let fields = row.fields();
+            shown.iter().map(|i| fields[*i].to_string()).collect()
+        }));
+    for (i, line) in lines.enumerate() {
+        if format == "csv" {
+            println!("{}", line.iter().map(|field| csv_field(field)).collect::<Vec<String>>().join(","));
+            continue;
+        }
+        // Cells of a Markdown table have to be on one line
+        let cells = line.iter().map(|field| field.trim_end().replace('|', "\\|").replace('\n', "<br>")).collect::<Vec<String>>();
+        println!("| {} |", cells.join(" | "));
+        if i == 0 {
+            println!("|{}", " --- |".repeat(cells.len()));
+        }
+    }
+}
+
+// Fields with commas, quotes, or newlines are quoted, with quotes doubled
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum SortKey {
+    Number(i64),
+    Text(Option<String>),
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), Error> {
+    println!("{}", serde_json::to_string_pretty(value).context("Failed to serialize list to JSON")?);
+    Ok(())
+}
+
+// Only the sizes of the case files are read, so huge cases that shouldn't be there are easy to notice
+fn load_largest_case(test: &Test, test_dir: &Path) -> String {
+    let mut test = test.clone();
+    if test.fill_cases(test_dir.to_path_buf()).is_err() {
+        return "Unknown".to_string();
+    }
+    largest_case(&test)
+}
+
+// The test should have its cases filled
+fn largest_case(test: &Test) -> String {
+    test.case_iter()
+        .map(|(name, case)| (name, case.input.size() + case.output_size()))
+        .max_by_key(|(_, size)| *size)
+        .map_or("None".to_string(), |(name, size)| format!("{} ({})", name, format_file_size(size as usize)))
+}
+
+// Input files in the directory, compressed or not, without pairing them up like fill_cases
+fn count_cases(test: &Test, test_dir: &Path) -> usize {
+    let Ok(entries) = fs::read_dir(test_dir) else {
+        return 0;
+    };
+    let suffixes = [format!(".{}", test.input_extension), format!(".{}.{}", test.input_extension, COMPRESSED_EXTENSION)];
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            suffixes.iter().any(|suffix| file_name.ends_with(suffix))
+        })
+        .count()
+}
+
+// Bytes of every file in the directory(Recursively), unreadable files are skipped
+pub fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => dir_size(&entry.path()),
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::run_history::HISTORY_FILE_NAME;
+
+    #[derive(clap::Parser)]
+    struct Cli {
+        #[command(flatten)]
+        list_test: ListTestArgs,
+    }
+
+    #[test]
+    fn case_table_sizes_without_reading_cases() {
+        let test_dir = tempfile::tempdir().unwrap();
+        fs::write(test_dir.path().join("1.in"), "1 2\n").unwrap();
+        fs::write(test_dir.path().join("1.out"), "3\n").unwrap();
+        fs::write(test_dir.path().join("2.in"), "10 20\n").unwrap();
+        let mut test = Test::from_stored_dir(test_dir.path()).unwrap();
+        test.cases.get_mut("2").unwrap().output = None;
+        // The table is built from the sizes found when the cases were loaded
+        for file in ["1.in", "1.out", "2.in"] {
+            fs::write(test_dir.path().join(file), "").unwrap();
+        }
+        let args = <Cli as clap::Parser>::parse_from(["test", "sum"]).list_test;
+        let table = CaseTable::from_test(&test, test_dir.path(), &args, None).unwrap();
+        let sizes = table.iter().map(|row| (row.case_name.as_str(), row.input_size, row.output_size.as_str())).collect::<Vec<_>>();
+        assert_eq!(sizes, [("1", 4, "2"), ("2", 6, "(none)")]);
+        assert!(table.iter().all(|row| row.input.is_empty() && row.output.is_empty() && row.last_verdict == "never run"));
+        assert!(CaseTable::headers().contains(&"Input Size(Bytes)".into()));
+    }
+
+    fn json_keys(value: &serde_json::Value) -> Vec<&str> {
+        let mut keys = value.as_object().unwrap().keys().map(String::as_str).collect::<Vec<&str>>();
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn test_json_field_names() {
+        let test_json = TestJson {
+            name: "two_sum".to_string(),
+            description: None,
+            submission_type: Some("Codeforces".to_string()),
+            link: Some("https://codeforces.com/contest/1700/problem/A".to_string()),
+            input_io: "STDIN".to_string(),
+            output_io: "STDOUT".to_string(),
+            case_count: Some(2),
+            tags: vec!["dp".to_string()],
+            added_at: None,
+        };
+        let value = serde_json::to_value(&test_json).unwrap();
+        let keys = ["added_at", "case_count", "description", "input_io", "link", "name", "output_io", "submission_type", "tags"];
+        assert_eq!(json_keys(&value), keys);
+        // Missing values are null so every test has the same fields
+        assert!(value["description"].is_null() && value["added_at"].is_null());
+        assert_eq!(value["case_count"], 2);
+        assert_eq!(value["tags"], serde_json::json!(["dp"]));
+    }
+
+    #[test]
+    fn case_json_field_names() {
+        let test_dir = tempfile::tempdir().unwrap();
+        fs::write(test_dir.path().join("1.in"), "1 2\n").unwrap();
+        fs::write(test_dir.path().join("1.out"), "3\n").unwrap();
+        fs::write(test_dir.path().join("2.in"), "10 20\n").unwrap();
+        let mut test = Test::from_stored_dir(test_dir.path()).unwrap();
+        test.cases.get_mut("2").unwrap().output = None;
+        let value = serde_json::to_value(CaseJson::from_test(&test, &[], false, false).unwrap()).unwrap();
+        assert_eq!(json_keys(&value[0]), ["alternate_count", "input_len", "name", "output_len"]);
+        assert_eq!(value[0]["input_len"], 4);
+        assert!(value[1]["output_len"].is_null());
+        // The contents are only there when they are shown
+        let value = serde_json::to_value(CaseJson::from_test(&test, &["1".to_string()], true, true).unwrap()).unwrap();
+        assert_eq!(json_keys(&value[0]), ["alternate_count", "input", "input_len", "name", "output", "output_len"]);
+        assert_eq!((value[0]["input"].as_str(), value[0]["output"].as_str()), (Some("1 2\n"), Some("3\n")));
+        assert_eq!(value.as_array().unwrap().len(), 1);
+        assert!(CaseJson::from_test(&test, &["3".to_string()], false, false).is_err());
+    }
+
+    #[test]
+    fn unreadable_history_is_unknown() {
+        let test_dir = tempfile::tempdir().unwrap();
+        assert_eq!(last_result(test_dir.path(), "test"), "never run");
+        fs::write(test_dir.path().join(HISTORY_FILE_NAME), "{ not json").unwrap();
+        assert_eq!(last_result(test_dir.path(), "test"), "unknown");
+        assert!(!last_run_failed(test_dir.path()));
+    }
+}
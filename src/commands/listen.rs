@@ -0,0 +1,234 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    path::PathBuf,
+};
+
+use clap::Args;
+use serde::Deserialize;
+use tempfile::TempDir;
+
+use crate::{
+    case_naming::CaseNaming,
+    commands::add::SubmissionData,
+    error::{Context, Error},
+    handle_error, names,
+    test_data::{IOType, ScanOptions, Test},
+};
+
+// The port Competitive Companion sends problems to by default
+const DEFAULT_PORT: &str = "10043";
+// Problems with bigger samples than this aren't expected, it stops a bad request from using up memory
+const MAX_BODY_BYTES: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, Args, Clone)]
+pub struct ListenArgs {
+    #[arg(short, long, default_value = DEFAULT_PORT, help = "The port to listen on, should match a custom port in Competitive Companion")]
+    port: u16,
+
+    #[arg(short, long, value_parser = clap::value_parser!(u64).range(1..), help = "Stop after adding this many tests, else listen until stopped with Ctrl-C")]
+    count: Option<u64>,
+}
+
+// The parts of the Competitive Companion problem format that are used, see https://github.com/jmerle/competitive-companion#explanation
+#[derive(Debug, Deserialize)]
+struct CompanionProblem {
+    name: String,
+    #[serde(default)]
+    group: String,
+    #[serde(default)]
+    url: String,
+    tests: Vec<CompanionTest>,
+    input: CompanionIO,
+    output: CompanionIO,
+    // In ms and MB
+    #[serde(rename = "timeLimit")]
+    time_limit: Option<u64>,
+    #[serde(rename = "memoryLimit")]
+    memory_limit: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CompanionTest {
+    input: String,
+    output: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CompanionIO {
+    #[serde(rename = "type")]
+    io_type: String,
+    #[serde(rename = "fileName")]
+    file_name: Option<String>,
+}
+
+impl ListenArgs {
+    pub fn bind(&self) -> Result<TcpListener, Error> {
+        let listener = TcpListener::bind(("127.0.0.1", self.port)).context(format!("Failed to listen on port {}", self.port))?;
+        Ok(listener)
+    }
+
+    pub fn get_count(&self) -> Option<u64> {
+        self.count
+    }
+
+    // Waits for the next problem from Competitive Companion and turns it into a test
+    pub fn receive_test(&self, listener: &TcpListener, tests: &HashMap<String, Test>) -> Result<(String, Test), Error> {
+        let (stream, _) = listener.accept().context("Failed to accept connection")?;
+        let body = read_request_body(stream)?;
+        let problem: CompanionProblem = serde_json::from_str(&body).context("Failed to parse problem sent by Competitive Companion")?;
+        let name = slugify(&problem.name);
+        if name.is_empty() {
+            return Err(format!("Can't make a test name from problem name \"{}\"", problem.name).into());
+        }
+        check_name(&name, tests)?;
+        if problem.tests.is_empty() {
+            return Err(format!("Problem \"{}\" has no test cases", problem.name).into());
+        }
+        let input_io = get_io(&problem.input, "stdin")?;
+        let output_io = get_io(&problem.output, "stdout")?;
+        let temp_dir = TempDir::new().context("Failed to create temporary directory to store test cases")?;
+        for (i, case) in problem.tests.iter().enumerate() {
+            fs::write(temp_dir.path().join(format!("example{}.in", i + 1)), &case.input).context("Failed to write example input")?;
+            fs::write(temp_dir.path().join(format!("example{}.out", i + 1)), &case.output).context("Failed to write example output")?;
+        }
+        let description = if problem.group.is_empty() {
+            problem.name.clone()
+        } else {
+            format!("{}: {}", problem.group, problem.name)
+        };
+        let mut test = Test::from_folder(
+            temp_dir.path().to_path_buf(),
+            &CaseNaming::EXTENSIONS("in".to_string(), "out".to_string()),
+            input_io,
+            output_io,
+            SubmissionData::try_from_link(&problem.url),
+            Some(description),
+            ScanOptions::default(),
+        )?;
+        test.time_limit_ms = problem.time_limit;
+        test.memory_limit_mb = problem.memory_limit;
+        test.record_metadata(Some(problem.url.clone()));
+        Ok((name, test))
+    }
+}
+
+// Checked again before the test is written, since another instance could have added one with the name while waiting
+pub fn check_name(name: &str, tests: &HashMap<String, Test>) -> Result<(), String> {
+    if tests.contains_key(name) {
+        return Err(format!("Test with name \"{}\" already exists", name));
+    }
+    if let Some(conflict) = names::find_conflict(name, tests.keys()) {
+        return Err(format!("Test \"{}\" only differs from \"{}\" in capitalization", conflict, name));
+    }
+    Ok(())
+}
+
+// Only what is needed for Competitive Companion's POST requests, the body is always JSON with a Content-Length
+fn read_request_body(mut stream: TcpStream) -> Result<String, String> {
+    let mut reader = BufReader::new(handle_error!(stream.try_clone(), "Failed to read request"));
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        handle_error!(reader.read_line(&mut line), "Failed to read request headers");
+        let line = line.trim();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((header, value)) = line.split_once(':') {
+            if header.trim().eq_ignore_ascii_case("content-length") {
+                content_length = handle_error!(value.trim().parse::<usize>(), "Invalid Content-Length header");
+            }
+        }
+    }
+    // The length is from the request, so it is checked before the body is allocated
+    if content_length > MAX_BODY_BYTES {
+        handle_error!(
+            stream.write_all(b"HTTP/1.1 413 Payload Too Large\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"),
+            "Failed to respond to request"
+        );
+        return Err(format!("Request body is {} bytes, more than the limit of {} MiB", content_length, MAX_BODY_BYTES / 1024 / 1024));
+    }
+    let mut body = vec![0; content_length];
+    handle_error!(reader.read_exact(&mut body), "Failed to read request body");
+    handle_error!(
+        stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n"),
+        "Failed to respond to request"
+    );
+    Ok(String::from_utf8_lossy(&body).to_string())
+}
+
+fn get_io(io: &CompanionIO, std_type: &str) -> Result<IOType, String> {
+    if io.io_type == std_type {
+        return Ok(IOType::STD);
+    }
+    match (io.io_type.as_str(), &io.file_name) {
+        ("file", Some(file_name)) => Ok(IOType::FILE(PathBuf::from(file_name))),
+        _ => Err(format!(
+            "Unsupported IO type \"{}\", only {} and files with a name are supported",
+            io.io_type, std_type
+        )),
+    }
+}
+
+// "A. Watermelon" becomes "a_watermelon"
+fn slugify(name: &str) -> String {
+    let name = name
+        .to_ascii_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    name.split('_').filter(|part| !part.is_empty()).collect::<Vec<&str>>().join("_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_data::TestCase;
+    use std::thread;
+
+    // Sends request to a listener on a free port, returning the response and what read_request_body made of it
+    fn send(request: String) -> (String, Result<String, String>) {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let client = thread::spawn(move || {
+            let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+            stream.write_all(request.as_bytes()).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        });
+        let (stream, _) = listener.accept().unwrap();
+        let body = read_request_body(stream);
+        (client.join().unwrap(), body)
+    }
+
+    #[test]
+    fn reads_body() {
+        let (response, body) = send("POST / HTTP/1.1\r\nContent-Type: application/json\r\nContent-Length: 7\r\n\r\n{\"a\":1}".to_string());
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert_eq!(body, Ok("{\"a\":1}".to_string()));
+    }
+
+    #[test]
+    fn rejects_huge_body() {
+        let (response, body) = send(format!("POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n", MAX_BODY_BYTES + 1));
+        assert!(response.starts_with("HTTP/1.1 413"));
+        assert!(body.is_err());
+    }
+
+    #[test]
+    fn new_name_must_not_clash() {
+        let tests = HashMap::from([("watermelon".to_string(), Test::adhoc(TestCase::new(vec![], None)))]);
+        assert!(check_name("watermelon", &tests).is_err());
+        assert!(check_name("Watermelon", &tests).is_err());
+        assert!(check_name("theatre_square", &tests).is_ok());
+    }
+
+    #[test]
+    fn slugifies_problem_names() {
+        assert_eq!(slugify("A. Watermelon"), "a_watermelon");
+    }
+}
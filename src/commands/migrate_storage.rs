@@ -0,0 +1,91 @@
+use std::{collections::HashMap, fs};
+
+use clap::Args;
+
+use crate::{
+    commands::add::format_file_size,
+    data_dir,
+    error::{Context, Error},
+    handle_error,
+    test_data::{alternate_path, Test},
+};
+
+#[derive(Args, Debug)]
+pub struct MigrateStorageArgs {
+    #[arg(help = "The tests to convert, all tests if none are given")]
+    test_names: Vec<String>,
+
+    #[arg(long, conflicts_with = "decompress", required_unless_present = "decompress", help = "Store the cases as .zst files")]
+    compress: bool,
+
+    #[arg(long, help = "Store the cases as plain files")]
+    decompress: bool,
+}
+
+impl MigrateStorageArgs {
+    pub fn run(&self, tests: &mut HashMap<String, Test>) -> Result<(), Error> {
+        if tests.is_empty() {
+            return Err(Error::OTHER("There are no tests to convert".to_string()));
+        }
+        for test_name in &self.test_names {
+            if !tests.contains_key(test_name) {
+                return Err(Test::not_found(test_name, tests));
+            }
+        }
+        let mut test_names = if self.test_names.is_empty() {
+            tests.keys().cloned().collect::<Vec<String>>()
+        } else {
+            self.test_names.clone()
+        };
+        test_names.sort();
+        let data_dir = data_dir::get()?;
+        let tests_dir = data_dir.join("tests");
+        let (mut total_before, mut total_after) = (0, 0);
+        for test_name in &test_names {
+            let test = tests.get_mut(test_name).unwrap();
+            test.fill_cases(tests_dir.join(test_name)).context(format!("Failed to load cases of \"{}\"", test_name))?;
+            if test.is_compressed() == self.compress {
+                println!("\"{}\" is already {}", test_name, if self.compress { "compressed" } else { "decompressed" });
+                test.cases.clear();
+                continue;
+            }
+            let (before, after) = self.convert(test, &tests_dir.join(test_name)).context(format!("Failed to convert \"{}\"", test_name))?;
+            test.cases.clear();
+            println!("Converted \"{}\": {} -> {}", test_name, format_file_size(before as usize), format_file_size(after as usize));
+            total_before += before;
+            total_after += after;
+        }
+        if total_before > 0 {
+            println!("Total: {} -> {}", format_file_size(total_before as usize), format_file_size(total_after as usize));
+        }
+        Ok(())
+    }
+
+    // All of the new files are written before the old ones are removed, so stopping part way never loses a case
+    // Returns the bytes the cases took on disk before and after
+    fn convert(&self, test: &Test, test_dir: &std::path::Path) -> Result<(u64, u64), String> {
+        let mut old_paths = vec![];
+        let mut after = 0;
+        for (name, case) in test.case_iter() {
+            let (input_file, output_file) = test.case_file_names(name, self.compress);
+            let (input_path, output_path) = (test_dir.join(input_file), test_dir.join(output_file));
+            if self.compress {
+                case.write_compressed(&input_path, &output_path, name)?;
+            } else {
+                case.write_data(&input_path, &output_path, name)?;
+            }
+            let alternate_paths = (1..=case.alternates.len()).map(|index| alternate_path(&output_path, index));
+            for path in [input_path, output_path.clone()].into_iter().chain(alternate_paths).filter(|path| path.is_file()) {
+                after += handle_error!(fs::metadata(&path), format!("Failed to read {:?}", path)).len();
+            }
+            old_paths.extend(case.input.path().into_iter().chain(case.output.as_ref().and_then(|output| output.path())));
+            old_paths.extend(case.alternates.iter().filter_map(|alternate| alternate.path()));
+        }
+        let mut before = 0;
+        for path in old_paths {
+            before += handle_error!(fs::metadata(path), format!("Failed to read {:?}", path)).len();
+            handle_error!(fs::remove_file(path), format!("Failed to remove {:?}", path));
+        }
+        Ok((before, after))
+    }
+}
@@ -0,0 +1,87 @@
+use std::{collections::HashMap, env, fs, path::Path, process::Command};
+
+use clap::Args;
+
+use crate::{
+    config::Config,
+    data_dir,
+    error::{Context, Error},
+    handle_error, handle_option,
+    test_data::Test,
+};
+
+// Kept in the test's directory instead of test.json, so it moves with renames and duplicates
+pub const NOTES_FILE_NAME: &str = "notes.md";
+#[cfg(target_os = "windows")]
+const DEFAULT_EDITOR: &str = "notepad";
+#[cfg(not(target_os = "windows"))]
+const DEFAULT_EDITOR: &str = "vi";
+
+#[derive(Debug, Args)]
+pub struct NoteArgs {
+    #[arg(help = "The name of the test to edit the notes of")]
+    test: String,
+
+    #[arg(long, help = "Print the notes instead of opening them in $VISUAL or $EDITOR")]
+    show: bool,
+}
+
+impl NoteArgs {
+    pub fn run(&self, tests: &HashMap<String, Test>) -> Result<(), Error> {
+        if !tests.contains_key(&self.test) {
+            return Err(Test::not_found(&self.test, tests));
+        }
+        let test_dir = data_dir::get()?.join("tests").join(&self.test);
+        let notes_path = test_dir.join(NOTES_FILE_NAME);
+        if self.show {
+            if !notes_path.is_file() {
+                return Err(format!("Test \"{}\" has no notes, add them with `cp-tester note {}`", self.test, self.test).into());
+            }
+            let notes = fs::read_to_string(&notes_path).context(format!("Failed to read notes({:?})", notes_path))?;
+            println!("{}", notes.trim_end());
+            return Ok(());
+        }
+        fs::create_dir_all(&test_dir).context("Failed to create test directory")?;
+        open_in_editor(&notes_path)?;
+        // Emptied notes are removed, so list doesn't show the test as having notes
+        if notes_path.is_file() && fs::read_to_string(&notes_path).context("Failed to read notes")?.trim().is_empty() {
+            fs::remove_file(&notes_path).context(format!("Failed to remove empty notes({:?})", notes_path))?;
+            println!("Notes of \"{}\" are empty, so they were removed", self.test);
+        } else if notes_path.is_file() {
+            println!("Saved notes of \"{}\"", self.test);
+        }
+        Ok(())
+    }
+}
+
+// $VISUAL, then $EDITOR, then the editor in the config, so it can be set where the environment variables can't be
+pub fn open_in_editor(path: &Path) -> Result<(), String> {
+    // The config might be what is being fixed, so it doesn't have to load
+    let config_editor = Config::get().ok().and_then(|config| config.editor);
+    // Editors like `code --wait` have arguments
+    let editor = env::var("VISUAL")
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .or(env::var("EDITOR").ok().filter(|editor| !editor.trim().is_empty()))
+        .or(config_editor)
+        .unwrap_or(DEFAULT_EDITOR.to_string());
+    let mut editor_args = editor.split_whitespace();
+    let program = editor_args.next();
+    let program = handle_option!(program, "No editor given in $VISUAL or $EDITOR");
+    let status = handle_error!(
+        Command::new(program).args(editor_args).arg(path).status(),
+        format!("Failed to start editor \"{}\", set $EDITOR or `cp-tester config set-editor` to the editor to use", editor)
+    );
+    if !status.success() {
+        return Err(format!(
+            "Editor \"{}\" exited with non-zero exit code: {}",
+            editor,
+            status.code().unwrap_or(-1)
+        ));
+    }
+    Ok(())
+}
+
+pub fn has_notes(test_name: &str) -> Result<bool, String> {
+    Ok(data_dir::get()?.join("tests").join(test_name).join(NOTES_FILE_NAME).is_file())
+}
@@ -0,0 +1,28 @@
+use clap::Args;
+
+use crate::test_data::Test;
+
+#[derive(Debug, Args)]
+pub struct OpenArgs {
+    #[arg(help = "The name of the test to open the problem page for")]
+    pub test_name: String,
+}
+
+impl OpenArgs {
+    pub fn run(&self, test: &Test) -> Result<(), String> {
+        let link = match &test.submission_data {
+            Some(submission_data) => &submission_data.link,
+            None => {
+                return Err(format!(
+                    "Test with name \"{}\" has no problem link, only tests added from USACO, Codeforces, or AtCoder links store one",
+                    self.test_name
+                ))
+            }
+        };
+        println!("Opening {}", link);
+        if let Err(e) = open::that(link) {
+            println!("Failed to open a browser ({}), open the link manually: \n{}", e, link);
+        }
+        Ok(())
+    }
+}
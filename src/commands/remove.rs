@@ -1,10 +1,131 @@
+use std::{
+    collections::HashMap,
+    io::{self, IsTerminal, Write},
+    path::Path,
+};
+
+use chrono::TimeDelta;
 use clap::Args;
 
+use crate::{
+    commands::{
+        add::format_file_size,
+        list::{dir_size, is_older_than, parse_age, SubmissionTypeFilter},
+    },
+    data_dir,
+    error::{Context, Error},
+    names::NameFilter,
+    test_data::Test,
+};
+
 #[derive(Debug, Args)]
 pub struct RemoveArgs {
-    #[arg(required_unless_present("all"), value_delimiter = ',',help ="The name of the test case to remove. \nIf multiple test cases are specified(Use a comma between cases), all of them will be removed")]
+    #[arg(required_unless_present_any(["all", "filter", "submission_type", "older_than"]), value_delimiter = ',',help ="The name of the test case to remove. \nIf multiple test cases are specified(Use a comma between cases), all of them will be removed")]
     pub test_name: Option<Vec<String>>,
 
     #[arg(short, long, help="Remove all tests")]
     pub all: bool,
+
+    #[arg(long, help = "Keep the archives the tests were downloaded from(If they were kept)")]
+    pub keep_archive: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["test_name", "all"],
+        help = "Remove the tests whose name contains this(Case-insensitive), or matches it if it is a glob with * or ?"
+    )]
+    pub filter: Option<String>,
+
+    #[arg(
+        short,
+        long,
+        value_enum,
+        ignore_case = true,
+        conflicts_with_all = ["test_name", "all"],
+        help = "Remove the tests with this submission type, none removes the tests added from folders or packages"
+    )]
+    pub submission_type: Option<SubmissionTypeFilter>,
+
+    #[arg(
+        long,
+        value_parser = parse_age,
+        conflicts_with_all = ["test_name", "all"],
+        help = "Remove the tests added more than this long ago, like 90d, 12h or 2w(Tests added before dates were recorded are kept)"
+    )]
+    pub older_than: Option<TimeDelta>,
+
+    #[arg(short, long, help = "Don't ask before removing the tests")]
+    pub yes: bool,
+
+    #[arg(long, conflicts_with = "yes", help = "Ask before removing the tests even if stdin isn't a terminal")]
+    pub interactive: bool,
+}
+
+impl RemoveArgs {
+    // The tests to remove, None if --filter didn't match anything
+    pub fn test_names(&self, tests: &HashMap<String, Test>) -> Result<Option<Vec<String>>, Error> {
+        if self.all {
+            if tests.is_empty() {
+                return Err("There are no tests to remove".to_string().into());
+            }
+            let mut test_names = tests.keys().cloned().collect::<Vec<String>>();
+            test_names.sort();
+            return Ok(Some(test_names));
+        }
+        if !self.is_filtered() {
+            let test_names = self.test_name.clone().unwrap();
+            if let Some(test_name) = test_names.iter().find(|test_name| !tests.contains_key(*test_name)) {
+                return Err(Test::not_found(test_name, tests));
+            }
+            return Ok(Some(test_names));
+        }
+        // The same filters as `cp-tester list`, so the tests can be checked with it first
+        let name_filter = NameFilter::new(self.filter.as_deref(), None)?;
+        let mut test_names = tests
+            .iter()
+            .filter(|(name, _)| name_filter.matches(name))
+            .filter(|(_, test)| self.submission_type.is_none_or(|submission_type| submission_type.matches(test)))
+            .filter(|(_, test)| self.older_than.is_none_or(|age| is_older_than(test, age)))
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<String>>();
+        if test_names.is_empty() {
+            println!("No tests match, nothing was removed");
+            return Ok(None);
+        }
+        test_names.sort();
+        Ok(Some(test_names))
+    }
+
+    // Whether the removal was confirmed, scripts that can't answer aren't asked unless --interactive is passed
+    pub fn confirm(&self, test_names: &[String], tests_path: &Path) -> Result<bool, Error> {
+        if self.yes {
+            return Ok(true);
+        }
+        if !self.interactive && !io::stdin().is_terminal() {
+            // What the filters match can change as tests are added, so they aren't trusted without asking
+            if self.is_filtered() {
+                return Err(format!("{} tests match, pass --yes to remove them", test_names.len()).into());
+            }
+            return Ok(true);
+        }
+        let mut bytes = 0;
+        for test_name in test_names {
+            bytes += dir_size(&tests_path.join(test_name));
+            if !self.keep_archive {
+                bytes += data_dir::archive_path(test_name)?.metadata().map_or(0, |metadata| metadata.len());
+            }
+        }
+        if self.is_filtered() {
+            eprintln!("{}", test_names.join("\n"));
+        }
+        eprint!("This will delete {} tests ({}). Continue? [y/N]: ", test_names.len(), format_file_size(bytes as usize));
+        io::stderr().flush().context("Failed to flush stderr")?;
+        let mut line = String::new();
+        io::stdin().read_line(&mut line).context("Failed to read from stdin")?;
+        Ok(line.trim().eq_ignore_ascii_case("y"))
+    }
+
+    fn is_filtered(&self) -> bool {
+        self.filter.is_some() || self.submission_type.is_some() || self.older_than.is_some()
+    }
 }
@@ -0,0 +1,136 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use clap::Args;
+
+use crate::{
+    data_dir,
+    error::{Context, Error},
+    handle_error,
+    run_history::{RunHistory, RunRecord},
+    subtasks::{total_score, Subtask},
+    test_data::Test,
+};
+
+// Diffs longer than this are cut off so the report stays readable
+const MAX_DIFF_LINES: usize = 100;
+
+#[derive(Debug, Args)]
+pub struct ReportArgs {
+    #[arg(help = "The name of the test to write a report for")]
+    test: String,
+
+    #[arg(short, long, default_value = "report.md", help = "The file to write the Markdown report to")]
+    output: PathBuf,
+
+    #[arg(short, long, help = "The run to write a report for(The number in the Run column of `cp-tester history`), defaults to the last run")]
+    run: Option<u32>,
+}
+
+impl ReportArgs {
+    pub fn run(&self, tests: &mut HashMap<String, Test>) -> Result<(), Error> {
+        let test = match tests.get_mut(&self.test) {
+            Some(test) => test,
+            None => return Err(Test::not_found(&self.test, tests)),
+        };
+        let data_dir = data_dir::get()?;
+        let test_dir = data_dir.join("tests").join(&self.test);
+        let history = RunHistory::load(&test_dir)?;
+        let run = match self.run {
+            Some(run_id) => history
+                .get_run(run_id)
+                .context(format!("Run {} isn't in the history of test \"{}\"(Old runs are removed)", run_id, self.test))?,
+            None => history.last_run().context(format!("Test \"{}\" hasn't been run yet", self.test))?,
+        };
+        test.fill_cases(test_dir).context("Failed to get test cases")?;
+        write_report(&self.output, &self.test, test, run)?;
+        println!("Wrote report for run {} to {:?}", run.id, self.output);
+        Ok(())
+    }
+}
+
+// The test should have its cases filled, they are used for the expected output in diffs
+pub fn write_report(path: &Path, test_name: &str, test: &Test, run: &RunRecord) -> Result<(), String> {
+    let mut report = String::new();
+    report.push_str(&format!("# {}: {}\n\n", test_name, run.verdict()));
+    report.push_str(&format!("- **File:** `{}`\n", run.file.to_string_lossy()));
+    if let Some(compile_command) = &run.compile_command {
+        report.push_str(&format!("- **Compiler:** `{}`\n", compile_command));
+    }
+    report.push_str(&format!("- **Date:** {}\n", run.timestamp.format("%Y-%m-%d %H:%M:%S %:z")));
+    report.push_str(&format!("- **Passed:** {}/{}\n", run.passed(), run.cases.len()));
+    // Scored with the subtasks the test has now, they aren't stored with the run
+    let results = run.cases.iter().map(|case| (case.name.as_str(), case.verdict.is_pass())).collect::<Vec<(&str, bool)>>();
+    let subtasks = Subtask::score_all(&test.subtasks, &test.get_sorted_case_names(), &results);
+    if !subtasks.is_empty() {
+        let (score, max_score) = total_score(&subtasks);
+        report.push_str(&format!("- **Score:** {}/{}\n\n", score, max_score));
+        report.push_str("| Subtask | Score | Passed |\n");
+        report.push_str("| --- | --- | --- |\n");
+        for subtask in &subtasks {
+            let not_run = if subtask.not_run > 0 { format!(", {} not run", subtask.not_run) } else { String::new() };
+            report.push_str(&format!(
+                "| {} | {}/{} | {}/{}{} |\n",
+                subtask.name, subtask.score, subtask.points, subtask.passed, subtask.cases, not_run
+            ));
+        }
+    }
+    report.push('\n');
+
+    report.push_str("| Case | Verdict | Time |\n");
+    report.push_str("| --- | --- | --- |\n");
+    for case in &run.cases {
+        report.push_str(&format!("| {} | {} | {} ms |\n", case.name, case.verdict, case.time_ms));
+    }
+
+    let failed = run.cases.iter().filter(|case| case.verdict.is_failure()).collect::<Vec<_>>();
+    if !failed.is_empty() {
+        report.push_str("\n## Failed cases\n");
+        for case in failed {
+            report.push_str(&format!("\n<details>\n<summary>{} ({})</summary>\n\n", case.name, case.verdict));
+            if let Some(details) = &case.details {
+                report.push_str(&format!("{}\n\n", details));
+            }
+            match (test.cases.get(&case.name).map(|expected| expected.get_output()).transpose()?.flatten(), &case.output) {
+                (Some(expected), Some(output)) => {
+                    report.push_str("```diff\n");
+                    report.push_str(&diff_lines(&String::from_utf8_lossy(&expected), output));
+                    report.push_str("```\n");
+                }
+                _ => report.push_str("No output was recorded for this case\n"),
+            }
+            report.push_str("\n</details>\n");
+        }
+    }
+    handle_error!(fs::write(path, report), format!("Failed to write report to {:?}", path));
+    Ok(())
+}
+
+// A line by line diff, lines that match are prefixed with a space, expected lines with - and output lines with +
+fn diff_lines(expected: &str, output: &str) -> String {
+    let expected = expected.lines().map(|line| line.trim_end()).collect::<Vec<&str>>();
+    let output = output.lines().map(|line| line.trim_end()).collect::<Vec<&str>>();
+    let mut diff = Vec::new();
+    for i in 0..expected.len().max(output.len()) {
+        match (expected.get(i), output.get(i)) {
+            (Some(e), Some(o)) if e == o => diff.push(format!(" {}", e)),
+            (e, o) => {
+                if let Some(e) = e {
+                    diff.push(format!("-{}", e));
+                }
+                if let Some(o) = o {
+                    diff.push(format!("+{}", o));
+                }
+            }
+        }
+    }
+    if diff.len() > MAX_DIFF_LINES {
+        let hidden = diff.len() - MAX_DIFF_LINES;
+        diff.truncate(MAX_DIFF_LINES);
+        diff.push(format!("...({} more lines)", hidden));
+    }
+    diff.iter().map(|line| format!("{}\n", line)).collect()
+}
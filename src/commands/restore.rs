@@ -0,0 +1,78 @@
+use std::{collections::HashMap, fs};
+
+use clap::Args;
+use tempfile::TempDir;
+
+use crate::{
+    case_naming::CaseNaming,
+    commands::add::extract_archive,
+    config::Config,
+    data_dir,
+    error::{Context, Error},
+    test_data::{ScanOptions, Test},
+};
+
+#[derive(Args, Debug)]
+pub struct RestoreArgs {
+    #[arg(help = "The name of the test to restore")]
+    test_name: String,
+}
+
+impl RestoreArgs {
+    // The cases in the archive are written over the stored ones, other cases(Like USACO examples from the problem page) are kept
+    pub fn run(&self, tests: &mut HashMap<String, Test>) -> Result<(), Error> {
+        let archive_path = data_dir::archive_path(&self.test_name)?;
+        let Some(test) = tests.get_mut(&self.test_name) else {
+            if archive_path.is_file() {
+                return Err(Error::TEST_NOT_FOUND(format!(
+                    "Test with name \"{}\" doesn't exist, add it again from the kept archive with `cp-tester add --zip {:?} -n {}`",
+                    self.test_name, archive_path, self.test_name
+                )));
+            }
+            return Err(Test::not_found(&self.test_name, tests));
+        };
+        if !archive_path.is_file() {
+            return Err(format!(
+                "No archive was kept for \"{}\", `cp-tester config set-keep-source-archives true` keeps them for tests added after it",
+                self.test_name
+            )
+            .into());
+        }
+        let temp_dir = TempDir::new().context("Failed to create temporary directory to extract archive")?;
+        extract_archive(&archive_path, temp_dir.path())?;
+        let test_dir = data_dir::get()?.join("tests").join(&self.test_name);
+        // The cases are stored the same way as the existing ones, or as the config says if they can't be read
+        let compress = match test.fill_cases(test_dir.clone()) {
+            Ok(()) => test.is_compressed(),
+            Err(_) => Config::get().context("Failed to load config")?.compress_test_data,
+        };
+        // Inputs without an output are only cases if the test was added with --allow-missing-outputs
+        let options = ScanOptions {
+            allow_missing_outputs: test.cases.values().any(|case| case.output.is_none()),
+            max_case_size: None,
+        };
+        // Found the same way as when the test was added
+        let naming =
+            CaseNaming::detect(temp_dir.path(), None).unwrap_or(CaseNaming::EXTENSIONS(test.input_extension.clone(), test.output_extension.clone()));
+        let mut restored = test.clone();
+        restored.cases.clear();
+        restored.fill_cases_with_naming(temp_dir.path().to_path_buf(), &naming, options)?;
+        for (name, case) in restored.cases.iter_mut() {
+            if let Some(stored) = test.cases.get(name) {
+                case.keep_alternates(stored)?;
+            }
+        }
+        fs::create_dir_all(&test_dir).context("Failed to create test directory")?;
+        restored.write_data(&test_dir, compress)?;
+        test.fill_cases(test_dir.clone())?;
+        test.metadata.data_size_bytes = Some(test.data_size());
+        test.cases.clear();
+        println!(
+            "Restored {} cases of \"{}\" from {:?}",
+            restored.cases.len(),
+            self.test_name,
+            archive_path
+        );
+        Ok(())
+    }
+}
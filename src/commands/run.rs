@@ -1,20 +1,63 @@
-use crate::{config::Config, handle_error, handle_option, test_data::Test};
+use crate::{
+    commands::add::SubmissionType,
+    color,
+    config::{check_env_name, Config},
+    error::{Context, Error},
+    handle_error, handle_option,
+    limits::{self, ChildLimits},
+    paths::{self, TempDir},
+    project_config::ProjectConfig,
+    run_history::{CaseResult, Verdict},
+    subtasks::{total_score, Subtask, SubtaskScore},
+    test_data::{display_bytes, preview_bytes, Test, TestCase, PREVIEW_LINE_CHARS},
+};
 use std::{
+    env,
     fs::{self, File},
-    io::{self, Read, Write},
-    path::PathBuf,
+    io::{self, BufRead, BufReader, IsTerminal, Read, Write},
+    path::{Path, PathBuf},
     process::{Command, Stdio},
+    sync::{Arc, Mutex},
+    thread::{self, JoinHandle},
     time::{Duration, Instant},
 };
 
 use clap::Args;
-use tempfile::TempDir;
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use serde::{Serialize, Serializer};
 use wait_timeout::ChildExt;
 
-#[derive(Debug, Args)]
+// Where the program output and correct output are written for the checker, next to the compiled program so the program can't see them
+const CHECKER_OUTPUT_FILE: &str = "checker.out";
+const CHECKER_ANSWER_FILE: &str = "checker.ans";
+// Each case is run in this folder of the temp directory, made again for every case so files from the last case can't be read
+const CASE_DIR_NAME: &str = "case";
+const TICKER_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Args, Clone)]
 pub struct RunArgs {
-    #[arg(help = "The name of the test to run")]
-    pub test: String,
+    #[arg(
+        value_delimiter = ',',
+        value_name = "TESTS",
+        help = "The names of the tests to run(Use a comma between tests or pass more than one), defaults to the test in the project config file(.cptester.json) if there is one. \nThe file to run can be passed after them instead of --file, so `cp-tester r <test> <file>` works"
+    )]
+    pub tests: Vec<String>,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["cases", "example", "save", "report", "fail_fast"],
+        help = "Run the file on input from stdin or --input-file instead of a stored test, and print its output"
+    )]
+    pub adhoc: bool,
+
+    #[arg(long, requires = "adhoc", help = "The input of --adhoc, instead of reading it from stdin")]
+    pub input_file: Option<PathBuf>,
+
+    #[arg(long, requires = "adhoc", help = "The correct output of --adhoc, the output is compared to it instead of printed")]
+    pub expected_file: Option<PathBuf>,
+
+    #[arg(long, help = "Stop at the first test that doesn't exist, fails to run, or has failed cases, instead of running the rest")]
+    pub fail_fast: bool,
 
     #[arg(
         short,
@@ -24,23 +67,107 @@ pub struct RunArgs {
     )]
     pub cases: Option<Vec<String>>,
 
-    #[arg(short, long, help = "Show input for each test case(Input can be very large)")]
-    pub show_input: bool,
+    // Options so the config is only used when they aren't given, --show-input=false turns it off for a run
+    #[arg(
+        short,
+        long,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        help = "Show input for each test case(Input can be very large), default is the config(Set with `cp-tester config set-show-input`)"
+    )]
+    pub show_input: Option<bool>,
+
+    #[arg(
+        short = 'o',
+        long,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        help = "Compare output of program to desired output, default is the config(Set with `cp-tester config set-compare-output`)"
+    )]
+    pub compare_output: Option<bool>,
 
-    #[arg(short = 'o', long, help = "Compare output of program to desired output")]
-    pub compare_output: bool,
+    #[arg(
+        long,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        help = "Show the first line where a failed output differs from the correct output, default is the config(Set with `cp-tester config set-diff`)"
+    )]
+    pub diff: Option<bool>,
 
-    #[arg(short,long,value_parser=file_exists,help="The file to run, should be a file with a valid extension(.c, .cpp, .java, .py)")]
-    pub file: PathBuf,
+    #[arg(long, help = "Show whole inputs and outputs with --show-input and --compare-output instead of their first lines")]
+    pub full: bool,
+
+    #[arg(short,long,value_parser=file_exists,help="The file to run, should be a file with a valid extension(.c, .cpp, .java, .py), defaults to the file in the project config file(.cptester.json) if there is one, then the file of the last run of the test, then the one in the current directory")]
+    pub file: Option<PathBuf>,
 
-    #[arg(long,default_value=Config::get_cpp_ver(),value_parser=["20","17","14","11"],help="The C++ version to compile with, default is the version in the config file, else 17")]
-    pub cpp_ver: String,
+    #[arg(long,value_parser=["20","17","14","11"],help="The C++ version to compile with, default is the version in the project config file, then the version in the config file, else 17")]
+    pub cpp_ver: Option<String>,
 
-    #[arg(short,long,default_value=Config::get_time_limit(),help="The time limit for each test case, in milliseconds, default is the time limit in the config file, else 1000")]
-    pub timeout: u64,
+    #[arg(short,long,help="The time limit for each test case, in milliseconds, default is the time limit of the problem if it was found when adding the test, then the time limit in the config file, else 1000")]
+    pub timeout: Option<u64>,
 
-    #[arg(short,long,help="If used, will only run cases that have example in their name (These cases are automatically added for USACO, Codeforces, and AtCoder)")]
+    #[arg(short,long,help="If used, will only run cases that have example in their name (These cases are automatically added for USACO, Codeforces, AtCoder, CSES, Kattis, and oj.uz)")]
     pub example: bool,
+
+    #[arg(long, help = "Print the results as a single JSON document to stdout, everything else is printed to stderr")]
+    pub json: bool,
+
+    #[arg(long, help = "Write a Markdown report of the run to this file")]
+    pub report: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_parser = file_exists,
+        conflicts_with = "no_checker",
+        help = "A checker(.c, .cpp, .java, .py) that judges the output instead of comparing it, for problems with more than one answer. It is run like a testlib checker: checker <input> <output> <answer>, and accepts the output by exiting with 0. Defaults to the checker stored with the test"
+    )]
+    pub checker: Option<PathBuf>,
+
+    #[arg(long, requires = "checker", help = "Store --checker with the test, so later runs use it without --checker")]
+    pub save: bool,
+
+    #[arg(long, help = "Compare the output to the correct output even if the test has a checker")]
+    pub no_checker: bool,
+
+    #[arg(long, help = "Use the settings of this config tag(Created with `cp-tester config create-tag`) instead of the config tag of the test or the default config")]
+    pub config_tag: Option<String>,
+
+    #[arg(
+        long,
+        value_parser = validate_temp_dir,
+        help = "The folder the program is compiled and run in a temporary directory of, default is the config(Set with `cp-tester config set-temp-dir`), else the system temp directory"
+    )]
+    pub temp_dir: Option<PathBuf>,
+
+    #[arg(
+        long = "env",
+        value_name = "NAME=VALUE",
+        value_parser = parse_env_var,
+        help = "Set an environment variable for the program(Pass more than once for more), on top of the config(Set with `cp-tester config set-run-env`). Compilers and checkers don't get it"
+    )]
+    pub env: Vec<(String, String)>,
+
+    #[arg(long, help = "Run the program without the resource limits of the config(Set with `cp-tester config set-limit`)")]
+    pub no_limits: bool,
+
+    #[arg(
+        short,
+        long,
+        help = "Print the environment variables and resource limits the program is given, and what the compiler prints while it compiles"
+    )]
+    pub verbose: bool,
+}
+
+fn parse_env_var(env_var: &str) -> Result<(String, String), String> {
+    let (name, value) = handle_option!(env_var.split_once('='), format!("Invalid environment variable \"{}\", use NAME=VALUE", env_var));
+    Ok((check_env_name(name)?, value.to_string()))
+}
+
+fn validate_temp_dir(path: &str) -> Result<PathBuf, String> {
+    paths::check_temp_dir(Path::new(path))
 }
 
 pub enum FileType {
@@ -50,23 +177,163 @@ pub enum FileType {
     PYTHON,
 }
 
+impl FileType {
+    // A stored checker can be any file, so this is checked again when it is compiled
+    fn from_path(file_path: &Path, cpp_ver: &str) -> Result<FileType, String> {
+        match file_path.extension().and_then(|extension| extension.to_str()) {
+            Some("cpp") => Ok(FileType::CPP(handle_error!(cpp_ver.parse::<i32>(), format!("Invalid C++ version: {}", cpp_ver)))),
+            Some("c") => Ok(FileType::C),
+            Some("java") => Ok(FileType::JAVA),
+            Some("py") => Ok(FileType::PYTHON),
+            _ => Err(format!(
+                "Can't run {:?}, only C(.c), C++(.cpp), Java(.java), and Python(.py) files are supported",
+                file_path
+            )),
+        }
+    }
+}
+
 #[derive(Debug)]
 struct RunCommand(Command);
 
+// Compiled in its own directory, so it doesn't replace the compiled program
+#[derive(Debug)]
+struct Checker {
+    _dir: TempDir,
+    run_command: RunCommand,
+}
+
+// A failed compile isn't an error of the program, so it is kept separate to be reported in --json
+#[derive(Debug)]
+enum CompileResult {
+    Compiled(RunCommand),
+    Failed(String),
+}
+
+// Schema of --json output, fields should only be added, not renamed or removed
+#[derive(Debug, Serialize)]
+pub struct RunReport {
+    pub test: String,
+    pub file: PathBuf,
+    pub compile: CompileReport,
+    pub cases: Vec<CaseReport>,
+    // Only for tests with subtasks
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub subtasks: Vec<SubtaskScore>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_score: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CompileReport {
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diagnostics: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CaseReport {
+    pub name: String,
+    #[serde(serialize_with = "serialize_verdict")]
+    pub verdict: Verdict,
+    pub time_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_kb: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_diff_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stderr_tail: Option<String>,
+}
+
+// The --json names of the verdicts are part of its schema, so they don't change with the names in history.json
+fn serialize_verdict<S: Serializer>(verdict: &Verdict, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(match verdict {
+        Verdict::PASSED => "passed",
+        Verdict::FAILED => "failed",
+        Verdict::TIMEOUT => "timeout",
+        Verdict::NOT_JUDGED => "not judged",
+        Verdict::RUNTIME_ERROR => "runtime error",
+    })
+}
+
 #[derive(Debug)]
 pub struct RunDir {
     temp_dir: TempDir,
-    run_command: RunCommand,
+    run_command: Option<RunCommand>,
+    compile_diagnostics: Option<String>,
+    compile_description: String,
     input_file: Option<PathBuf>,
     output_file: Option<PathBuf>,
     show_input: bool,
     compare_output: bool,
+    diff: bool,
+    preview_lines: Option<usize>,
     test: Test,
     unicode_output: bool,
     timeout: u64,
+    json: bool,
+    checker: Option<Checker>,
+    // The output of cases without a correct output is printed instead of skipped
+    adhoc: bool,
+    // Set on the program, None removes the variable
+    run_env: Vec<(String, Option<String>)>,
+    // Installed on the program when it is compiled, set again for each test since they depend on the time limit
+    child_limits: Option<Arc<ChildLimits>>,
+}
+
+// Human readable output goes to stderr with --json, so stdout only has the JSON document
+macro_rules! output {
+    ($json:expr, $($arg:tt)*) => {
+        if $json {
+            eprint!($($arg)*)
+        } else {
+            print!($($arg)*)
+        }
+    };
+}
+
+macro_rules! outputln {
+    ($json:expr) => {
+        output!($json, "\n")
+    };
+    ($json:expr, $($arg:tt)*) => {{
+        output!($json, $($arg)*);
+        output!($json, "\n");
+    }};
+}
+
+// outputln! for the code that runs more than one test
+pub fn output_line(json: bool, line: &str) {
+    outputln!(json, "{}", line);
+}
+
+// Printed after running more than one test, not_run are the tests that didn't exist or failed to run
+pub fn print_summary(reports: &[RunReport], not_run: &[&str], json: bool) {
+    outputln!(json, "Summary:");
+    for report in reports {
+        outputln!(json, "\t{}", report.summary());
+    }
+    for test_name in not_run {
+        outputln!(json, "\t{}: Not run", test_name);
+    }
+    let passed = reports.iter().map(|report| report.passed_cases()).sum::<usize>();
+    let cases = reports.iter().map(|report| report.cases.len()).sum::<usize>();
+    outputln!(json, "Passed {} of {} cases in {} of {} tests", passed, cases, reports.len(), reports.len() + not_run.len());
+}
+
+fn language_name(extension: &str) -> &str {
+    match extension {
+        "c" => "C",
+        "cpp" => "C++",
+        "java" => "Java",
+        "py" => "Python",
+        _ => extension,
+    }
 }
 
-fn file_exists(file: &str) -> Result<PathBuf, String> {
+pub fn file_exists(file: &str) -> Result<PathBuf, String> {
     let path = PathBuf::from(file);
     if !path.exists() {
         return Err(format!("There is no file at path: \"{}\"", file));
@@ -92,159 +359,790 @@ fn file_exists(file: &str) -> Result<PathBuf, String> {
             };
         }
     }
-    let path = handle_error!(path.canonicalize(), "Failed to canonicalize(Find absolute path), to file");
+    let path = handle_error!(paths::canonicalize(&path), "Failed to canonicalize(Find absolute path), to file");
     Ok(path)
 }
 
+impl RunArgs {
+    // CLI args always override the project config, which overrides the config file
+    // The C++ version of the config file is set by apply_config, since the config tag of the test is only known once the test is
+    pub fn apply_project_config(&mut self, project_config: Option<ProjectConfig>) -> Result<(), String> {
+        // Test names can't end with the extension of a language, so the last name is the file if it is one
+        if let Some(file) = self.tests.last().and_then(|last| file_exists(last).ok()) {
+            if self.file.is_some() {
+                return Err("Pass the file to run after the test names or with --file, not both".to_string());
+            }
+            self.file = Some(file);
+            self.tests.pop();
+        }
+        if self.adhoc && !self.tests.is_empty() {
+            return Err("--adhoc doesn't run a stored test, only pass the file to run".to_string());
+        }
+        if let Some(project_config) = project_config {
+            if self.tests.is_empty() && !self.adhoc {
+                self.tests.extend(project_config.test.clone());
+            }
+            if self.file.is_none() {
+                if let Some(file) = project_config.get_file() {
+                    self.file = Some(handle_error!(
+                        file_exists(&file.to_string_lossy()),
+                        "Invalid file in project config file(.cptester.json)"
+                    ));
+                }
+            }
+            if self.cpp_ver.is_none() {
+                if let Some(cpp_ver) = &project_config.cpp_ver {
+                    if !["20", "17", "14", "11"].contains(&cpp_ver.as_str()) {
+                        return Err(format!(
+                            "Invalid C++ version \"{}\" in project config file(.cptester.json), should be 20, 17, 14, or 11",
+                            cpp_ver
+                        ));
+                    }
+                    self.cpp_ver = Some(cpp_ver.clone());
+                }
+            }
+        }
+        if self.tests.is_empty() && !self.adhoc {
+            return Err("No test given, pass a test name or create a project config file with `cp-tester init`".to_string());
+        }
+        Ok(())
+    }
+
+    // Without --file or a project config, the file of the last run of the test is used if it still exists, else the one in the current directory
+    pub fn resolve_file(&mut self, last_file: Option<PathBuf>) -> Result<(), String> {
+        if self.file.is_some() {
+            return Ok(());
+        }
+        if let Some(file) = last_file.and_then(|file| file_exists(&file.to_string_lossy()).ok()) {
+            outputln!(self.json, "Using {}, the file of the last run", file.file_name().unwrap_or_default().to_string_lossy());
+            self.file = Some(file);
+            return Ok(());
+        }
+        self.file = Some(self.detect_file()?);
+        Ok(())
+    }
+
+    // The solution file in the current directory
+    // When there is more than one, they are listed to pick from if there is a terminal to ask in
+    fn detect_file(&self) -> Result<PathBuf, String> {
+        let current_dir = handle_error!(env::current_dir(), "Failed to get current directory");
+        let entries = handle_error!(fs::read_dir(&current_dir), "Failed to read current directory");
+        let mut files = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| file_exists(&entry.path().to_string_lossy()).ok())
+            .collect::<Vec<PathBuf>>();
+        files.sort();
+        let file_name = |file: &PathBuf| file.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let file = match files.len() {
+            0 => {
+                return Err(
+                    "No file given and there is no .c, .cpp, .java, or .py file in the current directory, pass a file after the test name or create a project config file with `cp-tester init`"
+                        .to_string(),
+                )
+            }
+            1 => files.remove(0),
+            // --adhoc reads the input from stdin, so it can't be used to pick
+            _ if !io::stdin().is_terminal() || !io::stderr().is_terminal() || (self.adhoc && self.input_file.is_none()) => {
+                return Err(format!(
+                    "No file given and there is more than one in the current directory({}), pass the one to run after the test name",
+                    files.iter().map(file_name).collect::<Vec<String>>().join(", ")
+                ))
+            }
+            _ => {
+                for (i, file) in files.iter().enumerate() {
+                    eprintln!("{}: {}", i + 1, file_name(file));
+                }
+                eprint!("File to run [1-{}]: ", files.len());
+                handle_error!(io::stderr().flush(), "Failed to flush stderr");
+                let mut line = String::new();
+                handle_error!(io::stdin().read_line(&mut line), "Failed to read from stdin");
+                let choice = line.trim().parse::<usize>().ok().filter(|choice| (1..=files.len()).contains(choice));
+                let choice = handle_option!(choice, format!("\"{}\" isn't one of the files, it should be a number from 1 to {}", line.trim(), files.len()));
+                files.remove(choice - 1)
+            }
+        };
+        outputln!(self.json, "Using {}", file_name(&file));
+        Ok(file)
+    }
+}
+
+impl RunArgs {
+    // The test of --adhoc, its output is only compared if --expected-file is given
+    pub fn adhoc_test(&self) -> Result<Test, String> {
+        let input = match &self.input_file {
+            Some(input_file) => handle_error!(fs::read(input_file), format!("Failed to read input file {:?}", input_file)),
+            None => {
+                if io::stdin().is_terminal() {
+                    eprintln!("Paste the input, then press Ctrl-D(Ctrl-Z then Enter on Windows) to run it");
+                }
+                let mut input = vec![];
+                handle_error!(io::stdin().read_to_end(&mut input), "Failed to read input from stdin");
+                input
+            }
+        };
+        let expected = match &self.expected_file {
+            Some(expected_file) => Some(handle_error!(fs::read(expected_file), format!("Failed to read expected file {:?}", expected_file))),
+            None => None,
+        };
+        Ok(Test::adhoc(TestCase::new(input, expected)))
+    }
+
+    // --config-tag, then the config tag of the test, then the default config
+    pub fn load_config(&mut self, test: &Test) -> Result<Config, String> {
+        let config_tag = self.config_tag.as_ref().or(test.config_tag.as_ref());
+        let config = Config::get_tagged(config_tag.map(|tag| tag.as_str()))?;
+        if self.cpp_ver.is_none() {
+            self.cpp_ver = Some(config.default_cpp_ver.to_string());
+        }
+        self.show_input.get_or_insert(config.default_show_input);
+        self.compare_output.get_or_insert(config.default_compare_output);
+        self.diff.get_or_insert(config.default_diff);
+        if self.temp_dir.is_none() {
+            self.temp_dir = config.temp_dir.clone();
+        }
+        Ok(config)
+    }
+}
+
 impl RunDir {
-    pub fn new(test: &Test, args: &RunArgs, config: &Config) -> Result<RunDir, String> {
+    // test_dir is where the checker stored with the test is
+    pub fn new(test: &Test, test_dir: &Path, args: &RunArgs, config: &Config) -> Result<RunDir, Error> {
         let mut test = test.clone();
         test.set_cases(&args.cases,args.example)?;
-        let temp_dir = handle_error!(TempDir::new(), "Failed to create temporary directory");
-        let temp_dir_path = temp_dir.path().to_path_buf();
-        let run_command = RunCommand::new(&temp_dir_path, &args.file, &args.cpp_ver, &config)?;
-        let (input_file, output_file) = test.get_files(&temp_dir_path);
-        Ok(RunDir {
+        let temp_dir = TempDir::new(args.temp_dir.as_deref())
+            .context(format!("Failed to create temporary directory in {:?}", args.temp_dir.clone().unwrap_or_else(env::temp_dir)))?;
+        let mut run_dir = RunDir {
             temp_dir,
-            run_command,
-            input_file,
-            output_file,
-            show_input: args.show_input,
-            compare_output: args.compare_output,
+            run_command: None,
+            compile_diagnostics: None,
+            compile_description: String::new(),
+            input_file: None,
+            output_file: None,
+            show_input: false,
+            compare_output: false,
+            diff: false,
+            preview_lines: None,
             test,
-            unicode_output: config.get_unicode_output(),
-            timeout: args.timeout,
-        })
-    }
-    pub fn run(&mut self) -> Result<(), String> {
-        for (name, case) in self.test.case_iter() {
-            print!("Test Case {}: ", name);
-            handle_error!(
-                io::stdout().flush(),
-                "Failed to flush stdout, used to display test case name before any error"
+            unicode_output: false,
+            timeout: 0,
+            json: args.json,
+            checker: None,
+            adhoc: args.adhoc,
+            run_env: vec![],
+            child_limits: None,
+        };
+        run_dir.load(test_dir, args, config)?;
+        Ok(run_dir)
+    }
+    // For running more than one test, the file is only compiled again if the config of the test compiles it differently
+    pub fn set_test(&mut self, test: &Test, test_dir: &Path, args: &RunArgs, config: &Config) -> Result<(), Error> {
+        let mut test = test.clone();
+        test.set_cases(&args.cases,args.example)?;
+        self.test = test;
+        Ok(self.load(test_dir, args, config)?)
+    }
+    // Compiles the file and sets everything that depends on the test
+    fn load(&mut self, test_dir: &Path, args: &RunArgs, config: &Config) -> Result<(), String> {
+        let file = handle_option!(args.file.as_ref(), "No file given to run");
+        let cpp_ver = handle_option!(args.cpp_ver.as_ref(), "No C++ version given");
+        let temp_dir_path = self.temp_dir.path().to_path_buf();
+        let compile_description = RunCommand::describe(file, cpp_ver, config)?;
+        if compile_description != self.compile_description {
+            (self.run_command, self.compile_diagnostics) = match RunCommand::compile(&temp_dir_path, file, cpp_ver, config, args.verbose)? {
+                CompileResult::Compiled(run_command) => (Some(run_command), None),
+                CompileResult::Failed(diagnostics) => (None, Some(diagnostics)),
+            };
+            self.child_limits = self.run_command.as_mut().map(|run_command| ChildLimits::install(&mut run_command.0));
+            self.compile_description = compile_description;
+        }
+        let run_env = config.get_run_env(&args.env);
+        if let Some(run_command) = self.run_command.as_mut() {
+            run_command.set_env(&self.run_env, &run_env);
+        }
+        if args.verbose {
+            let described = run_env
+                .iter()
+                .map(|(name, value)| match value {
+                    Some(value) => format!("{}={}", name, value),
+                    None => format!("{} removed", name),
+                })
+                .collect::<Vec<String>>();
+            outputln!(args.json, "Environment: {}", if described.is_empty() { "Inherited".to_string() } else { described.join(", ") });
+        }
+        self.run_env = run_env;
+        let test = &self.test;
+        let (input_file, output_file) = test.get_files(&temp_dir_path.join(CASE_DIR_NAME));
+        let timeout = match (args.timeout, test.time_limit_ms) {
+            (Some(timeout), _) => timeout,
+            (None, Some(time_limit_ms)) => {
+                outputln!(args.json, "Using problem time limit: {} ms", time_limit_ms);
+                time_limit_ms
+            }
+            (None, None) => config.default_timeout,
+        };
+        // USACO gives slower languages more time, a given --timeout is used as it is
+        let is_usaco = test.submission_data.as_ref().is_some_and(|submission_data| submission_data.submission_type == SubmissionType::USACO);
+        let extension = file.extension().unwrap_or_default().to_string_lossy().to_string();
+        let multiplier = config.usaco_time_multipliers.get(&extension).copied().unwrap_or(1.0);
+        let timeout = if args.timeout.is_none() && is_usaco && multiplier > 0.0 && multiplier != 1.0 {
+            let scaled_timeout = (timeout as f64 * multiplier) as u64;
+            outputln!(
+                args.json,
+                "{} detected: using {}× time limit = {} ms",
+                language_name(&extension),
+                multiplier,
+                scaled_timeout
             );
-            let run_command = &mut self.run_command.0;
+            scaled_timeout
+        } else {
+            timeout
+        };
+        let checker_path = match (&args.checker, &test.checker) {
+            _ if args.no_checker => None,
+            (Some(checker), _) => Some(checker.clone()),
+            (None, Some(checker)) => Some(test_dir.join(checker)),
+            (None, None) => None,
+        };
+        self.checker = match checker_path {
+            Some(checker_path) => {
+                outputln!(args.json, "Using checker {}", checker_path.file_name().unwrap_or_default().to_string_lossy());
+                let input_path = input_file.clone().unwrap_or(temp_dir_path.join("tmp.in"));
+                let checker_files = [input_path, temp_dir_path.join(CHECKER_OUTPUT_FILE), temp_dir_path.join(CHECKER_ANSWER_FILE)];
+                Some(Checker::new(&checker_path, &checker_files, cpp_ver, config, args.temp_dir.as_deref(), args.verbose)?)
+            }
+            None => None,
+        };
+        let limits = (!args.no_limits).then_some(&config.limits);
+        if let Some(child_limits) = &self.child_limits {
+            child_limits.set(limits, timeout);
+        }
+        if args.verbose {
+            outputln!(args.json, "Limits: {}", limits.map_or("None".to_string(), |limits| limits.describe(Some(timeout))));
+        }
+        self.input_file = input_file;
+        self.output_file = output_file;
+        self.show_input = args.show_input.unwrap_or_default();
+        self.compare_output = args.compare_output.unwrap_or_default();
+        self.diff = args.diff.unwrap_or_default();
+        self.preview_lines = config.preview_lines(args.full);
+        self.unicode_output = config.get_unicode_output();
+        self.timeout = timeout;
+        Ok(())
+    }
+    pub fn get_compile_diagnostics(&self) -> Option<&String> {
+        self.compile_diagnostics.as_ref()
+    }
+    pub fn get_compile_description(&self) -> &String {
+        &self.compile_description
+    }
+    pub fn run(&mut self) -> Result<Vec<CaseResult>, String> {
+        let mut results = vec![];
+        let json = self.json;
+        let run_command = handle_option!(self.run_command.as_mut(), "File failed to compile, can't run it");
+        let run_command = &mut run_command.0;
+        let stderr_path = self.temp_dir.path().join("tmp.err");
+        let case_dir = self.temp_dir.path().join(CASE_DIR_NAME);
+        // In a terminal a ticker shows which case is running, and the name is printed with the result instead
+        let ticker = !json && io::stdout().is_terminal() && io::stderr().is_terminal();
+        let case_count = self.test.cases.len();
+        for (i, (name, case)) in self.test.case_iter().enumerate() {
+            if !ticker {
+                output!(json, "Test Case {}: ", name);
+                handle_error!(
+                    io::stdout().flush(),
+                    "Failed to flush stdout, used to display test case name before any error"
+                );
+            }
+            handle_error!(paths::remove_dir_all(&case_dir), "Failed to remove the directory of the last case");
+            handle_error!(fs::create_dir(&case_dir), "Failed to create directory to run the case in");
             if let Some(file) = &self.input_file {
                 case.write_input(file, name)?;
+                // Otherwise a program that reads stdin anyway waits on the terminal until it times out
+                run_command.stdin(Stdio::null());
             } else {
                 let input_path = self.temp_dir.path().join("tmp.in");
                 case.write_input(&input_path, name)?;
                 let input_file = handle_error!(File::open(input_path), "Failed to open input file");
                 run_command.stdin(input_file);
             }
-            run_command.current_dir(self.temp_dir.path());
+            // The end of stderr is kept for --json, otherwise the program writes to the terminal
+            if json {
+                let stderr_file = handle_error!(File::create(&stderr_path), "Failed to create file for program stderr");
+                run_command.stderr(stderr_file);
+            }
+            run_command.current_dir(&case_dir);
             let timeout = Duration::from_millis(self.timeout);
 
-            let mut run_command = handle_error!(run_command.spawn(), "Failed to spawn thread for program");
+            let progress_bar = ticker.then(|| case_ticker(i + 1, case_count, name));
+            let mut child = handle_error!(run_command.spawn(), "Failed to spawn thread for program");
             let now = Instant::now();
-            let output = handle_error!(run_command.wait_timeout(timeout), "Failed to wait for program to finish");
+            let output = handle_error!(child.wait_timeout(timeout), "Failed to wait for program to finish");
             let time_taken = now.elapsed().as_micros() as f64 / 1000.0;
+            if let Some(progress_bar) = progress_bar {
+                progress_bar.finish_and_clear();
+                output!(json, "Test Case {}: ", name);
+            }
+            let stderr_tail = if json { get_stderr_tail(&stderr_path) } else { None };
 
             if output.is_none() {
-                println!("Program timed out in {} ms", timeout.as_millis());
+                // Otherwise it keeps running after cp-tester exits
+                let _ = child.kill();
+                let _ = child.wait();
+                outputln!(json, "Program timed out in {} ms", timeout.as_millis());
                 let fail_symbol = match self.unicode_output {
-                    true => "\x1b[31m❌\x1b[0m",
-                    false => "FAILED",
+                    true => color::red("❌"),
+                    false => "FAILED".to_string(),
                 };
-                println!("{}", fail_symbol);
+                outputln!(json, "{}", fail_symbol);
+                let mut result = CaseResult::new(
+                    name,
+                    Verdict::TIMEOUT,
+                    time_taken,
+                    Some(format!("Program timed out in {} ms", timeout.as_millis())),
+                );
+                result.stderr_tail = stderr_tail;
+                results.push(result);
                 continue;
             }
 
             let exit_status = output.unwrap();
             if !exit_status.success() {
-                return Err(format!("\nProgram exited with non-zero exit code: {}", exit_status.code().unwrap()));
+                // There is no exit code when the program is killed by a signal on Unix
+                let message = match exit_status.code() {
+                    Some(code) => format!("Program exited with non-zero exit code: {}", code),
+                    None => match limits::exceeded_limit(&exit_status) {
+                        Some(limit) => format!("Program was killed by a signal: {}", limit),
+                        None => "Program was killed by a signal".to_string(),
+                    },
+                };
+                outputln!(json, "{}", message);
+                outputln!(json, "{}", if self.unicode_output { color::red("❌") } else { "FAILED".to_string() });
+                let mut result = CaseResult::new(name, Verdict::RUNTIME_ERROR, time_taken, Some(message));
+                result.stderr_tail = stderr_tail;
+                results.push(result);
+                continue;
             }
             let output = if let Some(file) = &self.output_file {
+                // The case directory is new, so there is no output file from an earlier case to read instead
+                if !file.exists() {
+                    let message = format!("Program didn't create the output file({})", file.file_name().unwrap_or_default().to_string_lossy());
+                    outputln!(json, "{}", message);
+                    outputln!(json, "{}", if self.unicode_output { color::red("❌") } else { "FAILED".to_string() });
+                    let mut result = CaseResult::new(name, Verdict::FAILED, time_taken, Some(message));
+                    result.stderr_tail = stderr_tail;
+                    results.push(result);
+                    continue;
+                }
                 handle_error!(
                     fs::read(file),
-                    format!(
-                        "\nFailed to read from output file({}), most likely means your program doesn't create the necessary file",
-                        file.file_name().unwrap().to_str().unwrap()
-                    )
+                    format!("\nFailed to read from output file({})", file.file_name().unwrap_or_default().to_string_lossy())
                 )
             } else {
-                run_command.stdout.take().unwrap().bytes().map(|b| b.unwrap()).collect::<Vec<u8>>()
+                let mut output = vec![];
+                handle_error!(child.stdout.take().unwrap().read_to_end(&mut output), "Failed to read program output");
+                output
             };
-            let output = handle_error!(String::from_utf8(output), "Failed to turn output into valid UTF-8");
             handle_error!(io::stdout().flush(), "\nFailed to flush stdout");
             if self.show_input {
-                println!();
-                println!("Input:");
-                println!(
+                outputln!(json);
+                outputln!(json, "Input:");
+                outputln!(
+                    json,
                     "{}",
-                    case.get_input().lines().map(|l| format!("\t{}", l)).collect::<Vec<String>>().join("\n")
+                    case.preview_input(self.preview_lines)?.lines().map(|l| format!("\t{}", l)).collect::<Vec<String>>().join("\n")
                 );
             }
             if self.compare_output {
-                println!();
-                println!("Correct Output:");
-                println!(
+                outputln!(json);
+                outputln!(json, "Correct Output:");
+                outputln!(
+                    json,
                     "{}",
-                    case.get_output().lines().map(|l| format!("\t{}", l)).collect::<Vec<String>>().join("\n")
+                    case.preview_output(self.preview_lines)?.lines().map(|l| format!("\t{}", l)).collect::<Vec<String>>().join("\n")
                 );
-                println!("Program Output:");
-                println!("{}", output.lines().map(|l| format!("\t{}", l)).collect::<Vec<String>>().join("\n"));
+                outputln!(json, "Program Output:");
+                outputln!(json, "{}", preview_bytes(&output, self.preview_lines, output.len() as u64).lines().map(|l| format!("\t{}", l)).collect::<Vec<String>>().join("\n"));
             }
-            println!("{} milliseconds", time_taken);
+            outputln!(json, "{} milliseconds", time_taken);
             let pass_symbol = match self.unicode_output {
                 true => "✅",
                 false => "PASSED",
             };
             let fail_symbol = match self.unicode_output {
-                true => "\x1b[31m❌\x1b[0m",
-                false => "FAILED",
+                true => color::red("❌"),
+                false => "FAILED".to_string(),
             };
-            if case.get_output().trim() == output.trim() {
-                println!("{pass_symbol}");
+            // Compared as bytes, only ASCII whitespace is trimmed
+            let Some(expected) = case.get_output()? else {
+                if self.adhoc {
+                    outputln!(json, "Program Output:");
+                    outputln!(json, "{}", display_bytes(&output));
+                } else {
+                    outputln!(json, "No expected output — skipped comparison");
+                }
+                let mut result = CaseResult::new(name, Verdict::NOT_JUDGED, time_taken, None);
+                result.set_output(&display_bytes(output.trim_ascii()));
+                result.stderr_tail = stderr_tail;
+                results.push(result);
+                continue;
+            };
+            let mut result = if let Some(checker) = &mut self.checker {
+                handle_error!(fs::write(self.temp_dir.path().join(CHECKER_OUTPUT_FILE), &output), "Failed to write output for checker");
+                case.write_output(&self.temp_dir.path().join(CHECKER_ANSWER_FILE), name)?;
+                match checker.check()? {
+                    None => {
+                        outputln!(json, "{pass_symbol}");
+                        CaseResult::new(name, Verdict::PASSED, time_taken, None)
+                    }
+                    Some(message) => {
+                        outputln!(json, "{fail_symbol}");
+                        outputln!(json, "Checker: {}", message);
+                        let mut result = CaseResult::new(name, Verdict::FAILED, time_taken, Some(format!("Checker: {}", message)));
+                        result.set_output(&display_bytes(output.trim_ascii()));
+                        result
+                    }
+                }
+            } else if expected.trim_ascii() == output.trim_ascii() {
+                outputln!(json, "{pass_symbol}");
+                CaseResult::new(name, Verdict::PASSED, time_taken, None)
+            } else if let Some(alternate) = case.matching_alternate(&output)? {
+                outputln!(json, "{pass_symbol} (matches alternate output {})", alternate);
+                CaseResult::new(name, Verdict::PASSED, time_taken, None)
             } else {
-                println!("{fail_symbol}");
-            }
+                // The difference is shown from the main expected output, alternates are only mentioned
+                let mismatch = compare_outputs(&expected, &output);
+                let description = match case.alternates.len() {
+                    0 => mismatch.describe(),
+                    1 => format!("{}, the alternate output doesn't match either", mismatch.describe()),
+                    count => format!("{}, the {} alternate outputs don't match either", mismatch.describe(), count),
+                };
+                outputln!(json, "{} ({})", fail_symbol, description);
+                if let (true, Some(line)) = (self.diff, mismatch.line) {
+                    outputln!(json, "First difference, line {}:", line);
+                    outputln!(json, "\tExpected: {}", line_at(&expected, line));
+                    outputln!(json, "\tGot:      {}", line_at(&output, line));
+                }
+                let mut result = CaseResult::new(
+                    name,
+                    Verdict::FAILED,
+                    time_taken,
+                    Some(format!("Output differs from the correct output, {}", description)),
+                );
+                result.set_output(&display_bytes(output.trim_ascii()));
+                result.first_diff_line = mismatch.line;
+                result
+            };
+            result.stderr_tail = stderr_tail;
+            results.push(result);
         }
+        handle_error!(paths::remove_dir_all(&case_dir), "Failed to remove the directory of the last case");
+        Ok(results)
+    }
+}
+
+impl Checker {
+    // files are the input, output, and answer, they are the same paths for every case so the command can be reused
+    fn new(source: &Path, files: &[PathBuf; 3], cpp_ver: &str, config: &Config, temp_parent: Option<&Path>, verbose: bool) -> Result<Checker, String> {
+        let dir = handle_error!(TempDir::new(temp_parent), "Failed to create temporary directory for checker");
+        let mut run_command = match RunCommand::compile(dir.path(), source, cpp_ver, config, verbose)? {
+            CompileResult::Compiled(run_command) => run_command,
+            CompileResult::Failed(diagnostics) => return Err(format!("Failed to compile checker {:?}\n{}", source, diagnostics)),
+        };
+        run_command.0.args(files).current_dir(dir.path()).stdin(Stdio::null()).stderr(Stdio::piped());
+        Ok(Checker { _dir: dir, run_command })
+    }
+
+    // None if the output was accepted, else the message of the checker
+    fn check(&mut self) -> Result<Option<String>, String> {
+        let output = handle_error!(self.run_command.0.output(), "Failed to run checker");
+        if output.status.success() {
+            return Ok(None);
+        }
+        // testlib writes its message to stderr, other checkers usually to stdout
+        let message = [&output.stderr, &output.stdout]
+            .iter()
+            .map(|message| String::from_utf8_lossy(message).trim().to_string())
+            .find(|message| !message.is_empty())
+            .unwrap_or(format!("Exited with code {}", output.status.code().unwrap_or(-1)));
+        Ok(Some(message))
+    }
+}
+
+impl RunReport {
+    pub fn new(test: &str, file: &Path, compile_diagnostics: Option<&String>, results: &[CaseResult]) -> RunReport {
+        RunReport {
+            test: test.to_string(),
+            file: file.to_path_buf(),
+            compile: CompileReport {
+                success: compile_diagnostics.is_none(),
+                diagnostics: compile_diagnostics.cloned(),
+            },
+            cases: results
+                .iter()
+                .map(|result| CaseReport {
+                    name: result.name.clone(),
+                    verdict: result.verdict.clone(),
+                    time_ms: result.time_ms,
+                    memory_kb: None,
+                    first_diff_line: result.first_diff_line,
+                    stderr_tail: result.stderr_tail.clone(),
+                })
+                .collect(),
+            subtasks: vec![],
+            score: None,
+            max_score: None,
+        }
+    }
+
+    // case_names are all of the cases of the test, so subtasks with cases that weren't run don't score
+    pub fn score_subtasks(&mut self, subtasks: &[Subtask], case_names: &[&String]) {
+        if subtasks.is_empty() {
+            return;
+        }
+        let results = self.cases.iter().map(|case| (case.name.as_str(), case.verdict.is_pass())).collect::<Vec<(&str, bool)>>();
+        self.subtasks = Subtask::score_all(subtasks, case_names, &results);
+        let (score, max_score) = total_score(&self.subtasks);
+        self.score = Some(score);
+        self.max_score = Some(max_score);
+    }
+
+    // Printed after the cases of a test with subtasks
+    pub fn print_score(&self, json: bool) {
+        let (Some(score), Some(max_score)) = (self.score, self.max_score) else {
+            return;
+        };
+        outputln!(json, "Subtasks:");
+        for subtask in &self.subtasks {
+            outputln!(json, "\t{}", subtask.describe());
+        }
+        outputln!(json, "Score: {}/{}", score, max_score);
+    }
+
+    // The line printed after each test when running more than one
+    pub fn summary(&self) -> String {
+        match (self.score, self.max_score) {
+            (Some(score), Some(max_score)) => format!("{}: {}/{} passed, score {}/{}", self.test, self.passed_cases(), self.cases.len(), score, max_score),
+            _ => format!("{}: {}/{} passed", self.test, self.passed_cases(), self.cases.len()),
+        }
+    }
+
+    // Failed, timed out, and crashed cases
+    pub fn failed_cases(&self) -> usize {
+        self.wrong_answers() + self.timeouts_and_crashes()
+    }
+
+    pub fn wrong_answers(&self) -> usize {
+        self.cases.iter().filter(|case| case.verdict == Verdict::FAILED).count()
+    }
+
+    pub fn timeouts_and_crashes(&self) -> usize {
+        self.cases.iter().filter(|case| matches!(case.verdict, Verdict::TIMEOUT | Verdict::RUNTIME_ERROR)).count()
+    }
+
+    pub fn passed_cases(&self) -> usize {
+        self.cases.iter().filter(|case| case.verdict.is_pass()).count()
+    }
+
+    // One report is printed as it is, and more than one as an array, so runs of one test keep the same schema
+    pub fn print_all(reports: &[RunReport]) -> Result<(), String> {
+        let report = match reports {
+            [report] => serde_json::to_string_pretty(report),
+            reports => serde_json::to_string_pretty(reports),
+        };
+        let report = handle_error!(report, "Failed to serialize run results to JSON");
+        println!("{}", report);
         Ok(())
     }
 }
 
+// Where a wrong output first differs from the correct output, so the verdict, --diff, the history, and --json give the same line
+struct Mismatch {
+    // Line numbers start at 1, None when the only difference is whitespace at the end of lines
+    line: Option<usize>,
+    // None when the output is shorter or longer than that line
+    expected_line: Option<String>,
+    output_line: Option<String>,
+    // Lines of the output minus lines of the correct output
+    extra_lines: isize,
+}
+
+impl Mismatch {
+    // Like: first difference at line 1432: expected "Bessie", got "bessie", output is missing 5 lines
+    fn describe(&self) -> String {
+        let mut description = match self.line {
+            Some(line) => {
+                let quote = |text: &Option<String>| text.as_ref().map_or("no line".to_string(), |text| format!("\"{}\"", text));
+                format!("first difference at line {}: expected {}, got {}", line, quote(&self.expected_line), quote(&self.output_line))
+            }
+            None => "only whitespace at the end of lines differs".to_string(),
+        };
+        match self.extra_lines {
+            0 => {}
+            1 => description.push_str(", output has 1 extra line"),
+            -1 => description.push_str(", output is missing 1 line"),
+            extra_lines if extra_lines > 0 => description.push_str(&format!(", output has {} extra lines", extra_lines)),
+            extra_lines => description.push_str(&format!(", output is missing {} lines", -extra_lines)),
+        }
+        description
+    }
+}
+
+// Only called when the outputs aren't the same, trailing whitespace of lines is ignored to find the line
+fn compare_outputs(expected: &[u8], output: &[u8]) -> Mismatch {
+    let lines = |bytes: &[u8]| bytes.trim_ascii().split(|b| *b == b'\n').map(|l| l.trim_ascii_end().to_vec()).collect::<Vec<Vec<u8>>>();
+    let (expected_lines, output_lines) = (lines(expected), lines(output));
+    let line = (0..expected_lines.len().max(output_lines.len())).find(|i| expected_lines.get(*i) != output_lines.get(*i)).map(|i| i + 1);
+    Mismatch {
+        line,
+        expected_line: line.filter(|line| *line <= expected_lines.len()).map(|line| line_at(expected, line)),
+        output_line: line.filter(|line| *line <= output_lines.len()).map(|line| line_at(output, line)),
+        extra_lines: output_lines.len() as isize - expected_lines.len() as isize,
+    }
+}
+
+// The line as compare_outputs counts them, long lines are cut like previews
+fn line_at(bytes: &[u8], line: usize) -> String {
+    match bytes.trim_ascii().split(|b| *b == b'\n').nth(line - 1) {
+        Some(text) => {
+            let text = String::from_utf8_lossy(text.trim_ascii_end());
+            match text.char_indices().nth(PREVIEW_LINE_CHARS) {
+                Some((end, _)) => format!("{}...", &text[..end]),
+                None => text.to_string(),
+            }
+        }
+        None => "(no line)".to_string(),
+    }
+}
+
+fn get_stderr_tail(stderr_path: &Path) -> Option<String> {
+    const STDERR_TAIL_LINES: usize = 20;
+    let stderr = fs::read(stderr_path).ok()?;
+    let stderr = String::from_utf8_lossy(&stderr);
+    let lines = stderr.lines().collect::<Vec<&str>>();
+    if lines.is_empty() {
+        return None;
+    }
+    Some(lines[lines.len().saturating_sub(STDERR_TAIL_LINES)..].join("\n"))
+}
+
+// Returns the diagnostics of the compiler if it fails or takes longer than the compile timeout of the config
+// Output is read while it compiles, so what a compiler that hangs wrote before it was stopped is still shown
+fn run_compiler(mut compile_command: Command, config: &Config, verbose: bool) -> Result<Option<String>, String> {
+    compile_command.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+    let mut child = handle_error!(compile_command.spawn(), "Failed to compile file");
+    let (stdout, stdout_reader) = read_pipe(child.stdout.take(), false);
+    let (stderr, stderr_reader) = read_pipe(child.stderr.take(), verbose);
+    let collected = |output: &Arc<Mutex<Vec<u8>>>| output.lock().map(|output| String::from_utf8_lossy(&output).to_string()).unwrap_or_default();
+    let status = match config.compile_timeout {
+        0 => Some(handle_error!(child.wait(), "Failed to wait for compiler to finish")),
+        timeout => handle_error!(child.wait_timeout(Duration::from_secs(timeout)), "Failed to wait for compiler to finish"),
+    };
+    let Some(status) = status else {
+        let _ = child.kill();
+        let _ = child.wait();
+        // Programs the compiler started(Like cc1plus) can still have the pipes open, so the readers aren't waited for
+        return Ok(Some(format!("Compilation timed out after {}s\nStderr so far: {}", config.compile_timeout, collected(&stderr))));
+    };
+    let _ = stdout_reader.join();
+    let _ = stderr_reader.join();
+    if status.success() {
+        return Ok(None);
+    }
+    Ok(Some(format!(
+        "Compiler exited with non-zero exit code: {}\nStdout: {}\nStderr: {}",
+        status.code().map(|code| code.to_string()).unwrap_or("None".to_string()),
+        collected(&stdout),
+        collected(&stderr)
+    )))
+}
+
+// Collects a pipe of the compiler on another thread, and prints each line to stderr as it comes with echo(--verbose)
+fn read_pipe(pipe: Option<impl Read + Send + 'static>, echo: bool) -> (Arc<Mutex<Vec<u8>>>, JoinHandle<()>) {
+    let output = Arc::new(Mutex::new(vec![]));
+    let thread_output = Arc::clone(&output);
+    let reader = thread::spawn(move || {
+        let Some(pipe) = pipe else {
+            return;
+        };
+        let mut pipe = BufReader::new(pipe);
+        let mut line = vec![];
+        while pipe.read_until(b'\n', &mut line).is_ok_and(|read| read > 0) {
+            if echo {
+                let _ = io::stderr().write_all(&line);
+            }
+            if let Ok(mut output) = thread_output.lock() {
+                output.extend_from_slice(&line);
+            }
+            line.clear();
+        }
+    });
+    (output, reader)
+}
+
 impl RunCommand {
-    fn new(temp_path: &PathBuf, file_path: &PathBuf, cpp_ver: &String, config: &Config) -> Result<Self, String> {
-        let file_type = match file_path.extension().unwrap().to_str().unwrap() {
-            "cpp" => FileType::CPP(cpp_ver.parse().unwrap()),
-            "c" => FileType::C,
-            "java" => FileType::JAVA,
-            "py" => FileType::PYTHON,
-            _ => unreachable!("Invalid file extension"),
+    // Variables set for the last test that this one doesn't set go back to what cp-tester has, since the program is reused
+    fn set_env(&mut self, old_env: &[(String, Option<String>)], run_env: &[(String, Option<String>)]) {
+        for (name, _) in old_env {
+            match env::var_os(name) {
+                Some(value) => self.0.env(name, value),
+                None => self.0.env_remove(name),
+            };
+        }
+        for (name, value) in run_env {
+            match value {
+                Some(value) => self.0.env(name, value),
+                None => self.0.env_remove(name),
+            };
+        }
+    }
+    // The compiler(Or interpreter) and flags used for a file, without temp paths, for reports
+    fn describe(file_path: &Path, cpp_ver: &str, config: &Config) -> Result<String, String> {
+        let command = match FileType::from_path(file_path, cpp_ver)? {
+            FileType::CPP(ver) => {
+                let mut command = config.get_command("g++");
+                command.arg(format!("-std=c++{}", ver));
+                command
+            }
+            FileType::C => config.get_command("gcc"),
+            FileType::JAVA => config.get_command("javac"),
+            FileType::PYTHON => config.get_command("python3"),
         };
-        let mut run_command = match file_type {
+        let mut description = vec![command.get_program().to_string_lossy().to_string()];
+        for arg in command.get_args() {
+            description.push(arg.to_string_lossy().to_string());
+        }
+        // Sorting so the description is stable, since flags come from a HashMap
+        description[1..].sort();
+        Ok(description.join(" "))
+    }
+    fn compile(temp_path: &Path, file_path: &Path, cpp_ver: &str, config: &Config, verbose: bool) -> Result<CompileResult, String> {
+        let mut run_command = match FileType::from_path(file_path, cpp_ver)? {
             FileType::CPP(ver) => {
-                let mut compile_command = config.get_gpp_command();
-                compile_command.arg("-o").arg(temp_path.join("output"));
+                let mut compile_command = config.get_command("g++");
+                compile_command.arg("-o").arg(temp_path.join(paths::executable_name("output")));
                 compile_command.arg(format!("-std=c++{}", ver));
                 compile_command.arg(file_path);
-                let output = handle_error!(compile_command.output(), "Failed to compile file");
-                if !output.status.success() {
-                    return Err(format!(
-                        "Failed to compile file, exited with non-zero exit code: {}\nStdout: {}\nStderr: {}",
-                        output.status.code().unwrap(),
-                        String::from_utf8_lossy(&output.stdout),
-                        String::from_utf8_lossy(&output.stderr)
-                    ));
+                if let Some(diagnostics) = run_compiler(compile_command, config, verbose)? {
+                    return Ok(CompileResult::Failed(diagnostics));
                 }
-                // Using local address then will use env to make the location the temp dir, so it looks for files in the temp dir
-                let run_command = executable_run_command();
-                run_command
+                executable_run_command(temp_path)
             }
             FileType::C => {
-                let mut compile_command = config.get_gcc_command();
-                compile_command.arg("-o").arg(temp_path.join("output"));
+                let mut compile_command = config.get_command("gcc");
+                compile_command.arg("-o").arg(temp_path.join(paths::executable_name("output")));
                 compile_command.arg(file_path);
-                handle_error!(compile_command.output(), "Failed to compile file");
-                let run_command = executable_run_command();
-                run_command
+                if let Some(diagnostics) = run_compiler(compile_command, config, verbose)? {
+                    return Ok(CompileResult::Failed(diagnostics));
+                }
+                executable_run_command(temp_path)
             }
             FileType::JAVA => {
-                let mut compile_command = config.get_javac_command();
+                let mut compile_command = config.get_command("javac");
                 compile_command.arg(file_path);
                 compile_command.arg("-d").arg(temp_path);
-                handle_error!(compile_command.output(), "Failed to compile file");
+                if let Some(diagnostics) = run_compiler(compile_command, config, verbose)? {
+                    return Ok(CompileResult::Failed(diagnostics));
+                }
                 let mut class_name = temp_path.join(file_path.file_stem().unwrap());
                 let class_stem = class_name.clone();
                 class_name.set_extension("class");
@@ -254,29 +1152,172 @@ impl RunCommand {
                         class_name.to_str().unwrap()
                     ));
                 }
-                let mut run_command = config.get_java_command();
-                run_command.arg(class_stem.file_name().unwrap());
+                let mut run_command = config.get_command("java");
+                run_command.arg("-cp").arg(temp_path).arg(class_stem.file_name().unwrap());
                 run_command
             }
             FileType::PYTHON => {
-                let mut run_command = Command::new("python3");
-                run_command.arg("-O");
+                let mut run_command = config.get_command("python3");
                 run_command.arg(file_path);
                 run_command
             }
         };
         run_command.stdout(Stdio::piped());
-        Ok(RunCommand(run_command))
+        Ok(CompileResult::Compiled(RunCommand(run_command)))
     }
 }
 
-#[cfg(target_os = "windows")]
-fn executable_run_command() -> Command {
-    let mut tmp = Command::new("cmd");
-    tmp.args(["/c", ".\\output.exe"]);
-    tmp
+// "Running case 7/20" with the time it has been running, redrawn in place on stderr until the case finishes
+fn case_ticker(position: usize, case_count: usize, name: &str) -> ProgressBar {
+    let mut progress_bar = ProgressBar::with_draw_target(Some(case_count as u64), ProgressDrawTarget::stderr())
+        .with_position(position as u64)
+        .with_message(name.to_string());
+    if let Ok(style) = ProgressStyle::with_template("{spinner} Running case {pos}/{len}: {msg} ({elapsed})") {
+        progress_bar = progress_bar.with_style(style);
+    }
+    progress_bar.enable_steady_tick(TICKER_INTERVAL);
+    progress_bar
 }
-#[cfg(not(target_os = "windows"))]
-fn executable_run_command() -> Command {
-    Command::new("./output")
+
+// The full path, since the program is run in the directory of the case instead of where it was compiled
+fn executable_run_command(temp_path: &Path) -> Command {
+    Command::new(temp_path.join(paths::executable_name("output")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        case_naming::CaseNaming,
+        test_data::{IOType, ScanOptions},
+    };
+    use clap::Parser;
+
+    #[test]
+    fn file_type_from_extension() {
+        assert!(matches!(FileType::from_path(Path::new("sol.cpp"), "17"), Ok(FileType::CPP(17))));
+        assert!(matches!(FileType::from_path(Path::new("sol.c"), "17"), Ok(FileType::C)));
+        assert!(matches!(FileType::from_path(Path::new("Main.java"), "17"), Ok(FileType::JAVA)));
+        assert!(matches!(FileType::from_path(Path::new("sol.py"), "17"), Ok(FileType::PYTHON)));
+        assert!(FileType::from_path(Path::new("check.pas"), "17").is_err());
+        assert!(FileType::from_path(Path::new("checker"), "17").is_err());
+        assert!(FileType::from_path(Path::new("sol.cpp"), "latest").is_err());
+        assert!(RunCommand::describe(Path::new("check.pas"), "17", &Config::default()).is_err());
+    }
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        run: RunArgs,
+    }
+
+    #[test]
+    fn last_file_is_used_without_a_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let (last_file, given_file) = (dir.path().join("last.py"), dir.path().join("given.py"));
+        fs::write(&last_file, "print(1)").unwrap();
+        fs::write(&given_file, "print(2)").unwrap();
+
+        let mut args = Cli::parse_from(["run", "sum"]).run;
+        args.resolve_file(Some(last_file.clone())).unwrap();
+        assert_eq!(args.file, Some(paths::canonicalize(&last_file).unwrap()));
+
+        let mut args = Cli::parse_from(["run", "sum", "--file", &given_file.to_string_lossy()]).run;
+        args.resolve_file(Some(last_file)).unwrap();
+        assert_eq!(args.file, Some(paths::canonicalize(&given_file).unwrap()));
+    }
+
+    fn results() -> Vec<CaseResult> {
+        let mut failed = CaseResult::new("2", Verdict::FAILED, 12.5, Some("Wrong answer".to_string()));
+        failed.first_diff_line = Some(3);
+        vec![
+            CaseResult::new("1", Verdict::PASSED, 10.0, None),
+            failed,
+            CaseResult::new("3", Verdict::TIMEOUT, 2000.0, None),
+            CaseResult::new("4", Verdict::NOT_JUDGED, 1.0, None),
+            CaseResult::new("5", Verdict::RUNTIME_ERROR, 1.0, None),
+        ]
+    }
+
+    #[test]
+    fn json_report_schema() {
+        let report = RunReport::new("sum", Path::new("sol.cpp"), None, &results());
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["test"], "sum");
+        assert_eq!(json["file"], "sol.cpp");
+        assert_eq!(json["compile"], serde_json::json!({ "success": true }));
+        let verdicts = json["cases"].as_array().unwrap().iter().map(|case| case["verdict"].as_str().unwrap()).collect::<Vec<&str>>();
+        assert_eq!(verdicts, ["passed", "failed", "timeout", "not judged", "runtime error"]);
+        assert_eq!(json["cases"][1], serde_json::json!({ "name": "2", "verdict": "failed", "time_ms": 12.5, "first_diff_line": 3 }));
+        assert!(json.get("subtasks").is_none() && json.get("score").is_none());
+
+        let diagnostics = "error: expected ';'".to_string();
+        let report = RunReport::new("sum", Path::new("sol.cpp"), Some(&diagnostics), &[]);
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["compile"], serde_json::json!({ "success": false, "diagnostics": diagnostics }));
+        assert_eq!(json["cases"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn report_counts_verdicts() {
+        let report = RunReport::new("sum", Path::new("sol.cpp"), None, &results());
+        assert_eq!(report.passed_cases(), 1);
+        assert_eq!(report.wrong_answers(), 1);
+        assert_eq!(report.timeouts_and_crashes(), 2);
+        assert_eq!(report.failed_cases(), 3);
+        assert_eq!(report.summary(), "sum: 1/5 passed");
+    }
+
+    // Each case runs in a new directory, so an output file or scratch file from the case before it can't be read instead
+    #[cfg(unix)]
+    #[test]
+    fn cases_do_not_see_files_of_earlier_cases() {
+        let dir = tempfile::tempdir().unwrap();
+        let cases_dir = dir.path().join("cases");
+        fs::create_dir(&cases_dir).unwrap();
+        for case in 1..=4 {
+            fs::write(cases_dir.join(format!("{}.in", case)), format!("{}\n", case)).unwrap();
+            fs::write(cases_dir.join(format!("{}.out", case)), format!("{}\n", case)).unwrap();
+        }
+        let naming = CaseNaming::EXTENSIONS("in".to_string(), "out".to_string());
+        let (input_io, output_io) = (IOType::FILE(PathBuf::from("problem.in")), IOType::FILE(PathBuf::from("problem.out")));
+        let test = Test::from_folder(cases_dir, &naming, input_io, output_io, None, None, ScanOptions::default()).unwrap();
+        // Only writes the output of odd cases, and fails if a scratch file is already there
+        let program = dir.path().join("odd.py");
+        let source = concat!(
+            "import os\n",
+            "case = int(open('problem.in').read())\n",
+            "if os.path.exists('scratch') or os.path.exists('problem.out'):\n",
+            "    raise SystemExit(3)\n",
+            "open('scratch', 'w').write('x')\n",
+            "if case % 2 == 1:\n",
+            "    open('problem.out', 'w').write(f'{case}\\n')\n",
+        );
+        fs::write(&program, source).unwrap();
+        let mut args = Cli::parse_from(["run", "odd", "--file", &program.to_string_lossy()]).run;
+        args.cpp_ver = Some("17".to_string());
+        let mut run_dir = RunDir::new(&test, dir.path(), &args, &Config::default()).unwrap();
+        let results = run_dir.run().unwrap();
+        let verdicts = results.iter().map(|result| (result.name.as_str(), &result.verdict)).collect::<Vec<_>>();
+        assert_eq!(verdicts, [("1", &Verdict::PASSED), ("2", &Verdict::FAILED), ("3", &Verdict::PASSED), ("4", &Verdict::FAILED)]);
+        assert!(results[1].details.as_deref().unwrap().contains("didn't create the output file(problem.out)"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn compiler_that_hangs_is_stopped() {
+        let mut config = Config::default();
+        config.compile_timeout = 1;
+        let mut compile_command = Command::new("sh");
+        compile_command.arg("-c").arg("echo including >&2; sleep 10");
+        let diagnostics = run_compiler(compile_command, &config, false).unwrap().unwrap();
+        assert!(diagnostics.starts_with("Compilation timed out after 1s"));
+        assert!(diagnostics.contains("including"));
+
+        let mut compile_command = Command::new("sh");
+        compile_command.arg("-c").arg("echo bad code >&2; exit 1");
+        let diagnostics = run_compiler(compile_command, &config, false).unwrap().unwrap();
+        assert!(diagnostics.contains("exit code: 1") && diagnostics.contains("bad code"));
+        assert_eq!(run_compiler(Command::new("true"), &config, false), Ok(None));
+    }
 }
@@ -0,0 +1,131 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use clap::Args;
+
+use crate::{commands::run::file_exists, data_dir, error::Error, handle_error, handle_option, test_data::Test};
+
+// Checkers from Polygon include it, so it is copied with them when it is next to them
+const TESTLIB_FILE_NAME: &str = "testlib.h";
+
+#[derive(Debug, Args)]
+pub struct SetCheckerArgs {
+    #[arg(help = "The name of the test")]
+    test_name: String,
+
+    #[arg(
+        value_parser = file_exists,
+        required_unless_present = "remove",
+        help = "The checker(.c, .cpp, .java, .py), run like a testlib checker: checker <input> <output> <answer>, accepting the output by exiting with 0"
+    )]
+    checker: Option<PathBuf>,
+
+    #[arg(long, conflicts_with = "checker", help = "Remove the checker of the test, so runs compare the output again")]
+    remove: bool,
+}
+
+impl SetCheckerArgs {
+    pub fn run(&self, tests: &mut HashMap<String, Test>) -> Result<(), Error> {
+        let test = match tests.get_mut(&self.test_name) {
+            Some(test) => test,
+            None => return Err(Test::not_found(&self.test_name, tests)),
+        };
+        let test_dir = data_dir::get()?.join("tests").join(&self.test_name);
+        match &self.checker {
+            Some(checker) => {
+                save_checker(test, &test_dir, checker)?;
+                println!("Runs of \"{}\" use the checker {:?} now", self.test_name, checker.file_name().unwrap_or_default());
+            }
+            None => {
+                if test.checker.is_none() {
+                    return Err(format!("Test \"{}\" has no checker", self.test_name).into());
+                }
+                remove_checker(test, &test_dir)?;
+                println!("Removed the checker of \"{}\"", self.test_name);
+            }
+        }
+        Ok(())
+    }
+}
+
+// The checker keeps its file name, since Java checkers have to be named like their class
+pub fn save_checker(test: &mut Test, test_dir: &Path, source: &Path) -> Result<(), String> {
+    let file_name = handle_option!(source.file_name(), format!("Invalid checker path {:?}", source));
+    if test.checker.as_deref().is_some_and(|checker| checker != Path::new(file_name)) {
+        remove_checker(test, test_dir)?;
+    }
+    copy_checker(source, test_dir)?;
+    test.checker = Some(PathBuf::from(file_name));
+    Ok(())
+}
+
+// Copies the checker into the test's directory, with testlib.h if it is next to it
+pub fn copy_checker(source: &Path, test_dir: &Path) -> Result<(), String> {
+    handle_error!(fs::create_dir_all(test_dir), "Failed to create test directory");
+    let testlib = source.with_file_name(TESTLIB_FILE_NAME);
+    let files = if testlib.is_file() { vec![source.to_path_buf(), testlib] } else { vec![source.to_path_buf()] };
+    for file in files {
+        let destination = test_dir.join(handle_option!(file.file_name(), format!("Invalid checker path {:?}", file)));
+        // Setting the stored checker again would copy it onto itself, which empties it
+        if destination.canonicalize().ok() == file.canonicalize().ok() {
+            continue;
+        }
+        handle_error!(fs::copy(&file, &destination), format!("Failed to copy {:?} into the test directory", file));
+    }
+    Ok(())
+}
+
+// testlib.h was copied with the checker, so it goes with it
+fn remove_checker(test: &mut Test, test_dir: &Path) -> Result<(), String> {
+    if let Some(checker) = test.checker.take() {
+        for path in [test_dir.join(checker), test_dir.join(TESTLIB_FILE_NAME)] {
+            if path.is_file() {
+                handle_error!(fs::remove_file(&path), format!("Failed to remove old checker file {:?}", path));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_data::TestCase;
+
+    #[test]
+    fn checker_is_copied_and_removed_with_testlib() {
+        let source_dir = tempfile::tempdir().unwrap();
+        let test_dir = tempfile::tempdir().unwrap();
+        let checker = source_dir.path().join("check.cpp");
+        fs::write(&checker, "int main() {}").unwrap();
+        fs::write(source_dir.path().join(TESTLIB_FILE_NAME), "// testlib").unwrap();
+        let mut test = Test::adhoc(TestCase::new(vec![], None));
+
+        save_checker(&mut test, test_dir.path(), &checker).unwrap();
+        assert_eq!(test.checker, Some(PathBuf::from("check.cpp")));
+        assert!(test_dir.path().join("check.cpp").is_file());
+        assert!(test_dir.path().join(TESTLIB_FILE_NAME).is_file());
+
+        remove_checker(&mut test, test_dir.path()).unwrap();
+        assert_eq!(test.checker, None);
+        assert!(!test_dir.path().join("check.cpp").exists());
+        assert!(!test_dir.path().join(TESTLIB_FILE_NAME).exists());
+    }
+
+    #[test]
+    fn missing_tests_are_not_found_errors() {
+        let mut tests = HashMap::from([("sum".to_string(), Test::adhoc(TestCase::new(vec![], None)))]);
+        let args = SetCheckerArgs {
+            test_name: "sun".to_string(),
+            checker: None,
+            remove: true,
+        };
+        match args.run(&mut tests) {
+            Err(Error::TEST_NOT_FOUND(message)) => assert!(message.ends_with("Did you mean: sum?"), "{}", message),
+            other => panic!("Expected TEST_NOT_FOUND, got {:?}", other),
+        }
+    }
+}
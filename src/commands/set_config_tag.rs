@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+
+use clap::Args;
+
+use crate::{
+    config::Config,
+    error::{Context, Error},
+    test_data::Test,
+};
+
+#[derive(Debug, Args)]
+pub struct SetConfigTagArgs {
+    #[arg(help = "The name of the test")]
+    test_name: String,
+
+    #[arg(
+        required_unless_present = "remove",
+        help = "The config tag runs of the test use, created with `cp-tester config create-tag`"
+    )]
+    tag: Option<String>,
+
+    #[arg(
+        long,
+        conflicts_with = "tag",
+        help = "Remove the config tag of the test, so runs use the default config again"
+    )]
+    remove: bool,
+}
+
+impl SetConfigTagArgs {
+    pub fn run(&self, tests: &mut HashMap<String, Test>) -> Result<(), Error> {
+        let test = match tests.get_mut(&self.test_name) {
+            Some(test) => test,
+            None => return Err(Test::not_found(&self.test_name, tests)),
+        };
+        match &self.tag {
+            Some(tag) => {
+                // Loaded to check that the tag exists
+                Config::get_tagged(Some(tag)).context("Invalid config tag")?;
+                test.config_tag = Some(tag.clone());
+                println!("Runs of \"{}\" use the config tag \"{}\" now", self.test_name, tag);
+            }
+            None => {
+                if test.config_tag.take().is_none() {
+                    return Err(format!("Test \"{}\" has no config tag", self.test_name).into());
+                }
+                println!("Runs of \"{}\" use the default config now", self.test_name);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+use clap::Args;
+
+use crate::{
+    color, data_dir,
+    error::{Context, Error},
+    subtasks::Subtask,
+    test_data::Test,
+};
+
+#[derive(Debug, Args)]
+pub struct SetSubtasksArgs {
+    #[arg(help = "The name of the test")]
+    test_name: String,
+
+    #[arg(
+        help = "The subtasks, like 'subtask1=*:10,subtask2=big_*:40'(name=pattern:points, comma separated). Patterns are globs matched against case names, more than one can be separated by |. Prints the current subtasks if not given"
+    )]
+    subtasks: Option<String>,
+
+    #[arg(
+        long,
+        conflicts_with = "subtasks",
+        help = "Remove the subtasks of the test, so runs are only counted by cases again"
+    )]
+    remove: bool,
+}
+
+impl SetSubtasksArgs {
+    // Returns if the subtasks changed, so the tests only have to be written then
+    pub fn run(&self, tests: &mut HashMap<String, Test>) -> Result<bool, Error> {
+        let test = match tests.get_mut(&self.test_name) {
+            Some(test) => test,
+            None => return Err(Test::not_found(&self.test_name, tests)),
+        };
+        if self.remove {
+            if test.subtasks.is_empty() {
+                return Err(format!("Test \"{}\" has no subtasks", self.test_name).into());
+            }
+            test.subtasks.clear();
+            println!("Removed the subtasks of \"{}\"", self.test_name);
+            return Ok(true);
+        }
+        // Cases are loaded to show how many each subtask has
+        let test_dir = data_dir::get()?.join("tests").join(&self.test_name);
+        test.fill_cases(test_dir).context("Failed to load cases of the test")?;
+        let Some(spec) = &self.subtasks else {
+            if test.subtasks.is_empty() {
+                println!("Test \"{}\" has no subtasks", self.test_name);
+            } else {
+                print_subtasks(test, &test.subtasks);
+            }
+            test.cases.clear();
+            return Ok(false);
+        };
+        let subtasks = Subtask::parse_all(spec)?;
+        print_subtasks(test, &subtasks);
+        println!("Runs of \"{}\" are scored by {} subtasks now", self.test_name, subtasks.len());
+        test.subtasks = subtasks;
+        test.cases.clear();
+        Ok(true)
+    }
+}
+
+// One line per subtask with its amount of cases, subtasks without cases can never score so they are warned about
+fn print_subtasks(test: &Test, subtasks: &[Subtask]) {
+    let scores = Subtask::score_all(subtasks, &test.get_sorted_case_names(), &[]);
+    for (subtask, score) in subtasks.iter().zip(&scores) {
+        println!("{}: {} points, {} cases({})", subtask.name, subtask.points, score.cases, subtask.pattern);
+        if score.cases == 0 {
+            eprintln!("{}: Subtask \"{}\" matches no cases", color::yellow("WARNING"), subtask.name);
+        }
+    }
+}
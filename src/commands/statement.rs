@@ -0,0 +1,152 @@
+use std::{
+    collections::HashMap,
+    env, fs,
+    io::{IsTerminal, Write},
+    process::{Command, Stdio},
+};
+
+use clap::Args;
+use regex::Regex;
+
+use crate::{
+    commands::add::{decode_entities, get_link_html, SubmissionType},
+    data_dir,
+    error::{Context, Error},
+    handle_error,
+    test_data::Test,
+};
+
+// The downloaded problem page is cached in the test's directory, so it moves with renames and removes
+pub const STATEMENT_FILE_NAME: &str = "statement.html";
+const DEFAULT_PAGER: &str = "less";
+const PRE_REGEX_STR: &str = r"(?s)<pre[^>]*>(?<content>.*?)</pre>";
+
+#[derive(Debug, Args)]
+pub struct StatementArgs {
+    #[arg(help = "The name of the test to show the problem statement for")]
+    test: String,
+
+    #[arg(short, long, help = "Download the problem page again instead of using the cached copy")]
+    refresh: bool,
+
+    #[arg(long, help = "Print the cached HTML of the problem page instead of the statement")]
+    raw_html: bool,
+}
+
+impl StatementArgs {
+    pub fn run(&self, tests: &HashMap<String, Test>) -> Result<(), Error> {
+        let test = tests.get(&self.test).ok_or_else(|| Test::not_found(&self.test, tests))?;
+        let submission_data = test
+            .submission_data
+            .as_ref()
+            .context(format!("Test \"{}\" has no problem link, so there is no statement to show", self.test))?;
+        let data_dir = data_dir::get()?;
+        let statement_path = data_dir.join("tests").join(&self.test).join(STATEMENT_FILE_NAME);
+        let html = if statement_path.is_file() && !self.refresh {
+            fs::read_to_string(&statement_path).context(format!("Failed to read cached statement({:?})", statement_path))?
+        } else {
+            eprintln!("Downloading {}", submission_data.link);
+            let html = get_link_html(&submission_data.link)?;
+            fs::write(&statement_path, &html).context(format!("Failed to cache statement({:?})", statement_path))?;
+            html
+        };
+        if self.raw_html {
+            println!("{}", html);
+            return Ok(());
+        }
+        let statement = html_to_text(extract_statement(&submission_data.submission_type, &html))?;
+        Ok(page(&statement)?)
+    }
+}
+
+// Only the part of the page with the statement is kept, from the start marker to the first end marker found
+// Falls back to the whole page if the layout has changed
+fn extract_statement<'a>(submission_type: &SubmissionType, html: &'a str) -> &'a str {
+    let (start, ends): (&str, &[&str]) = match submission_type {
+        SubmissionType::USACO => ("<h2> USACO", &["<p>Problem credits", "<form", "<script"]),
+        SubmissionType::CODEFORCES => ("<div class=\"problem-statement\">", &["<script"]),
+        // AtCoder has the statement in Japanese then English, only the English one is shown
+        SubmissionType::ATCODER => ("<span class=\"lang-en\">", &["<div class=\"a2a_kit", "<form", "<script"]),
+        SubmissionType::CSES => ("<div class=\"content\">", &["<div class=\"nav sidebar", "<script"]),
+        SubmissionType::KATTIS => ("<div class=\"problembody\">", &["<script"]),
+        SubmissionType::OJUZ => ("<div class=\"problem-view\"", &["<footer", "<script"]),
+    };
+    let html = match html.find(start) {
+        Some(index) => &html[index..],
+        None => html,
+    };
+    match ends.iter().filter_map(|end| html.find(end)).min() {
+        Some(index) => &html[..index],
+        None => html,
+    }
+}
+
+// Turns HTML into readable text with Markdown style headers and lists, <pre> blocks(Samples) are kept as they are in code blocks
+fn html_to_text(html: &str) -> Result<String, String> {
+    let pre_regex = handle_error!(Regex::new(PRE_REGEX_STR), "Failed to create regex for <pre> blocks");
+    let mut text = String::new();
+    let mut last = 0;
+    for cap in pre_regex.captures_iter(html) {
+        let whole = cap.get(0).unwrap();
+        text.push_str(&convert_block(&html[last..whole.start()])?);
+        let content = cap.name("content").unwrap().as_str();
+        let content = replace_all(r"(?i)<br\s*/?>|</div>", content, "\n")?;
+        let content = decode_entities(&replace_all(r"<[^>]*>", &content, "")?);
+        text.push_str(&format!("\n\n```\n{}\n```\n\n", content.trim_matches('\n')));
+        last = whole.end();
+    }
+    text.push_str(&convert_block(&html[last..])?);
+    let text = replace_all(r"\n[ \t]+", &text, "\n")?;
+    let text = replace_all(r"\n{3,}", &text, "\n\n")?;
+    Ok(text.trim().to_string())
+}
+
+fn convert_block(html: &str) -> Result<String, String> {
+    let html = replace_all(r"(?is)<(script|style)[^>]*>.*?</(script|style)>", html, "")?;
+    let html = replace_all(r"\s+", &html, " ")?;
+    let html = replace_all(r"(?i)<h[1-6][^>]*>", &html, "\n\n## ")?;
+    // Codeforces uses divs instead of headers for the sections of the statement
+    let html = replace_all(r#"<div class="(section-title|title)">"#, &html, "\n\n## ")?;
+    let html = replace_all(r"(?i)</h[1-6]>|<p[^>]*>|</p>", &html, "\n\n")?;
+    let html = replace_all(r"(?i)<li[^>]*>", &html, "\n- ")?;
+    let html = replace_all(r"(?i)<br\s*/?>|</div>|</ul>|</ol>", &html, "\n")?;
+    let html = replace_all(r"<[^>]*>", &html, "")?;
+    // Codeforces wraps math in $$$
+    let html = html.replace("$$$", "");
+    Ok(decode_entities(&html))
+}
+
+fn replace_all(regex: &str, text: &str, replacement: &str) -> Result<String, String> {
+    let regex = handle_error!(Regex::new(regex), format!("Failed to create regex from string - String is {}", regex));
+    Ok(regex.replace_all(text, replacement).to_string())
+}
+
+// Uses $PAGER(Or less) when printing to a terminal, otherwise prints normally
+fn page(text: &str) -> Result<(), String> {
+    if !std::io::stdout().is_terminal() {
+        println!("{}", text);
+        return Ok(());
+    }
+    let pager = env::var("PAGER").unwrap_or(DEFAULT_PAGER.to_string());
+    let mut pager_args = pager.split_whitespace();
+    let child = match pager_args.next() {
+        Some(program) => Command::new(program).args(pager_args).stdin(Stdio::piped()).spawn(),
+        None => {
+            println!("{}", text);
+            return Ok(());
+        }
+    };
+    let mut child = match child {
+        Ok(child) => child,
+        Err(_) => {
+            println!("{}", text);
+            return Ok(());
+        }
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // The pager closing early(Like quitting less) isn't an error
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    handle_error!(child.wait(), "Failed to wait for pager");
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+
+use clap::{Args, Subcommand};
+
+use crate::{error::Error, test_data::Test};
+
+#[derive(Args, Debug)]
+pub struct TagArgs {
+    #[command(subcommand)]
+    tag_command: TagCommands,
+}
+
+#[derive(Subcommand, Debug)]
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+enum TagCommands {
+    #[command(about = "Add tags to a test", arg_required_else_help = true)]
+    ADD(TagTestArgs),
+
+    #[command(about = "Remove tags from a test", arg_required_else_help = true)]
+    REMOVE(TagTestArgs),
+}
+
+#[derive(Args, Debug)]
+struct TagTestArgs {
+    #[arg(help = "The name of the test")]
+    test_name: String,
+
+    #[arg(required = true, value_delimiter = ',', help = "Tags(Space or comma separated), tags are stored in lowercase")]
+    tags: Vec<String>,
+}
+
+impl TagArgs {
+    pub fn run(&self, tests: &mut HashMap<String, Test>) -> Result<(), Error> {
+        match &self.tag_command {
+            TagCommands::ADD(args) => {
+                let test = get_test(tests, &args.test_name)?;
+                let added = test.add_tags(&args.tags);
+                if added.is_empty() {
+                    println!("Test \"{}\" already has all of the given tags", args.test_name);
+                } else {
+                    println!("Added tags to \"{}\": {}", args.test_name, added.join(", "));
+                }
+            }
+            TagCommands::REMOVE(args) => {
+                let test = get_test(tests, &args.test_name)?;
+                let removed = test.remove_tags(&args.tags);
+                if removed.is_empty() {
+                    println!("Test \"{}\" has none of the given tags", args.test_name);
+                } else {
+                    println!("Removed tags from \"{}\": {}", args.test_name, removed.join(", "));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn get_test<'a>(tests: &'a mut HashMap<String, Test>, test_name: &str) -> Result<&'a mut Test, Error> {
+    if !tests.contains_key(test_name) {
+        return Err(Test::not_found(test_name, tests));
+    }
+    Ok(tests.get_mut(test_name).unwrap())
+}
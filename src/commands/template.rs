@@ -0,0 +1,170 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::Local;
+use clap::Args;
+
+use crate::{
+    error::{Context, Error},
+    handle_option,
+    test_data::{IOType, Test},
+    DEFAULT_FOLDER_NAME,
+};
+
+pub const TEMPLATE_LANGS: [&str; 4] = ["c", "cpp", "java", "py"];
+
+// Lines with {{input_file}} or {{output_file}} are dropped for tests that use standard IO, so the freopen lines only show up when they are needed
+// Lines with {{problem_link}} are dropped for tests without a link
+const DEFAULT_C_TEMPLATE: &str = "// {{test_name}}
+// {{problem_link}}
+// {{date}}
+#include <stdio.h>
+
+int main() {
+    freopen(\"{{input_file}}\", \"r\", stdin);
+    freopen(\"{{output_file}}\", \"w\", stdout);
+
+    return 0;
+}
+";
+
+const DEFAULT_CPP_TEMPLATE: &str = "// {{test_name}}
+// {{problem_link}}
+// {{date}}
+#include <bits/stdc++.h>
+using namespace std;
+
+int main() {
+    freopen(\"{{input_file}}\", \"r\", stdin);
+    freopen(\"{{output_file}}\", \"w\", stdout);
+    ios::sync_with_stdio(false);
+    cin.tie(nullptr);
+
+    return 0;
+}
+";
+
+const DEFAULT_JAVA_TEMPLATE: &str = "// {{test_name}}
+// {{problem_link}}
+// {{date}}
+import java.io.*;
+import java.util.*;
+
+public class {{class_name}} {
+    public static void main(String[] args) throws IOException {
+        BufferedReader in = new BufferedReader(new InputStreamReader(System.in));
+        PrintWriter out = new PrintWriter(new BufferedWriter(new OutputStreamWriter(System.out)));
+        in = new BufferedReader(new FileReader(\"{{input_file}}\"));
+        out = new PrintWriter(new BufferedWriter(new FileWriter(\"{{output_file}}\")));
+
+        out.close();
+    }
+}
+";
+
+const DEFAULT_PYTHON_TEMPLATE: &str = "# {{test_name}}
+# {{problem_link}}
+# {{date}}
+import sys
+
+sys.stdin = open(\"{{input_file}}\", \"r\")
+sys.stdout = open(\"{{output_file}}\", \"w\")
+";
+
+#[derive(Debug, Args)]
+pub struct TemplateArgs {
+    #[arg(help = "The name of the test to create a solution file for")]
+    test: String,
+
+    #[arg(short, long, default_value = "cpp", value_parser = TEMPLATE_LANGS, help = "The language of the solution file")]
+    lang: String,
+
+    #[arg(short, long, help = "The file to create, defaults to sol with the extension of the language")]
+    output: Option<PathBuf>,
+
+    #[arg(long, help = "Overwrite the file if it already exists")]
+    force: bool,
+}
+
+impl TemplateArgs {
+    pub fn run(&self, tests: &HashMap<String, Test>) -> Result<(), Error> {
+        let test = tests.get(&self.test).ok_or_else(|| Test::not_found(&self.test, tests))?;
+        let output = match &self.output {
+            Some(output) => output.clone(),
+            None => PathBuf::from(format!("sol.{}", self.lang)),
+        };
+        if output.exists() && !self.force {
+            return Err(format!("File {:?} already exists, use --force to overwrite it", output).into());
+        }
+        let template_path = get_template_path(&self.lang)?;
+        let template = if template_path.is_file() {
+            fs::read_to_string(&template_path).context(format!("Failed to read template({:?})", template_path))?
+        } else {
+            get_default_template(&self.lang).to_string()
+        };
+        let solution = fill_template(&template, &self.test, test, &output);
+        fs::write(&output, solution).context(format!("Failed to write solution file({:?})", output))?;
+        println!("Created {:?} for test \"{}\"", output, self.test);
+        Ok(())
+    }
+}
+
+// Custom templates are copied here by `cp-tester config set-template`
+pub fn get_template_path(lang: &str) -> Result<PathBuf, String> {
+    let config_dir = handle_option!(
+        dirs::config_local_dir(),
+        "Failed to get config directory, not sure why this should happen, look into dirs::config_local_dir() to find more about error"
+    );
+    Ok(config_dir.join(DEFAULT_FOLDER_NAME).join("templates").join(format!("template.{}", lang)))
+}
+
+fn get_default_template(lang: &str) -> &'static str {
+    match lang {
+        "c" => DEFAULT_C_TEMPLATE,
+        "cpp" => DEFAULT_CPP_TEMPLATE,
+        "java" => DEFAULT_JAVA_TEMPLATE,
+        "py" => DEFAULT_PYTHON_TEMPLATE,
+        _ => unreachable!("Invalid template language"),
+    }
+}
+
+fn fill_template(template: &str, test_name: &str, test: &Test, output: &Path) -> String {
+    let problem_link = match &test.submission_data {
+        Some(submission_data) => submission_data.link.clone(),
+        None => String::new(),
+    };
+    let class_name = output.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+    let mut lines = vec![];
+    for line in template.lines() {
+        if matches!(test.input_io, IOType::STD) && line.contains("{{input_file}}") {
+            continue;
+        }
+        if matches!(test.output_io, IOType::STD) && line.contains("{{output_file}}") {
+            continue;
+        }
+        if test.submission_data.is_none() && line.contains("{{problem_link}}") {
+            continue;
+        }
+        let line = line
+            .replace("{{test_name}}", test_name)
+            .replace("{{problem_link}}", &problem_link)
+            .replace("{{date}}", &Local::now().format("%Y-%m-%d").to_string())
+            .replace("{{class_name}}", &class_name)
+            .replace("{{input_file}}", &io_file_name(&test.input_io))
+            .replace("{{output_file}}", &io_file_name(&test.output_io));
+        lines.push(line);
+    }
+    let mut solution = lines.join("\n");
+    solution.push('\n');
+    solution
+}
+
+fn io_file_name(io: &IOType) -> String {
+    match io {
+        IOType::STD => String::new(),
+        IOType::FILE(path) => path.to_string_lossy().to_string(),
+    }
+}
@@ -1,195 +1,875 @@
-use std::{collections::HashMap, fmt, fs, process::Command};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt, fs,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{Mutex, OnceLock},
+};
 
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
 
-use crate::{handle_error, handle_option, DEFAULT_FOLDER_NAME};
+use crate::{
+    color::{self, ColorSetting},
+    error::{Context, Error},
+    handle_error, handle_option, json_file,
+    limits::Limits,
+    paths, toml_file, DEFAULT_FOLDER_NAME,
+};
 
 const DEFAULT_CPP_VER: i32 = 17;
 const DEFAULT_TIME_LIMIT: u64 = 5000;
+const DEFAULT_COMPILE_TIMEOUT_SECS: u64 = 60;
+const DEFAULT_HISTORY_LIMIT: usize = 20;
+const DEFAULT_DOWNLOAD_RETRIES: u32 = 3;
+const DEFAULT_MAX_CASE_SIZE_MB: u64 = 512;
+const DEFAULT_PREVIEW_LINES: usize = 20;
+pub const DEFAULT_INPUT_EXTENSION: &str = "in";
+pub const DEFAULT_OUTPUT_EXTENSION: &str = "out";
+// USACO gives Java and Python more time than C and C++, by file extension
+const DEFAULT_USACO_TIME_MULTIPLIERS: [(&str, f64); 2] = [("java", 2.0), ("py", 4.0)];
+// Some judges(Codeforces behind Cloudflare) block requests that don't look like they come from a browser
+const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36";
+// Amount of characters of a cookie shown by `config print`
+const COOKIE_PREVIEW_LENGTH: usize = 6;
+// Defined by many judges, so solutions can tell they are being judged(Like to skip reading from a file)
+const ONLINE_JUDGE_VAR: &str = "ONLINE_JUDGE";
+// Names `config get` and `config unset` take, with the setting in config.json they are for
+pub const CONFIG_KEYS: [(&str, &str); 28] = [
+    ("cpp-ver", "default_cpp_ver"),
+    ("unicode", "unicode_output"),
+    ("color", "color"),
+    ("timeout", "default_timeout"),
+    ("compile-timeout", "compile_timeout"),
+    ("language-flags", "language_flags"),
+    ("history-limit", "history_limit"),
+    ("download-retries", "download_retries"),
+    ("user-agent", "user_agent"),
+    ("http-headers", "http_headers"),
+    ("cookies", "cookies"),
+    ("proxy", "proxy"),
+    ("usaco-time-multipliers", "usaco_time_multipliers"),
+    ("compress-test-data", "compress_test_data"),
+    ("keep-source-archives", "keep_source_archives"),
+    ("max-case-size", "max_case_size_mb"),
+    ("preview-lines", "preview_lines"),
+    ("input-extension", "default_input_extension"),
+    ("output-extension", "default_output_extension"),
+    ("compare-output", "default_compare_output"),
+    ("show-input", "default_show_input"),
+    ("diff", "default_diff"),
+    ("run-env", "run_env"),
+    ("online-judge", "online_judge"),
+    ("limits", "limits"),
+    ("editor", "editor"),
+    ("data-dir", "data_dir"),
+    ("temp-dir", "temp_dir"),
+];
+// Settings that are only read to move them somewhere else, so they aren't unknown
+const OLD_SETTINGS: [&str; 5] = ["cookie", "gcc_flags", "gpp_flags", "java_flags", "javac_flags"];
+// Older config files had a setting for the flags of each tool, they are moved into language_flags
+const OLD_FLAG_SETTINGS: [(&str, &str); 4] = [("gcc_flags", "gcc"), ("gpp_flags", "g++"), ("java_flags", "java"), ("javac_flags", "javac")];
+// The compilers and interpreters cp-tester runs, which flags can be set for
+pub const LANGUAGE_TOOLS: [&str; 5] = ["gcc", "g++", "java", "javac", "python3"];
 
-#[derive(Debug, Serialize, Deserialize)]
+static CONFIG_FILE: OnceLock<ConfigFile> = OnceLock::new();
+
+// config.toml is used instead of config.json when it exists, JSON is the default
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConfigFormat {
+    JSON,
+    TOML,
+}
+
+// Set with `cp-tester config set-online-judge`, inherit leaves ONLINE_JUDGE as it is in the environment of cp-tester
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum OnlineJudgeSetting {
+    INHERIT,
+    SET,
+    CLEAR,
+}
+
+// The settings each tag changes, by tag name
+type Tags = HashMap<String, Map<String, Value>>;
+
+// Older config files were only the default config, they are moved into default_config when loaded
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ConfigFile {
     default_config: Config,
-    tags: HashMap<String, Option<Config>>,
+    // The settings each tag changes, by their name in config.json, settings a tag doesn't change come from the default config
+    #[serde(default)]
+    tags: Tags,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+// Settings missing from config.json, like ones added in a newer version, are the default
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default = "Config::default")]
 pub struct Config {
     pub(crate) default_cpp_ver: i32,
     pub(crate) unicode_output: bool,
+    // Colors of errors and warnings, auto only uses them when the output is a terminal
+    #[serde(default = "default_color")]
+    pub(crate) color: ColorSetting,
     pub(crate) default_timeout: u64,
-    pub(crate) gcc_flags: HashMap<String, String>,
-    pub(crate) gpp_flags: HashMap<String, String>,
-    pub(crate) java_flags: HashMap<String, String>,
-    pub(crate) javac_flags: HashMap<String, String>,
+    // Seconds a compiler can take before it is killed, so a hanging compile doesn't stop run forever, 0 for no limit
+    #[serde(default = "default_compile_timeout")]
+    pub(crate) compile_timeout: u64,
+    // Tool(g++, python3) to its flags, a flag with a value is passed as flag=value
+    #[serde(default = "default_language_flags")]
+    pub(crate) language_flags: HashMap<String, HashMap<String, String>>,
+    #[serde(default = "default_history_limit")]
+    pub(crate) history_limit: usize,
+    #[serde(default = "default_download_retries")]
+    pub(crate) download_retries: u32,
+    #[serde(default = "default_user_agent")]
+    pub(crate) user_agent: String,
+    #[serde(default)]
+    pub(crate) http_headers: HashMap<String, String>,
+    // Older configs had one cookie sent to every site, it is removed when the config is loaded
+    #[serde(default, skip_serializing)]
+    cookie: Option<String>,
+    // Domain to the Cookie header sent to it and its subdomains
+    #[serde(default)]
+    pub(crate) cookies: HashMap<String, String>,
+    // Used instead of HTTP_PROXY and HTTPS_PROXY when set
+    #[serde(default)]
+    pub(crate) proxy: Option<String>,
+    #[serde(default = "default_usaco_time_multipliers")]
+    pub(crate) usaco_time_multipliers: HashMap<String, f64>,
+    // New tests are stored as .zst files, existing tests are converted with `cp-tester migrate-storage`
+    #[serde(default)]
+    pub(crate) compress_test_data: bool,
+    // Archives downloaded by add are kept in archives/ so `cp-tester restore` can extract them again
+    #[serde(default)]
+    pub(crate) keep_source_archives: bool,
+    // Inputs and outputs bigger than this are skipped when adding, 0 for no limit
+    #[serde(default = "default_max_case_size_mb")]
+    pub(crate) max_case_size_mb: u64,
+    // Lines of each input and output shown by list test and run, 0 for all of them
+    #[serde(default = "default_preview_lines")]
+    pub(crate) preview_lines: usize,
+    // Used by add when no extension is given, without a dot
+    #[serde(default = "default_input_extension")]
+    pub(crate) default_input_extension: String,
+    #[serde(default = "default_output_extension")]
+    pub(crate) default_output_extension: String,
+    // Used by run when --compare-output, --show-input, or --diff aren't given
+    #[serde(default)]
+    pub(crate) default_compare_output: bool,
+    #[serde(default)]
+    pub(crate) default_show_input: bool,
+    #[serde(default)]
+    pub(crate) default_diff: bool,
+    // Environment variables run gives the program it judges, compilers and checkers don't get them
+    #[serde(default)]
+    pub(crate) run_env: HashMap<String, String>,
+    // Whether the judged program gets ONLINE_JUDGE=1, doesn't get it, or gets it only if cp-tester did
+    #[serde(default = "default_online_judge")]
+    pub(crate) online_judge: OnlineJudgeSetting,
+    // Resource limits of the judged program on Unix, turned off for a run with --no-limits
+    #[serde(default)]
+    pub(crate) limits: Limits,
+    // Used by `config edit` and `note` when $VISUAL and $EDITOR aren't set
+    #[serde(default)]
+    pub(crate) editor: Option<String>,
+    // Where test.json and tests/ are, the default data directory when unset
+    #[serde(default)]
+    pub(crate) data_dir: Option<PathBuf>,
+    // Where run compiles and runs programs, the system temp directory when unset
+    #[serde(default)]
+    pub(crate) temp_dir: Option<PathBuf>,
+}
+
+fn default_color() -> ColorSetting {
+    ColorSetting::AUTO
+}
+
+fn default_online_judge() -> OnlineJudgeSetting {
+    OnlineJudgeSetting::INHERIT
+}
+
+fn default_compile_timeout() -> u64 {
+    DEFAULT_COMPILE_TIMEOUT_SECS
+}
+
+fn default_history_limit() -> usize {
+    DEFAULT_HISTORY_LIMIT
+}
+
+fn default_download_retries() -> u32 {
+    DEFAULT_DOWNLOAD_RETRIES
+}
+
+fn default_max_case_size_mb() -> u64 {
+    DEFAULT_MAX_CASE_SIZE_MB
+}
+
+fn default_preview_lines() -> usize {
+    DEFAULT_PREVIEW_LINES
+}
+
+fn default_input_extension() -> String {
+    DEFAULT_INPUT_EXTENSION.to_string()
+}
+
+fn default_output_extension() -> String {
+    DEFAULT_OUTPUT_EXTENSION.to_string()
+}
+
+fn default_language_flags() -> HashMap<String, HashMap<String, String>> {
+    let c_flags = HashMap::from([("-O2".to_string(), "".to_string()), ("-lm".to_string(), "".to_string())]);
+    HashMap::from([
+        ("gcc".to_string(), c_flags.clone()),
+        ("g++".to_string(), c_flags),
+        ("python3".to_string(), HashMap::from([("-O".to_string(), "".to_string())])),
+    ])
+}
+
+fn default_user_agent() -> String {
+    DEFAULT_USER_AGENT.to_string()
+}
+
+fn default_usaco_time_multipliers() -> HashMap<String, f64> {
+    DEFAULT_USACO_TIME_MULTIPLIERS
+        .iter()
+        .map(|(extension, multiplier)| (extension.to_string(), *multiplier))
+        .collect()
 }
 
 impl Config {
     pub fn default() -> Config {
-        let mut gcc_flags = HashMap::new();
-        let mut gpp_flags = HashMap::new();
-        let java_flags = HashMap::new();
-        let javac_flags = HashMap::new();
-        gcc_flags.insert("-O2".to_string(), "".to_string());
-        gpp_flags.insert("-O2".to_string(), "".to_string());
-        gcc_flags.insert("-lm".to_string(), "".to_string());
-        gpp_flags.insert("-lm".to_string(), "".to_string());
         Config {
-            gcc_flags,
-            gpp_flags,
-            java_flags,
-            javac_flags,
+            language_flags: default_language_flags(),
             default_timeout: DEFAULT_TIME_LIMIT,
             default_cpp_ver: DEFAULT_CPP_VER,
+            compile_timeout: DEFAULT_COMPILE_TIMEOUT_SECS,
             unicode_output: false,
+            color: ColorSetting::AUTO,
+            history_limit: DEFAULT_HISTORY_LIMIT,
+            download_retries: DEFAULT_DOWNLOAD_RETRIES,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            http_headers: HashMap::new(),
+            cookie: None,
+            cookies: HashMap::new(),
+            proxy: None,
+            usaco_time_multipliers: default_usaco_time_multipliers(),
+            compress_test_data: false,
+            keep_source_archives: false,
+            max_case_size_mb: DEFAULT_MAX_CASE_SIZE_MB,
+            preview_lines: DEFAULT_PREVIEW_LINES,
+            default_input_extension: DEFAULT_INPUT_EXTENSION.to_string(),
+            default_output_extension: DEFAULT_OUTPUT_EXTENSION.to_string(),
+            default_compare_output: false,
+            default_show_input: false,
+            default_diff: false,
+            run_env: HashMap::new(),
+            online_judge: OnlineJudgeSetting::INHERIT,
+            limits: Limits::default(),
+            editor: None,
+            data_dir: None,
+            temp_dir: None,
+        }
+    }
+    // None when whole cases are shown
+    pub fn preview_lines(&self, full: bool) -> Option<usize> {
+        if full || self.preview_lines == 0 {
+            None
+        } else {
+            Some(self.preview_lines)
         }
     }
     pub fn get() -> Result<Config, String> {
-        let config_dir = handle_option!(
-            dirs::config_local_dir(),
-            "Failed to get config directory, not sure why this should happen, look into dirs::config_local_dir() to find more about error"
-        );
-        let config_dir = config_dir.join(DEFAULT_FOLDER_NAME);
-        if !config_dir.exists() {
-            handle_error!(fs::create_dir_all(&config_dir), "Failed to create config directory");
+        Ok(ConfigFile::get()?.default_config)
+    }
+    // The default config with the settings the tag changes, None is the default config
+    pub fn get_tagged(tag: Option<&str>) -> Result<Config, String> {
+        let config_file = ConfigFile::get()?;
+        match tag {
+            Some(tag) => config_file.tag_config(tag),
+            None => Ok(config_file.default_config),
         }
-        if !config_dir.is_dir() {
-            return Err(format!("Config directory: {:?} is not a directory", config_dir));
+    }
+    // The tool with the flags set for it
+    pub fn get_command(&self, tool: &str) -> Command {
+        let mut command = Command::new(paths::program_name(tool));
+        for (flag, value) in self.language_flags.get(tool).into_iter().flatten() {
+            command.arg(format!("{}{}{}", flag, if value.is_empty() { "" } else { "=" }, value));
         }
-        let config_path = config_dir.join("config.json");
-        let config: Config = if config_path.exists() {
-            let config_file = handle_error!(fs::read_to_string(&config_path), "Failed to read config file");
-            handle_error!(serde_json::from_str(&config_file), "Failed to parse config file")
-        } else {
-            let config = Config::default();
-            let config_file = handle_error!(serde_json::to_string_pretty(&config), "Failed to serialize config file");
-            handle_error!(fs::write(&config_path, config_file), "Failed to write config file");
-            config
-        };
+        command
+    }
+    // Config tags are kept, unless the config file can't be read
+    pub fn reset() -> Result<(), String> {
+        let mut config_file = ConfigFile::load().unwrap_or(ConfigFile::new(Config::default()));
+        config_file.default_config = Config::default();
+        config_file.save()?;
+        println!("Config file reset to default");
+        Ok(())
+    }
+    // The cookie of the longest domain that is the host of the link or a parent of it, so a cookie for atcoder.jp is also sent to img.atcoder.jp
+    pub fn get_cookie(&self, host: &str) -> Option<&String> {
+        let host = host.to_ascii_lowercase();
+        self.cookies
+            .iter()
+            .filter(|(domain, _)| host == **domain || host.ends_with(&format!(".{}", domain)))
+            .max_by_key(|(domain, _)| domain.len())
+            .map(|(_, cookie)| cookie)
+    }
+    // Variables set for the judged program, sorted by name, None removes it
+    // ONLINE_JUDGE comes first, so run_env and then extra(From --env) can change it
+    pub fn get_run_env(&self, extra: &[(String, String)]) -> Vec<(String, Option<String>)> {
+        let mut run_env = BTreeMap::new();
+        match self.online_judge {
+            OnlineJudgeSetting::INHERIT => {}
+            OnlineJudgeSetting::SET => {
+                run_env.insert(ONLINE_JUDGE_VAR.to_string(), Some("1".to_string()));
+            }
+            OnlineJudgeSetting::CLEAR => {
+                run_env.insert(ONLINE_JUDGE_VAR.to_string(), None);
+            }
+        }
+        for (name, value) in self.run_env.iter().chain(extra.iter().map(|(name, value)| (name, value))) {
+            run_env.insert(name.clone(), Some(value.clone()));
+        }
+        run_env.into_iter().collect()
+    }
+    pub fn get_unicode_output(&self) -> bool {
+        self.unicode_output
+    }
+    // Tags are kept as they are
+    pub fn save(&self) -> Result<(), Error> {
+        let mut config_file = ConfigFile::load()?;
+        config_file.default_config = self.clone();
+        Ok(config_file.save()?)
+    }
+    // Unknown settings and values of the wrong type are left out with a warning, so only invalid JSON stops the config from loading
+    fn from_settings(settings: Map<String, Value>, source: &str) -> Result<Config, String> {
+        let mut config = Config::default().to_value()?;
+        let mut unknown = vec![];
+        let mut invalid = vec![];
+        for (key, value) in settings {
+            if !config.contains_key(&key) && !OLD_SETTINGS.contains(&key.as_str()) {
+                unknown.push(key);
+                continue;
+            }
+            let mut with_setting = config.clone();
+            with_setting.insert(key.clone(), value);
+            match serde_json::from_value::<Config>(Value::Object(with_setting.clone())) {
+                Ok(_) => config = with_setting,
+                Err(error) => invalid.push(format!("{}({})", key, error)),
+            }
+        }
+        if !unknown.is_empty() {
+            warn_once(format!("Unknown settings in {} are ignored: {}", source, unknown.join(", ")));
+        }
+        if !invalid.is_empty() {
+            warn_once(format!("Invalid settings in {} use their default: {}", source, invalid.join(", ")));
+        }
+        Ok(handle_error!(serde_json::from_value(Value::Object(config)), format!("Failed to load {}", source)))
+    }
+    // The value of a setting as it would be typed, maps have one entry per line, flags like they are passed to the compiler
+    pub fn get_setting(&self, setting: &str) -> Result<String, String> {
+        let value = handle_option!(self.to_value()?.remove(setting), format!("Unknown setting \"{}\"", setting));
+        let separator = if setting.ends_with("_flags") { "=" } else { ": " };
+        Ok(match value {
+            Value::Null => String::new(),
+            Value::String(string) => string,
+            Value::Object(map) => {
+                let mut lines = map
+                    .into_iter()
+                    .map(|(key, value)| match value {
+                        // language_flags, one tool per line
+                        Value::Object(flags) => {
+                            let mut flags = flags
+                                .into_iter()
+                                .map(|(flag, value)| match value.as_str() {
+                                    Some("") => flag,
+                                    Some(value) => format!("{}={}", flag, value),
+                                    None => format!("{}={}", flag, value),
+                                })
+                                .collect::<Vec<String>>();
+                            flags.sort_unstable();
+                            format!("{}: {}", key, flags.join(" "))
+                        }
+                        Value::String(string) if string.is_empty() => key,
+                        Value::String(string) => format!("{}{}{}", key, separator, string),
+                        value => format!("{}{}{}", key, separator, value),
+                    })
+                    .collect::<Vec<String>>();
+                lines.sort_unstable();
+                lines.join("\n")
+            }
+            value => value.to_string(),
+        })
+    }
+    // Only the setting is set back to its default
+    pub fn unset_setting(&mut self, setting: &str) -> Result<(), String> {
+        let default = handle_option!(Config::default().to_value()?.remove(setting), format!("Unknown setting \"{}\"", setting));
+        let mut config = self.to_value()?;
+        config.insert(setting.to_string(), default);
+        *self = handle_error!(serde_json::from_value(Value::Object(config)), "Failed to unset setting");
+        Ok(())
+    }
+    fn to_value(&self) -> Result<Map<String, Value>, String> {
+        match handle_error!(serde_json::to_value(self), "Failed to serialize config") {
+            Value::Object(map) => Ok(map),
+            _ => Err("Config didn't serialize to an object, this error shouldn't happen".to_string()),
+        }
+    }
+}
 
-        Ok(config)
+// Loaded once when the program starts, so a command reads config.json once and a broken one fails before anything is done
+// Config commands don't call it, they load the file each time since they change it
+pub fn init() -> Result<(), Error> {
+    let config_file = ConfigFile::load()?;
+    let _ = CONFIG_FILE.set(config_file);
+    Ok(())
+}
+
+impl ConfigFile {
+    // The config file from init, or loaded from config.json if init wasn't called
+    fn get() -> Result<ConfigFile, String> {
+        match CONFIG_FILE.get() {
+            Some(config_file) => Ok(config_file.clone()),
+            // Only the message is kept, Config::get is used with String errors everywhere
+            None => ConfigFile::load().map_err(|error| error.to_string()),
+        }
     }
-    pub fn get_cpp_ver() -> &'static str {
-        let config = Config::get();
-        let cpp_ver = if let Ok(conf) = config {
-            let cpp_ver = conf.default_cpp_ver.clone();
-            cpp_ver.to_string()
-        } else {
-            DEFAULT_CPP_VER.to_string()
-        };
-        Box::leak(cpp_ver.into_boxed_str())
+    fn new(default_config: Config) -> ConfigFile {
+        ConfigFile {
+            default_config,
+            tags: HashMap::new(),
+        }
     }
-    pub fn get_time_limit() -> &'static str {
-        let config = Config::get();
-        let time_limit = if let Ok(conf) = config {
-            let time_limit = conf.default_timeout.clone();
-            time_limit.to_string()
-        } else {
-            DEFAULT_TIME_LIMIT.to_string()
+    pub fn load() -> Result<ConfigFile, Error> {
+        let config_path = config_path()?;
+        if !config_path.exists() {
+            let config_file = ConfigFile::new(Config::default());
+            config_file.save()?;
+            return Ok(config_file);
+        }
+        let format = ConfigFormat::of(&config_path);
+        let value: Value = match format {
+            ConfigFormat::JSON => json_file::read(&config_path),
+            ConfigFormat::TOML => toml_file::read(&config_path),
+        }
+        .context(format!(
+            "Failed to read {}, fix it with `cp-tester config edit` or start over with `cp-tester config reset`",
+            format.file_name()
+        ))?;
+        let (mut config_file, is_old_format) = ConfigFile::from_value(value, format.file_name())?;
+        if is_old_format {
+            config_file.save()?;
+        }
+        color::set_setting(config_file.default_config.color);
+        if config_file.default_config.cookie.take().is_some() {
+            eprintln!("{}: Cookies are now set for each site, set it again with `cp-tester config set-cookie <domain> <cookie>`", color::yellow("WARNING"));
+            config_file.save()?;
+        }
+        Ok(config_file)
+    }
+    // Also true if the file is in an older format, so it should be saved again
+    fn from_value(value: Value, source: &str) -> Result<(ConfigFile, bool), Error> {
+        let Value::Object(file) = value else {
+            return Err(format!("Failed to load {}, it isn't a JSON object, start over with `cp-tester config reset`", source).into());
+        };
+        let (settings, tags, is_old_format) = ConfigFile::split(file)?;
+        let config_file = ConfigFile {
+            default_config: Config::from_settings(settings, source)?,
+            tags,
         };
-        Box::leak(time_limit.into_boxed_str())
+        Ok((config_file, is_old_format))
     }
-    pub fn get_gcc_command(&self) -> Command {
-        let mut command = Command::new("gcc");
-        for (flag, value) in self.gcc_flags.iter() {
-            command.arg(format!("{}{}{}", flag, if value.is_empty() { "" } else { "=" }, value));
+    pub fn save(&self) -> Result<(), String> {
+        self.save_to(&config_path()?)
+    }
+    fn save_to(&self, path: &Path) -> Result<(), String> {
+        match ConfigFormat::of(path) {
+            ConfigFormat::JSON => handle_error!(json_file::write(path, &self), "Failed to write config file"),
+            ConfigFormat::TOML => handle_error!(toml_file::write(path, &self), "Failed to write config file"),
         }
-        command
+        Ok(())
     }
-    pub fn get_gpp_command(&self) -> Command {
-        let mut command = Command::new("g++");
-        for (flag, value) in self.gpp_flags.iter() {
-            command.arg(format!("{}{}{}", flag, if value.is_empty() { "" } else { "=" }, value));
+    // The old file is moved to a .bak file, since config.toml would be used over config.json
+    pub fn convert(to: ConfigFormat) -> Result<PathBuf, Error> {
+        let from_path = config_path()?;
+        let to_path = config_dir()?.join(to.file_name());
+        if from_path == to_path {
+            return Err(format!("The config file is already {}", to.file_name()).into());
         }
-        command
+        let config_file = ConfigFile::load()?;
+        config_file.save_to(&to_path)?;
+        if from_path.exists() {
+            let backup_path = json_file::with_suffix(&from_path, "bak");
+            fs::rename(&from_path, &backup_path).context(format!("Failed to move {:?} to {:?}", from_path, backup_path))?;
+            return Ok(backup_path);
+        }
+        Ok(from_path)
     }
-    pub fn get_java_command(&self) -> Command {
-        let mut command = Command::new("java");
-        for (flag, value) in self.java_flags.iter() {
-            command.arg(format!("{}{}{}", flag, if value.is_empty() { "" } else { "=" }, value));
+    // The default config settings and the tags of a config file, config files from before tags were only the default config
+    // Also true if the file is in an older format, so it should be saved again
+    fn split(mut file: Map<String, Value>) -> Result<(Map<String, Value>, Tags, bool), String> {
+        let (mut settings, mut tags, mut is_old_format) = match file.remove("default_config") {
+            Some(settings) => {
+                let settings = match settings {
+                    Value::Object(settings) => settings,
+                    _ => Map::new(),
+                };
+                let tags: Tags = handle_error!(
+                    serde_json::from_value(file.remove("tags").unwrap_or(Value::Object(Map::new()))),
+                    "Failed to load config tags"
+                );
+                (settings, tags, false)
+            }
+            None => (file, HashMap::new(), true),
+        };
+        let default_flags = Config::default().to_value()?.remove("language_flags").unwrap_or(Value::Null);
+        is_old_format |= migrate_flags(&mut settings, &default_flags);
+        // Tags with old flag settings change the flags of the default config
+        let base_flags = settings.get("language_flags").cloned().unwrap_or(Value::Null);
+        for overrides in tags.values_mut() {
+            is_old_format |= migrate_flags(overrides, &base_flags);
         }
-        command
+        Ok((settings, tags, is_old_format))
     }
-    pub fn get_javac_command(&self) -> Command {
-        let mut command = Command::new("javac");
-        for (flag, value) in self.javac_flags.iter() {
-            command.arg(format!("{}{}{}", flag, if value.is_empty() { "" } else { "=" }, value));
+    // With merge, only the settings and tags in the file change, everything else in the current config is kept
+    pub fn import(contents: &str, format: ConfigFormat, merge: bool) -> Result<ConfigFile, Error> {
+        ConfigFile::validate(contents, format)?;
+        let file = match format.parse(contents).context(format!("Invalid {:?}", format))? {
+            Value::Object(file) => file,
+            _ => return Err("The config isn't an object".to_string().into()),
+        };
+        let (settings, tags, _) = ConfigFile::split(file)?;
+        if !merge {
+            return Ok(ConfigFile {
+                default_config: Config::from_settings(settings, "the imported config")?,
+                tags,
+            });
         }
-        command
+        let mut config_file = ConfigFile::load()?;
+        let mut config = config_file.default_config.to_value()?;
+        config.extend(settings);
+        config_file.default_config = Config::from_settings(config, "the imported config")?;
+        for (tag, overrides) in tags {
+            config_file.tags.entry(tag).or_default().extend(overrides);
+        }
+        Ok(config_file)
     }
-    pub fn reset() -> Result<(), String> {
-        let config_dir = handle_option!(
-            dirs::config_local_dir(),
-            "Failed to get config directory, not sure why this should happen, look into dirs::config_local_dir() to find more about error"
-        );
-        let config_dir = config_dir.join(DEFAULT_FOLDER_NAME);
-        if !config_dir.exists() {
-            handle_error!(fs::create_dir_all(&config_dir), "Failed to create config directory");
+    // Errors for hand edited config files, unknown settings are errors too since they are most likely typos
+    pub fn validate(contents: &str, format: ConfigFormat) -> Result<(), String> {
+        let value: Value = handle_error!(format.parse(contents), format!("Invalid {:?}", format));
+        let configs = match value.get("default_config") {
+            Some(default_config) => {
+                let unknown = value
+                    .as_object()
+                    .map_or(vec![], |file| file.keys().filter(|key| !["default_config", "tags"].contains(&key.as_str())).cloned().collect());
+                if !unknown.is_empty() {
+                    return Err(format!("Unknown top-level keys, only default_config and tags can be next to each other: {}", unknown.join(", ")));
+                }
+                let config_file = handle_error!(format.parse::<ConfigFile>(contents), "Invalid config");
+                let mut configs = vec![("default_config".to_string(), default_config.clone())];
+                for (tag, overrides) in config_file.tags {
+                    configs.push((format!("tag \"{}\"", tag), Value::Object(overrides)));
+                }
+                configs
+            }
+            None => {
+                handle_error!(format.parse::<Config>(contents), "Invalid config");
+                vec![("config".to_string(), value)]
+            }
+        };
+        let known = Config::default().to_value()?;
+        for (name, config) in configs {
+            let Value::Object(config) = config else {
+                return Err(format!("The {} isn't an object", name));
+            };
+            let unknown = config
+                .keys()
+                .filter(|key| !known.contains_key(*key) && !OLD_SETTINGS.contains(&key.as_str()))
+                .cloned()
+                .collect::<Vec<String>>();
+            if !unknown.is_empty() {
+                return Err(format!("Unknown settings in the {}: {}", name, unknown.join(", ")));
+            }
         }
-        if !config_dir.is_dir() {
-            return Err(format!("Config directory: {:?} is not a directory", config_dir));
+        Ok(())
+    }
+    pub fn tag_config(&self, tag: &str) -> Result<Config, String> {
+        let overrides = handle_option!(
+            self.tags.get(tag),
+            format!("Config tag \"{}\" doesn't exist, create it with `cp-tester config create-tag {}`", tag, tag)
+        );
+        let mut config = self.default_config.to_value()?;
+        config.extend(overrides.clone());
+        Config::from_settings(config, &format!("config tag \"{}\"", tag))
+    }
+    // Settings changed from before to after are added to the tag, a tag keeps its own value once it changes a setting
+    pub fn set_tag_config(&mut self, tag: &str, before: &Config, after: &Config) -> Result<(), String> {
+        let before = before.to_value()?;
+        let overrides = handle_option!(self.tags.get_mut(tag), format!("Config tag \"{}\" doesn't exist", tag));
+        for (key, value) in after.to_value()? {
+            if before.get(&key) != Some(&value) {
+                overrides.insert(key, value);
+            }
         }
-        let config_path = config_dir.join("config.json");
-        let config = Config::default();
-        let config_file = handle_error!(serde_json::to_string_pretty(&config), "Failed to serialize config file");
-        handle_error!(fs::write(&config_path, config_file), "Failed to write config file");
-        println!("Config file reset to default");
         Ok(())
     }
-    pub fn get_unicode_output(&self) -> bool {
-        self.unicode_output
+    // The tag uses the setting from the default config again, false if it didn't change it
+    pub fn unset_tag_setting(&mut self, tag: &str, setting: &str) -> Result<bool, String> {
+        let overrides = handle_option!(self.tags.get_mut(tag), format!("Config tag \"{}\" doesn't exist", tag));
+        Ok(overrides.remove(setting).is_some())
     }
-    pub fn save(&self) -> Result<(), String> {
-        let config_dir = handle_option!(
-            dirs::config_local_dir(),
-            "Failed to get config directory, not sure why this should happen, look into dirs::config_local_dir() to find more about error"
-        );
-        let config_dir = config_dir.join(DEFAULT_FOLDER_NAME);
-        if !config_dir.exists() {
-            handle_error!(fs::create_dir_all(&config_dir), "Failed to create config directory");
+    pub fn create_tag(&mut self, tag: &str) -> Result<(), String> {
+        if self.tags.contains_key(tag) {
+            return Err(format!("Config tag \"{}\" already exists", tag));
         }
-        if !config_dir.is_dir() {
-            return Err(format!("Config directory: {:?} is not a directory", config_dir));
+        self.tags.insert(tag.to_string(), Map::new());
+        Ok(())
+    }
+    pub fn delete_tag(&mut self, tag: &str) -> Result<(), String> {
+        if self.tags.remove(tag).is_none() {
+            return Err(format!("Config tag \"{}\" doesn't exist", tag));
         }
-        let config_path = config_dir.join("config.json");
-        let config_file = handle_error!(serde_json::to_string_pretty(&self), "Failed to serialize config file");
-        handle_error!(fs::write(&config_path, config_file), "Failed to write config file");
         Ok(())
     }
+    // Tag names with the settings they change, sorted by name
+    pub fn list_tags(&self) -> Vec<(&String, Vec<&String>)> {
+        let mut tags = self
+            .tags
+            .iter()
+            .map(|(tag, overrides)| {
+                let mut settings = overrides.keys().collect::<Vec<&String>>();
+                settings.sort();
+                (tag, settings)
+            })
+            .collect::<Vec<(&String, Vec<&String>)>>();
+        tags.sort();
+        tags
+    }
 }
 
-impl fmt::Display for Config {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut gcc_flags = vec![];
-        let mut gpp_flags = vec![];
-        let mut java_flags = vec![];
-        let mut javac_flags = vec![];
-        for (flag, value) in self.gcc_flags.iter() {
-            gcc_flags.push(format!("\"{}{}{}\"", flag, if value.is_empty() { "" } else { "=" }, value));
+// The config is loaded by several parts of a command, the warnings are only needed once
+fn warn_once(warning: String) {
+    static WARNED: Mutex<Vec<String>> = Mutex::new(vec![]);
+    let Ok(mut warned) = WARNED.lock() else {
+        return;
+    };
+    if !warned.contains(&warning) {
+        eprintln!("{}: {}", color::yellow("WARNING"), warning);
+        warned.push(warning);
+    }
+}
+
+// Moves old flag settings into language_flags, on top of the flags in base, returns if anything was moved
+fn migrate_flags(settings: &mut Map<String, Value>, base: &Value) -> bool {
+    let mut language_flags = match (settings.get("language_flags"), base) {
+        (Some(Value::Object(language_flags)), _) | (None, Value::Object(language_flags)) => language_flags.clone(),
+        _ => Map::new(),
+    };
+    let mut migrated = false;
+    for (setting, tool) in OLD_FLAG_SETTINGS {
+        match settings.remove(setting) {
+            Some(Value::Object(flags)) if flags.is_empty() => {
+                language_flags.remove(tool);
+            }
+            Some(flags) => {
+                language_flags.insert(tool.to_string(), flags);
+            }
+            None => continue,
         }
-        for (flag, value) in self.gpp_flags.iter() {
-            gpp_flags.push(format!("\"{}{}{}\"", flag, if value.is_empty() { "" } else { "=" }, value));
+        migrated = true;
+    }
+    if migrated {
+        settings.insert("language_flags".to_string(), Value::Object(language_flags));
+    }
+    migrated
+}
+
+impl ConfigFormat {
+    // Files that don't end in .toml are JSON
+    pub fn of(path: &Path) -> ConfigFormat {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => ConfigFormat::TOML,
+            _ => ConfigFormat::JSON,
         }
-        for (flag, value) in self.java_flags.iter() {
-            java_flags.push(format!("\"{}{}{}\"", flag, if value.is_empty() { "" } else { "=" }, value));
+    }
+    pub fn file_name(self) -> &'static str {
+        match self {
+            ConfigFormat::JSON => "config.json",
+            ConfigFormat::TOML => "config.toml",
         }
-        for (flag, value) in self.javac_flags.iter() {
-            javac_flags.push(format!("\"{}{}{}\"", flag, if value.is_empty() { "" } else { "=" }, value));
+    }
+    fn parse<T: DeserializeOwned>(self, contents: &str) -> Result<T, String> {
+        match self {
+            ConfigFormat::JSON => serde_json::from_str(contents).map_err(|error| error.to_string()),
+            ConfigFormat::TOML => toml_file::parse(contents),
         }
-        gcc_flags.sort_unstable();
-        gpp_flags.sort_unstable();
-        java_flags.sort_unstable();
-        javac_flags.sort_unstable();
+    }
+}
 
-        let gcc_flags = gcc_flags.join(", ");
-        let gpp_flags = gpp_flags.join(", ");
-        let java_flags = java_flags.join(", ");
-        let javac_flags = javac_flags.join(", ");
+// Names of environment variables can't be empty or have = in them, since they are passed as NAME=VALUE
+pub fn check_env_name(name: &str) -> Result<String, String> {
+    if name.is_empty() || name.contains('=') || name.contains('\0') {
+        return Err(format!("Invalid environment variable name \"{}\"", name));
+    }
+    Ok(name.to_string())
+}
+
+// config.toml if it exists, else config.json
+pub fn config_path() -> Result<PathBuf, String> {
+    let config_dir = config_dir()?;
+    let toml_path = config_dir.join(ConfigFormat::TOML.file_name());
+    if toml_path.exists() {
+        return Ok(toml_path);
+    }
+    Ok(config_dir.join(ConfigFormat::JSON.file_name()))
+}
+
+pub fn config_dir() -> Result<PathBuf, String> {
+    let config_dir = handle_option!(
+        dirs::config_local_dir(),
+        "Failed to get config directory, not sure why this should happen, look into dirs::config_local_dir() to find more about error"
+    );
+    let config_dir = config_dir.join(DEFAULT_FOLDER_NAME);
+    if !config_dir.exists() {
+        handle_error!(fs::create_dir_all(&config_dir), "Failed to create config directory");
+    }
+    if !config_dir.is_dir() {
+        return Err(format!("Config directory: {:?} is not a directory", config_dir));
+    }
+    Ok(config_dir)
+}
+
+impl fmt::Display for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // One line for each tool that has flags
+        let mut language_flags = self
+            .language_flags
+            .iter()
+            .filter(|(_, flags)| !flags.is_empty())
+            .map(|(tool, flags)| {
+                let mut flags = flags
+                    .iter()
+                    .map(|(flag, value)| format!("\"{}{}{}\"", flag, if value.is_empty() { "" } else { "=" }, value))
+                    .collect::<Vec<String>>();
+                flags.sort_unstable();
+                format!("{} flags: {}\n", tool, flags.join(", "))
+            })
+            .collect::<Vec<String>>();
+        language_flags.sort_unstable();
+        let mut http_headers = self.http_headers.iter().map(|(header, value)| format!("\"{}: {}\"", header, value)).collect::<Vec<String>>();
+        http_headers.sort_unstable();
+        let mut usaco_time_multipliers = self
+            .usaco_time_multipliers
+            .iter()
+            .map(|(extension, multiplier)| format!("{}: {}x", extension, multiplier))
+            .collect::<Vec<String>>();
+        usaco_time_multipliers.sort_unstable();
+        // Cookies are as good as a password, so only the start is shown to tell them apart
+        let mut cookies = self
+            .cookies
+            .iter()
+            .map(|(domain, cookie)| {
+                let preview = cookie.chars().take(COOKIE_PREVIEW_LENGTH).collect::<String>();
+                format!("{}: {}...", domain, preview)
+            })
+            .collect::<Vec<String>>();
+        cookies.sort_unstable();
+        let mut run_env = self.run_env.iter().map(|(name, value)| format!("{}={}", name, value)).collect::<Vec<String>>();
+        run_env.sort_unstable();
 
         write!(
             f,
-            "Default C++ version: {}\nUnicode output: {}\nDefault time limit: {} ms\nGCC flags: {}\nG++ flags: {}\nJava flags: {}\nJavac flags: {}\n",
-            self.default_cpp_ver, self.unicode_output, self.default_timeout, gcc_flags, gpp_flags, java_flags, javac_flags
+            "Default C++ version: {}\nUnicode output: {}\nColor: {}\nDefault time limit: {} ms\nCompile timeout: {}\n{}Runs kept in history per test: {}\nDownload attempts: {}\nUser-Agent: {}\nHTTP headers: {}\nCookies: {}\nProxy: {}\nUSACO time multipliers: {}\nCompress test data: {}\nKeep source archives: {}\nMax case size: {}\nPreview lines: {}\nDefault input extension: {}\nDefault output extension: {}\nCompare output by default: {}\nShow input by default: {}\nShow diff by default: {}\nRun environment: {}\nONLINE_JUDGE: {}\nLimits: {}\nEditor: {}\nData directory: {}\nTemporary directory: {}\n",
+            self.default_cpp_ver,
+            self.unicode_output,
+            match self.color {
+                ColorSetting::AUTO => "auto",
+                ColorSetting::ALWAYS => "always",
+                ColorSetting::NEVER => "never",
+            },
+            self.default_timeout,
+            if self.compile_timeout == 0 { "No limit".to_string() } else { format!("{} s", self.compile_timeout) },
+            if language_flags.is_empty() { "Flags: None\n".to_string() } else { language_flags.concat() },
+            self.history_limit,
+            self.download_retries,
+            self.user_agent,
+            http_headers.join(", "),
+            cookies.join(", "),
+            self.proxy.as_deref().unwrap_or("From environment"),
+            usaco_time_multipliers.join(", "),
+            self.compress_test_data,
+            self.keep_source_archives,
+            if self.max_case_size_mb == 0 { "No limit".to_string() } else { format!("{} MB", self.max_case_size_mb) },
+            if self.preview_lines == 0 { "All".to_string() } else { self.preview_lines.to_string() },
+            self.default_input_extension,
+            self.default_output_extension,
+            self.default_compare_output,
+            self.default_show_input,
+            self.default_diff,
+            if run_env.is_empty() { "None".to_string() } else { run_env.join(", ") },
+            match self.online_judge {
+                OnlineJudgeSetting::INHERIT => "inherit",
+                OnlineJudgeSetting::SET => "set",
+                OnlineJudgeSetting::CLEAR => "clear",
+            },
+            self.limits.describe(None),
+            self.editor.as_deref().unwrap_or("None"),
+            self.data_dir.as_ref().map_or("Default".to_string(), |data_dir| data_dir.to_string_lossy().to_string()),
+            self.temp_dir.as_ref().map_or("System default".to_string(), |temp_dir| temp_dir.to_string_lossy().to_string())
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> (ConfigFile, bool) {
+        ConfigFile::from_value(serde_json::from_str(json).unwrap(), "config.json").unwrap()
+    }
+
+    #[test]
+    fn old_config_files_are_moved_into_default_config() {
+        let json = r#"{"default_cpp_ver": 14, "unicode_output": true, "default_timeout": 2000, "gpp_flags": {"-O2": ""}}"#;
+        let (config_file, is_old_format) = parse(json);
+        assert!(is_old_format);
+        let config = &config_file.default_config;
+        assert_eq!((config.default_cpp_ver, config.unicode_output, config.default_timeout), (14, true, 2000));
+        assert_eq!(config.language_flags["g++"], HashMap::from([("-O2".to_string(), String::new())]));
+        // Settings added after the file was written are the default
+        assert_eq!(config.history_limit, DEFAULT_HISTORY_LIMIT);
+        assert_eq!(config.default_input_extension, DEFAULT_INPUT_EXTENSION);
+        assert!(config_file.tags.is_empty());
+    }
+
+    #[test]
+    fn missing_unknown_and_invalid_settings_use_the_default() {
+        let json = r#"{
+            "default_config": {"default_timeout": 5000, "colour": "always", "history_limit": "ten", "compress_test_data": true},
+            "tags": {"usaco": {"default_timeout": 4000}}
+        }"#;
+        let (config_file, is_old_format) = parse(json);
+        assert!(!is_old_format);
+        let config = &config_file.default_config;
+        assert_eq!((config.default_timeout, config.compress_test_data), (5000, true));
+        assert_eq!(config.history_limit, DEFAULT_HISTORY_LIMIT);
+        assert_eq!(config.default_cpp_ver, DEFAULT_CPP_VER);
+        assert_eq!(config_file.tag_config("usaco").unwrap().default_timeout, 4000);
+
+        let (config_file, _) = parse("{}");
+        assert_eq!(config_file.default_config.default_timeout, DEFAULT_TIME_LIMIT);
+        assert!(ConfigFile::from_value(serde_json::json!([1, 2]), "config.json").is_err());
+    }
+
+    #[test]
+    fn syntax_errors_have_the_line_and_column() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        fs::write(&path, "{\n  \"default_timeout\": 2000,\n}").unwrap();
+        let error = json_file::read::<Value>(&path).unwrap_err();
+        assert!(matches!(&error, Error::PARSE { message, .. } if message.contains("line 3 column 1")), "{}", error);
+    }
+}
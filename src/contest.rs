@@ -0,0 +1,138 @@
+use regex::Regex;
+
+use crate::{
+    commands::add::get_link_html,
+    error::{Context, Error},
+};
+
+const CODEFORCES_HOST: &str = "https://codeforces.com";
+const ATCODER_HOST: &str = "https://atcoder.jp";
+const CODEFORCES_CONTEST_REGEX_STR: &str = r#"^https?://(www\.)?codeforces\.com/contest/(?<contest>\d+)/?(problems/?)?$"#;
+const ATCODER_CONTEST_REGEX_STR: &str = r#"^https?://(www\.)?atcoder\.jp/contests/(?<contest>[^/?#]+)/?(tasks/?)?$"#;
+// Both sites link every problem twice in the task list, first with its letter then with its title
+// Codeforces puts HTML comments around the title, which are removed afterwards
+const CODEFORCES_PROBLEM_REGEX_STR: &str = r#"(?s)<a href="(?<link>/contest/\d+/problem/(?<id>[A-Za-z0-9]+))">(?<text>([^<]|<!--.*?-->)*?)</a>"#;
+const ATCODER_PROBLEM_REGEX_STR: &str = r#"<a href="(?<link>/contests/[^/"]+/tasks/(?<id>[^/"]+))">(?<text>[^<]*?)</a>"#;
+const HTML_COMMENT_REGEX_STR: &str = r#"(?s)<!--.*?-->"#;
+
+// A problem from the task list of a contest
+#[derive(Debug, Clone)]
+pub struct ContestProblem {
+    pub label: String,
+    pub title: String,
+    pub link: String,
+}
+
+// A Codeforces contest(https://codeforces.com/contest/1700) or AtCoder contest(https://atcoder.jp/contests/abc300)
+#[derive(Debug)]
+pub struct Contest {
+    pub id: String,
+    // Codeforces contests are numbers, so their tests start with cf like cf1700_a_optimal_path
+    name_prefix: String,
+    pub problems: Vec<ContestProblem>,
+}
+
+impl Contest {
+    pub fn is_contest_link(link: &str) -> bool {
+        contest_id(CODEFORCES_CONTEST_REGEX_STR, link).is_some() || contest_id(ATCODER_CONTEST_REGEX_STR, link).is_some()
+    }
+
+    pub fn from_link(link: &str) -> Result<Contest, Error> {
+        let (id, name_prefix, tasks_link, problem_regex, host) = if let Some(id) = contest_id(CODEFORCES_CONTEST_REGEX_STR, link) {
+            (
+                id.clone(),
+                format!("cf{}", id),
+                format!("{}/contest/{}", CODEFORCES_HOST, id),
+                CODEFORCES_PROBLEM_REGEX_STR,
+                CODEFORCES_HOST,
+            )
+        } else if let Some(id) = contest_id(ATCODER_CONTEST_REGEX_STR, link) {
+            (
+                id.clone(),
+                id.clone(),
+                format!("{}/contests/{}/tasks", ATCODER_HOST, id),
+                ATCODER_PROBLEM_REGEX_STR,
+                ATCODER_HOST,
+            )
+        } else {
+            return Err(format!("Not a Codeforces or AtCoder contest link: {}", link).into());
+        };
+        let page = get_link_html(&tasks_link)?;
+        let problem_regex = Regex::new(problem_regex)
+            .map_err(|error| error.to_string())
+            .context(format!("Failed to create regex from string - String is {}", problem_regex))?;
+        let comment_regex = Regex::new(HTML_COMMENT_REGEX_STR)
+            .map_err(|error| error.to_string())
+            .context(format!("Failed to create regex from string - String is {}", HTML_COMMENT_REGEX_STR))?;
+        let mut problems: Vec<(String, ContestProblem)> = vec![];
+        for cap in problem_regex.captures_iter(&page) {
+            let problem_id = cap["id"].to_string();
+            let text = comment_regex.replace_all(&cap["text"], "").trim().to_string();
+            if text.is_empty() {
+                continue;
+            }
+            match problems.iter_mut().find(|(existing_id, _)| *existing_id == problem_id) {
+                Some((_, problem)) if problem.title.is_empty() && text != problem.label => problem.title = text,
+                Some(_) => (),
+                None => problems.push((
+                    problem_id,
+                    ContestProblem {
+                        label: text,
+                        title: String::new(),
+                        link: format!("{}{}", host, &cap["link"]),
+                    },
+                )),
+            }
+        }
+        if problems.is_empty() {
+            return Err(format!(
+                "No problems found on the task list of contest {}({}), it might not have started yet",
+                id, tasks_link
+            )
+            .into());
+        }
+        Ok(Contest {
+            id,
+            name_prefix,
+            problems: problems.into_iter().map(|(_, problem)| problem).collect(),
+        })
+    }
+
+    // Only the problems with the given letters(Case insensitive), every problem if none are given
+    pub fn filter_problems(&self, labels: &[String]) -> Result<Vec<ContestProblem>, String> {
+        if labels.is_empty() {
+            return Ok(self.problems.clone());
+        }
+        let mut problems = vec![];
+        for label in labels {
+            let problem = self.problems.iter().find(|problem| problem.label.eq_ignore_ascii_case(label.trim()));
+            match problem {
+                Some(problem) => problems.push(problem.clone()),
+                None => {
+                    return Err(format!(
+                        "Contest {} has no problem {}, it has {}",
+                        self.id,
+                        label,
+                        self.problems.iter().map(|problem| problem.label.clone()).collect::<Vec<String>>().join(", ")
+                    ))
+                }
+            }
+        }
+        Ok(problems)
+    }
+
+    // Like abc300_a_n_choice_question, so problems from a contest are listed together
+    pub fn test_name(&self, problem: &ContestProblem) -> String {
+        let name = format!("{}_{}_{}", self.name_prefix, problem.label, problem.title)
+            .to_ascii_lowercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect::<String>();
+        name.split('_').filter(|part| !part.is_empty()).collect::<Vec<&str>>().join("_")
+    }
+}
+
+fn contest_id(regex: &str, link: &str) -> Option<String> {
+    let regex = Regex::new(regex).ok()?;
+    regex.captures(link.trim()).map(|cap| cap["contest"].to_string())
+}
@@ -0,0 +1,56 @@
+use std::{env, path::PathBuf, sync::OnceLock};
+
+use crate::{config::Config, handle_error, handle_option, DEFAULT_FOLDER_NAME};
+
+// Used instead of data_dir in the config, but not instead of --data-dir
+pub const HOME_ENV_VAR: &str = "CP_TESTER_HOME";
+
+static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+// Set once from --data-dir when the program starts, every command gets the directory from get so they can't disagree
+pub fn init(flag: Option<PathBuf>) -> Result<(), String> {
+    let data_dir = resolve(flag)?;
+    let _ = DATA_DIR.set(data_dir);
+    Ok(())
+}
+
+// The folder with test.json and tests/
+pub fn get() -> Result<PathBuf, String> {
+    match DATA_DIR.get() {
+        Some(data_dir) => Ok(data_dir.clone()),
+        None => resolve(None),
+    }
+}
+
+// From highest to lowest priority: --data-dir, CP_TESTER_HOME, data_dir in the config, the default
+fn resolve(flag: Option<PathBuf>) -> Result<PathBuf, String> {
+    if let Some(data_dir) = flag {
+        return absolute(data_dir);
+    }
+    if let Some(home) = env::var_os(HOME_ENV_VAR).filter(|home| !home.is_empty()) {
+        return absolute(PathBuf::from(home));
+    }
+    if let Some(data_dir) = handle_error!(Config::get(), "Failed to load config file").data_dir {
+        return Ok(data_dir);
+    }
+    default()
+}
+
+// The archive a test was downloaded from, kept when keep_source_archives is set
+// Archives are found by their contents when extracted, so tar.gz archives are named .zip too
+pub fn archive_path(test_name: &str) -> Result<PathBuf, String> {
+    Ok(get()?.join("archives").join(format!("{}.zip", test_name)))
+}
+
+pub fn default() -> Result<PathBuf, String> {
+    let data_dir = handle_option!(
+        dirs::data_local_dir(),
+        "Failed to get data directory, not sure why this should happen, look into dirs::data_local_dir() to find more about error"
+    );
+    Ok(data_dir.join(DEFAULT_FOLDER_NAME))
+}
+
+// Relative paths are from the directory cp-tester is run in
+pub fn absolute(path: PathBuf) -> Result<PathBuf, String> {
+    Ok(handle_error!(std::path::absolute(&path), format!("Invalid data directory: {:?}", path)))
+}
@@ -0,0 +1,94 @@
+use std::{fmt::Display, io};
+
+use thiserror::Error as ThisError;
+
+use crate::run_history::Verdict;
+
+// The messages keep the format of handle_error!, so converted code prints the same errors
+// String errors become OTHER when they are returned with ? from code that returns Error
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+#[derive(Debug, ThisError)]
+pub enum Error {
+    // The message says what failed and for which link, like a timeout or a status code that isn't 200
+    #[error("{0}")]
+    NETWORK(String),
+    #[error("{context}: \nError Data: {source}")]
+    IO { context: String, source: io::Error },
+    #[error("{context}: \nError Data: {message}")]
+    PARSE { context: String, message: String },
+    // The message has the names the test could be a typo of
+    #[error("{0}")]
+    TEST_NOT_FOUND(String),
+    #[error("Failed to compile file\n{diagnostics}")]
+    COMPILE_FAILED { diagnostics: String },
+    // The worst verdict decides the exit code
+    #[error("{message}")]
+    CASE_FAILED { verdict: Verdict, message: String },
+    #[error("{context}: \nError Data: {source}")]
+    CONTEXT { context: String, source: Box<Error> },
+    #[error("{0}")]
+    OTHER(String),
+}
+
+// Listed in the help of run
+pub const EXIT_CODES_HELP: &str = "Exit codes:\n  0  Every case passed\n  1  cp-tester failed, like when a test doesn't exist or the config is broken\n  2  The file failed to compile\n  3  A case had a wrong answer\n  4  A case timed out or the program crashed\nWhen more than one applies, the lowest code is used";
+
+impl Error {
+    // So editors and scripts can tell a wrong solution from a problem with cp-tester
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::COMPILE_FAILED { .. } => 2,
+            Error::CASE_FAILED { verdict: Verdict::FAILED, .. } => 3,
+            Error::CASE_FAILED { .. } => 4,
+            Error::CONTEXT { source, .. } => source.exit_code(),
+            _ => 1,
+        }
+    }
+}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Error::OTHER(message)
+    }
+}
+
+// handle_error! and handle_option! for code that returns Error, the context is the custom message
+pub trait Context<T> {
+    fn context(self, context: impl Display) -> Result<T, Error>;
+}
+
+impl<T> Context<T> for Result<T, io::Error> {
+    fn context(self, context: impl Display) -> Result<T, Error> {
+        self.map_err(|source| Error::IO { context: context.to_string(), source })
+    }
+}
+
+impl<T> Context<T> for Result<T, reqwest::Error> {
+    fn context(self, context: impl Display) -> Result<T, Error> {
+        self.map_err(|source| Error::NETWORK(format!("{}: \nError Data: {}", context, source)))
+    }
+}
+
+impl<T> Context<T> for Result<T, serde_json::Error> {
+    fn context(self, context: impl Display) -> Result<T, Error> {
+        self.map_err(|error| Error::PARSE { context: context.to_string(), message: error.to_string() })
+    }
+}
+
+impl<T> Context<T> for Result<T, Error> {
+    fn context(self, context: impl Display) -> Result<T, Error> {
+        self.map_err(|source| Error::CONTEXT { context: context.to_string(), source: Box::new(source) })
+    }
+}
+
+impl<T> Context<T> for Result<T, String> {
+    fn context(self, context: impl Display) -> Result<T, Error> {
+        self.map_err(Error::OTHER).context(context)
+    }
+}
+
+impl<T> Context<T> for Option<T> {
+    fn context(self, context: impl Display) -> Result<T, Error> {
+        self.ok_or_else(|| Error::OTHER(context.to_string()))
+    }
+}
@@ -0,0 +1,67 @@
+use std::{
+    fs::{self, File},
+    io::{self, IsTerminal, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::error::{Context, Error};
+
+// test.json and config.json are written to a temp file that replaces the old one, so a crash can't leave half a file
+// The previous version is kept as a .bak file to restore if the file still gets corrupted
+pub fn write<T: Serialize>(path: &Path, value: &T) -> Result<(), Error> {
+    let contents = serde_json::to_string_pretty(value).context(format!("Failed to serialize {:?}", path))?;
+    let temp_path = with_suffix(path, "tmp");
+    let mut temp_file = File::create(&temp_path).context(format!("Failed to create {:?}", temp_path))?;
+    temp_file.write_all(contents.as_bytes()).context(format!("Failed to write {:?}", temp_path))?;
+    temp_file.sync_all().context(format!("Failed to write {:?}", temp_path))?;
+    // Only a version that parses is backed up, so a corrupted file never replaces a good backup
+    if fs::read_to_string(path).is_ok_and(|old| serde_json::from_str::<serde_json::Value>(&old).is_ok()) {
+        fs::copy(path, with_suffix(path, "bak")).context(format!("Failed to back up {:?}", path))?;
+    }
+    fs::rename(&temp_path, path).context(format!("Failed to replace {:?}", path))
+}
+
+// If the file doesn't parse, the backup can be restored, which is asked about when running in a terminal
+pub fn read<T: DeserializeOwned>(path: &Path) -> Result<T, Error> {
+    let contents = fs::read_to_string(path).context(format!("Failed to read {:?}", path))?;
+    let error = match serde_json::from_str(&contents) {
+        Ok(value) => return Ok(value),
+        Err(error) => error,
+    };
+    let backup_path = with_suffix(path, "bak");
+    let backup = fs::read_to_string(&backup_path)
+        .ok()
+        .and_then(|backup| serde_json::from_str::<T>(&backup).ok());
+    let Some(backup) = backup else {
+        return Err(Error::PARSE {
+            context: format!("Failed to parse {:?}, and there is no usable backup", path),
+            message: error.to_string(),
+        });
+    };
+    let message = format!(
+        "Failed to parse {:?}({}), the previous version is saved at {:?}",
+        path, error, backup_path
+    );
+    if !io::stdin().is_terminal() {
+        return Err(Error::OTHER(format!("{}, copy it over {:?} to restore it", message, path)));
+    }
+    eprint!("{}\nRestore it? Changes since it was saved are lost [y/N]: ", message);
+    io::stderr().flush().context("Failed to flush stderr")?;
+    let mut line = String::new();
+    io::stdin().read_line(&mut line).context("Failed to read from stdin")?;
+    if !line.trim().eq_ignore_ascii_case("y") {
+        return Err(Error::OTHER(format!("{}, copy it over {:?} to restore it", message, path)));
+    }
+    fs::copy(&backup_path, path).context(format!("Failed to restore {:?}", path))?;
+    eprintln!("Restored {:?} from backup", path);
+    Ok(backup)
+}
+
+// test.json becomes test.json.tmp, unlike Path::with_extension a name with dots like a.b keeps all of them
+pub fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(format!(".{}", suffix));
+    PathBuf::from(path)
+}
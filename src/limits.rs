@@ -0,0 +1,178 @@
+use std::{
+    process::{Command, ExitStatus},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_FILE_SIZE_MB: u64 = 1024;
+// Seconds of CPU time past the time limit before the backstop stops the program
+const CPU_BACKSTOP_MARGIN_SECS: u64 = 1;
+
+// The [limits] section of the config, resource limits of the program run judges so a broken one can't take down the machine
+// Only applied on Unix, with setrlimit
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct Limits {
+    // Largest file the program can write, in MB, 0 for no limit
+    pub file_size_mb: u64,
+    // Processes the user can have while the program runs, 0 for no limit
+    // It counts every process of the user(And threads on Linux), so it has to be above what is already running
+    pub processes: u64,
+    pub core_dumps: bool,
+    // Stops the program once it used a second more CPU time than the time limit, in case it isn't killed when the time limit passes
+    pub cpu_backstop: bool,
+}
+
+impl Default for Limits {
+    fn default() -> Limits {
+        Limits {
+            file_size_mb: DEFAULT_FILE_SIZE_MB,
+            processes: 0,
+            core_dumps: false,
+            cpu_backstop: true,
+        }
+    }
+}
+
+impl Limits {
+    // Like: file size 1024 MB, no core dumps, CPU time 3 s
+    // Without the time limit(For config print), the CPU time limit is described instead of given
+    pub fn describe(&self, timeout_ms: Option<u64>) -> String {
+        let mut limits = vec![];
+        if self.file_size_mb > 0 {
+            limits.push(format!("file size {} MB", self.file_size_mb));
+        }
+        if self.processes > 0 {
+            limits.push(format!("{} processes", self.processes));
+        }
+        if !self.core_dumps {
+            limits.push("no core dumps".to_string());
+        }
+        match timeout_ms {
+            Some(timeout_ms) => {
+                if let Some(cpu_secs) = self.cpu_secs(timeout_ms) {
+                    limits.push(format!("CPU time {} s", cpu_secs));
+                }
+            }
+            None if self.cpu_backstop => limits.push("CPU time a second over the time limit".to_string()),
+            None => {}
+        }
+        if limits.is_empty() {
+            return "None".to_string();
+        }
+        limits.join(", ")
+    }
+    fn cpu_secs(&self, timeout_ms: u64) -> Option<u64> {
+        (self.cpu_backstop && timeout_ms > 0).then(|| timeout_ms.div_ceil(1000) + CPU_BACKSTOP_MARGIN_SECS)
+    }
+}
+
+// The limits a command is spawned with, changed between tests since the program is compiled once
+// They are read after fork in pre_exec, where locking or allocating isn't safe, so they are atomics and 0 is no limit
+#[derive(Debug, Default)]
+pub struct ChildLimits {
+    file_size_bytes: AtomicU64,
+    processes: AtomicU64,
+    cpu_secs: AtomicU64,
+    no_core_dumps: AtomicBool,
+}
+
+impl ChildLimits {
+    // Every command spawned after this has the limits, until they are set again
+    pub fn install(command: &mut Command) -> Arc<ChildLimits> {
+        let limits = Arc::new(ChildLimits::default());
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            let child_limits = Arc::clone(&limits);
+            // Safe since apply only reads atomics and calls setrlimit, which are fine to use between fork and exec
+            unsafe {
+                command.pre_exec(move || child_limits.apply());
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = command;
+        limits
+    }
+    // None is --no-limits
+    pub fn set(&self, limits: Option<&Limits>, timeout_ms: u64) {
+        let default = Limits {
+            file_size_mb: 0,
+            processes: 0,
+            core_dumps: true,
+            cpu_backstop: false,
+        };
+        let limits = limits.unwrap_or(&default);
+        self.file_size_bytes
+            .store(limits.file_size_mb.saturating_mul(1024 * 1024), Ordering::Relaxed);
+        self.processes.store(limits.processes, Ordering::Relaxed);
+        self.cpu_secs.store(limits.cpu_secs(timeout_ms).unwrap_or(0), Ordering::Relaxed);
+        self.no_core_dumps.store(!limits.core_dumps, Ordering::Relaxed);
+    }
+    #[cfg(unix)]
+    fn apply(&self) -> std::io::Result<()> {
+        let file_size_bytes = self.file_size_bytes.load(Ordering::Relaxed);
+        if file_size_bytes > 0 {
+            set_rlimit(libc::RLIMIT_FSIZE, file_size_bytes, file_size_bytes)?;
+        }
+        let processes = self.processes.load(Ordering::Relaxed);
+        if processes > 0 {
+            set_rlimit(libc::RLIMIT_NPROC, processes, processes)?;
+        }
+        // SIGXCPU at the soft limit, then SIGKILL at the hard limit if the program handles it
+        let cpu_secs = self.cpu_secs.load(Ordering::Relaxed);
+        if cpu_secs > 0 {
+            set_rlimit(libc::RLIMIT_CPU, cpu_secs, cpu_secs + CPU_BACKSTOP_MARGIN_SECS)?;
+        }
+        if self.no_core_dumps.load(Ordering::Relaxed) {
+            set_rlimit(libc::RLIMIT_CORE, 0, 0)?;
+        }
+        Ok(())
+    }
+}
+
+// Limits already lower than the new one(Like from ulimit) are kept, since raising the hard limit isn't allowed
+#[cfg(unix)]
+fn set_rlimit(resource: RlimitResource, soft: u64, hard: u64) -> std::io::Result<()> {
+    let mut current = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+    if unsafe { libc::getrlimit(resource, &mut current) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    let hard = (hard as libc::rlim_t).min(current.rlim_max);
+    let new = libc::rlimit {
+        rlim_cur: (soft as libc::rlim_t).min(hard),
+        rlim_max: hard,
+    };
+    if unsafe { libc::setrlimit(resource, &new) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+// The type of the resource argument of setrlimit isn't the same on every Unix
+#[cfg(all(unix, target_os = "linux", target_env = "gnu"))]
+type RlimitResource = libc::__rlimit_resource_t;
+#[cfg(all(unix, not(all(target_os = "linux", target_env = "gnu"))))]
+type RlimitResource = libc::c_int;
+
+// The limit a program that was killed by a signal went over, if the signal is one the limits send
+pub fn exceeded_limit(status: &ExitStatus) -> Option<&'static str> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        match status.signal() {
+            Some(libc::SIGXFSZ) => Some("file size limit exceeded"),
+            Some(libc::SIGXCPU) => Some("CPU time limit exceeded"),
+            _ => None,
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = status;
+        None
+    }
+}
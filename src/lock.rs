@@ -0,0 +1,61 @@
+use std::{
+    fs::{self, File, OpenOptions, TryLockError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{data_dir, handle_error};
+
+pub const LOCK_FILE_NAME: &str = "lock";
+pub const HISTORY_LOCK_FILE_NAME: &str = "history.lock";
+// How long to wait for another instance before giving up
+const LOCK_WAIT: Duration = Duration::from_secs(10);
+const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// Held while a command uses the stored tests, the lock is released when this is dropped(Or the process exits)
+pub struct DataLock {
+    _file: File,
+}
+
+// Commands that change tests take an exclusive lock, ones that only read them take a shared lock so they can run together
+pub fn acquire(exclusive: bool) -> Result<DataLock, String> {
+    lock_file(LOCK_FILE_NAME, exclusive)
+}
+
+// run only takes a shared lock, so two runs could both add to the run history of a test at once
+// Held from loading the history to saving it, it is a different file since the shared lock can't become exclusive
+pub fn acquire_history() -> Result<DataLock, String> {
+    lock_file(HISTORY_LOCK_FILE_NAME, true)
+}
+
+fn lock_file(file_name: &str, exclusive: bool) -> Result<DataLock, String> {
+    let data_dir = data_dir::get()?;
+    handle_error!(fs::create_dir_all(&data_dir), "Failed to create data directory");
+    let lock_path = data_dir.join(file_name);
+    let file = handle_error!(
+        OpenOptions::new().create(true).truncate(false).write(true).open(&lock_path),
+        format!("Failed to open lock file({:?})", lock_path)
+    );
+    let start = Instant::now();
+    let mut waiting = false;
+    loop {
+        let result = if exclusive { file.try_lock() } else { file.try_lock_shared() };
+        match result {
+            Ok(()) => return Ok(DataLock { _file: file }),
+            Err(TryLockError::WouldBlock) if start.elapsed() < LOCK_WAIT => {
+                if !waiting {
+                    eprintln!("Waiting for another cp-tester instance to finish...");
+                    waiting = true;
+                }
+                thread::sleep(LOCK_POLL_INTERVAL);
+            }
+            Err(TryLockError::WouldBlock) => {
+                return Err(format!(
+                    "Another cp-tester instance is running and using the stored tests, try again once it finishes(Lock file: {:?})",
+                    lock_path
+                ))
+            }
+            Err(TryLockError::Error(e)) => return Err(format!("Failed to lock {:?}: {}", lock_path, e)),
+        }
+    }
+}
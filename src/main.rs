@@ -2,17 +2,52 @@ use std::process::exit;
 
 mod commands {
     pub mod add;
+    pub mod add_alternate;
     pub mod config;
+    pub mod describe;
+    pub mod doctor;
+    pub mod duplicate;
+    pub mod history;
+    pub mod init;
     pub mod list;
+    pub mod listen;
+    pub mod migrate_storage;
+    pub mod note;
+    pub mod open;
     pub mod remove;
     pub mod rename;
+    pub mod report;
+    pub mod restore;
     pub mod run;
+    pub mod set_checker;
+    pub mod set_config_tag;
+    pub mod set_subtasks;
+    pub mod statement;
+    pub mod tag;
+    pub mod template;
 }
+mod case_naming;
 mod cli;
+mod color;
 mod config;
+mod contest;
+mod data_dir;
+mod error;
+mod http;
+mod json_file;
+mod limits;
+mod lock;
 mod macros;
+mod names;
+mod package;
+mod parallel;
+mod paths;
 mod program_data;
+mod project_config;
+mod run_history;
+mod subtasks;
 mod test_data;
+mod toml_file;
 use program_data::ProgramData;
 
 // Implementation ideas
@@ -40,8 +75,8 @@ fn main() {
 
     match program_result {
         Err(e) => {
-            eprintln!("\x1b[31mERROR\x1b[0m: {e}");
-            exit(1)
+            eprintln!("{}: {}", color::red("ERROR"), e);
+            exit(e.exit_code())
         }
         _ => (),
     };
@@ -0,0 +1,180 @@
+// Test names are directory names and case names are file names, so they have to work on Windows and macOS too,
+// where names are case-insensitive and some characters aren't allowed
+
+use regex::Regex;
+
+use crate::handle_error;
+
+const INVALID_CHARS: [char; 9] = ['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+const RESERVED_NAMES: [&str; 4] = ["con", "prn", "aux", "nul"];
+
+// kind is "Test" or "Case", for the error
+pub fn validate(name: &str, kind: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err(format!("{} name can't be empty", kind));
+    }
+    if let Some(c) = name.chars().find(|c| INVALID_CHARS.contains(c) || c.is_control()) {
+        return Err(format!(
+            "{} name \"{}\" has \"{}\", which can't be in file names on Windows(Invalid characters are {})",
+            kind,
+            name,
+            c.escape_default(),
+            INVALID_CHARS.iter().collect::<String>()
+        ));
+    }
+    if name.ends_with(['.', ' ']) {
+        return Err(format!("{} name \"{}\" can't end with a dot or space", kind, name));
+    }
+    if is_reserved(name) {
+        return Err(format!("{} name \"{}\" is reserved on Windows", kind, name));
+    }
+    Ok(())
+}
+
+// For names made from problem titles or file names, like "What is 1+1?" from Codeforces
+pub fn sanitize(name: &str) -> String {
+    let name = name
+        .chars()
+        .map(|c| if INVALID_CHARS.contains(&c) || c.is_control() { '_' } else { c })
+        .collect::<String>();
+    let name = name.trim_end_matches(['.', ' ']).to_string();
+    if is_reserved(&name) {
+        format!("{}_", name)
+    } else {
+        name
+    }
+}
+
+// An existing name that is the same as name except for capitalization, so both would be the same file or directory
+pub fn find_conflict<'a>(name: &str, existing: impl IntoIterator<Item = &'a String>) -> Option<&'a String> {
+    existing
+        .into_iter()
+        .find(|other| other.as_str() != name && other.eq_ignore_ascii_case(name))
+}
+
+// CON, and also CON.txt and COM1 to COM9 and LPT1 to LPT9
+fn is_reserved(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or_default().to_ascii_lowercase();
+    let numbered = ["com", "lpt"].iter().any(|prefix| {
+        stem.strip_prefix(prefix)
+            .is_some_and(|number| number.len() == 1 && ('1'..='9').contains(&number.chars().next().unwrap()))
+    });
+    RESERVED_NAMES.contains(&stem.as_str()) || numbered
+}
+
+// Test names matched by --filter(A case-insensitive substring, or a glob with * and ?) and --regex, a name has to match both if both are given
+#[derive(Debug, Default)]
+pub struct NameFilter {
+    patterns: Vec<Regex>,
+    description: Option<String>,
+}
+
+impl NameFilter {
+    pub fn new(filter: Option<&str>, regex: Option<&str>) -> Result<NameFilter, String> {
+        let mut patterns = vec![];
+        if let Some(filter) = filter {
+            let pattern = if filter.contains(['*', '?']) {
+                let glob = filter
+                    .chars()
+                    .map(|c| match c {
+                        '*' => ".*".to_string(),
+                        '?' => ".".to_string(),
+                        c => regex::escape(&c.to_string()),
+                    })
+                    .collect::<String>();
+                format!("(?i)^{}$", glob)
+            } else {
+                format!("(?i){}", regex::escape(filter))
+            };
+            patterns.push(handle_error!(Regex::new(&pattern), format!("Invalid filter \"{}\"", filter)));
+        }
+        if let Some(regex) = regex {
+            patterns.push(handle_error!(Regex::new(regex), format!("Invalid regex \"{}\"", regex)));
+        }
+        let description = match (filter, regex) {
+            (Some(filter), Some(regex)) => Some(format!("{}' and '{}", filter, regex)),
+            (Some(pattern), None) | (None, Some(pattern)) => Some(pattern.to_string()),
+            (None, None) => None,
+        };
+        Ok(NameFilter { patterns, description })
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        self.patterns.iter().all(|pattern| pattern.is_match(name))
+    }
+
+    // The filters as given, None if there are none
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+}
+
+// Appends up to three of the known names closest to name to the error, for typos in long test names
+pub fn did_you_mean<'a>(error: String, name: &str, known: impl IntoIterator<Item = &'a String>) -> String {
+    let name = name.to_lowercase();
+    // Names that contain the given one come first, then the ones fewer edits away
+    let mut close = known
+        .into_iter()
+        .filter_map(|other| {
+            let lower = other.to_lowercase();
+            let score = if lower.contains(&name) { 0 } else { edit_distance(&name, &lower) };
+            (score <= (name.chars().count() / 3).max(1)).then_some((score, other))
+        })
+        .collect::<Vec<(usize, &String)>>();
+    if close.is_empty() {
+        return error;
+    }
+    close.sort();
+    let names = close.iter().take(3).map(|(_, other)| other.as_str()).collect::<Vec<&str>>();
+    format!("{}. Did you mean: {}?", error, names.join(", "))
+}
+
+// Levenshtein distance, the amount of characters that have to be inserted, removed or replaced to turn a into b
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<char>>();
+    let mut previous = (0..=b.len()).collect::<Vec<usize>>();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let replace = previous[j] + usize::from(a_char != *b_char);
+            current.push(replace.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn edit_distance_counts_changed_characters() {
+        assert_eq!(edit_distance("fence_paiting", "fence_painting"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("same", "same"), 0);
+    }
+
+    #[test]
+    fn typos_get_the_closest_names() {
+        let known = names(&["fence_painting_bronze_jan21", "fence_painting_silver_jan21", "cow_jog_bronze_dec14", "two_sum"]);
+        let error = did_you_mean("Test doesn't exist".to_string(), "fence_paiting_bronze_jan21", &known);
+        assert_eq!(error, "Test doesn't exist. Did you mean: fence_painting_bronze_jan21, fence_painting_silver_jan21?");
+        // Names that contain the given one come before the ones that are a few edits away
+        let error = did_you_mean("Not found".to_string(), "Two", &names(&["tw0", "two_sum"]));
+        assert_eq!(error, "Not found. Did you mean: two_sum, tw0?");
+        assert_eq!(did_you_mean("Not found".to_string(), "marathon", &known), "Not found");
+        assert_eq!(did_you_mean("Not found".to_string(), "a", &names(&[])), "Not found");
+    }
+
+    #[test]
+    fn at_most_three_names_are_suggested() {
+        let known = names(&["case1", "case2", "case3", "case4"]);
+        assert_eq!(did_you_mean("Not found".to_string(), "case", &known), "Not found. Did you mean: case1, case2, case3?");
+    }
+}
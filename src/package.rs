@@ -0,0 +1,315 @@
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use regex::Regex;
+
+use crate::{color, handle_error, handle_option, subtasks::Subtask, test_data::IOType};
+
+const POLYGON_METADATA_FILE_NAME: &str = "problem.xml";
+const ICPC_METADATA_FILE_NAME: &str = "problem.yaml";
+const POLYGON_SHORT_NAME_REGEX_STR: &str = r#"<problem[^>]*\sshort-name="(?<name>[^"]*)""#;
+const POLYGON_NAME_REGEX_STR: &str = r#"<name[^>]*language="english"[^>]*value="(?<name>[^"]*)""#;
+const POLYGON_TIME_LIMIT_REGEX_STR: &str = r#"<time-limit>(?<time_limit>\d+)</time-limit>"#;
+const POLYGON_MEMORY_LIMIT_REGEX_STR: &str = r#"<memory-limit>(?<memory_limit>\d+)</memory-limit>"#;
+const POLYGON_JUDGING_REGEX_STR: &str = r#"<judging[^>]*>"#;
+const POLYGON_CHECKER_REGEX_STR: &str = r#"(?s)<checker[^>]*>.*?<source[^>]*path="(?<path>[^"]*)""#;
+const POLYGON_TEST_REGEX_STR: &str = r#"<test(\s[^>]*)?/?>"#;
+const POLYGON_GROUP_REGEX_STR: &str = r#"<group\s[^>]*>"#;
+const ICPC_NAME_REGEX_STR: &str = r#"(?m)^name:\s*['"]?(?<name>[^'"\n]*?)['"]?\s*$"#;
+const ICPC_TIME_LIMIT_REGEX_STR: &str = r#"(?m)^\s*time_limit:\s*(?<time_limit>[0-9.]+)"#;
+const ICPC_MEMORY_LIMIT_REGEX_STR: &str = r#"(?m)^\s*memory:\s*(?<memory_limit>\d+)"#;
+const CHECKER_EXTENSIONS: [&str; 4] = ["cpp", "c", "java", "py"];
+
+#[derive(Debug, PartialEq)]
+pub enum PackageType {
+    Polygon,
+    Icpc,
+}
+
+impl fmt::Display for PackageType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let string = match self {
+            PackageType::Polygon => "Polygon",
+            PackageType::Icpc => "ICPC",
+        };
+        write!(f, "{}", string)
+    }
+}
+
+// A problem package from Polygon(problem.xml) or in the ICPC format(problem.yaml)
+#[derive(Debug)]
+pub struct Package {
+    pub package_type: PackageType,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub time_limit_ms: Option<u64>,
+    pub memory_limit_mb: Option<u64>,
+    pub checker: Option<PathBuf>,
+    pub input_io: IOType,
+    pub output_io: IOType,
+    // Case name, input file, answer file
+    pub cases: Vec<(String, PathBuf, PathBuf)>,
+    // Polygon groups with points, empty for other packages
+    pub subtasks: Vec<Subtask>,
+}
+
+impl Package {
+    pub fn from_dir(dir: &Path) -> Result<Package, String> {
+        let root = handle_option!(
+            find_package_root(dir),
+            format!("Not a problem package, no {} or {} found", POLYGON_METADATA_FILE_NAME, ICPC_METADATA_FILE_NAME)
+        );
+        if root.join(POLYGON_METADATA_FILE_NAME).is_file() {
+            Package::from_polygon(&root)
+        } else {
+            Package::from_icpc(&root)
+        }
+    }
+
+    fn from_polygon(root: &Path) -> Result<Package, String> {
+        let metadata_path = root.join(POLYGON_METADATA_FILE_NAME);
+        let metadata = handle_error!(fs::read_to_string(&metadata_path), format!("Failed to read {:?}", metadata_path));
+        let name = capture(POLYGON_SHORT_NAME_REGEX_STR, &metadata, "name")?;
+        let description = capture(POLYGON_NAME_REGEX_STR, &metadata, "name")?;
+        let time_limit_ms = capture(POLYGON_TIME_LIMIT_REGEX_STR, &metadata, "time_limit")?.and_then(|t| t.parse().ok());
+        // Polygon stores the memory limit in bytes
+        let memory_limit_mb = capture(POLYGON_MEMORY_LIMIT_REGEX_STR, &metadata, "memory_limit")?
+            .and_then(|m| m.parse::<u64>().ok())
+            .map(|m| m / (1024 * 1024));
+        let checker = capture(POLYGON_CHECKER_REGEX_STR, &metadata, "path")?
+            .map(|path| root.join(path))
+            .filter(|path| path.is_file());
+        // Like a Pascal checker, which can't be compiled
+        let checker = match checker {
+            Some(checker) if !is_checker_source(&checker) => {
+                eprintln!(
+                    "{}: The checker {:?} isn't used since it isn't a .{} file",
+                    color::yellow("WARNING"),
+                    checker.file_name().unwrap_or_default(),
+                    CHECKER_EXTENSIONS.join(", .")
+                );
+                None
+            }
+            checker => checker,
+        };
+        let judging = capture(POLYGON_JUDGING_REGEX_STR, &metadata, "0")?.unwrap_or_default();
+        let input_io = match attribute(&judging, "input-file")? {
+            Some(file) if !file.is_empty() => IOType::FILE(PathBuf::from(file)),
+            _ => IOType::STD,
+        };
+        let output_io = match attribute(&judging, "output-file")? {
+            Some(file) if !file.is_empty() => IOType::FILE(PathBuf::from(file)),
+            _ => IOType::STD,
+        };
+
+        // The nth <test> tag is test n, and says if it is a sample and which group it is in
+        let test_regex = handle_error!(Regex::new(POLYGON_TEST_REGEX_STR), "Failed to create regex for tests");
+        let samples = test_regex
+            .find_iter(&metadata)
+            .map(|test| test.as_str().contains("sample=\"true\""))
+            .collect::<Vec<bool>>();
+        let test_groups = test_regex
+            .find_iter(&metadata)
+            .map(|test| attribute(test.as_str(), "group"))
+            .collect::<Result<Vec<Option<String>>, String>>()?;
+        let tests_dir = root.join("tests");
+        let files = handle_error!(tests_dir.read_dir(), format!("Failed to read tests folder of package({:?})", tests_dir));
+        let mut cases = vec![];
+        for file in files.filter_map(|file| file.ok()) {
+            let input_path = file.path();
+            if input_path.extension().is_some() {
+                continue;
+            }
+            let answer_path = input_path.with_extension("a");
+            if !answer_path.is_file() {
+                continue;
+            }
+            let number = input_path.file_name().unwrap().to_string_lossy().to_string();
+            let is_sample = number.parse::<usize>().ok().and_then(|n| samples.get(n.wrapping_sub(1))).copied().unwrap_or(false);
+            let case_name = format!("{}_{}", if is_sample { "sample" } else { "secret" }, number);
+            cases.push((case_name, input_path, answer_path));
+        }
+        if cases.is_empty() {
+            return Err("No tests with answers found in the package, if it is a Linux package the tests have to be generated first(Run doall.sh)".to_string());
+        }
+        let subtasks = polygon_subtasks(&metadata, &test_groups, &cases)?;
+        Ok(Package {
+            package_type: PackageType::Polygon,
+            name,
+            description,
+            time_limit_ms,
+            memory_limit_mb,
+            checker,
+            input_io,
+            output_io,
+            cases,
+            subtasks,
+        })
+    }
+
+    fn from_icpc(root: &Path) -> Result<Package, String> {
+        let metadata_path = root.join(ICPC_METADATA_FILE_NAME);
+        let metadata = handle_error!(fs::read_to_string(&metadata_path), format!("Failed to read {:?}", metadata_path));
+        let description = capture(ICPC_NAME_REGEX_STR, &metadata, "name")?;
+        // Older packages keep the time limit in a .timelimit file
+        let time_limit = match capture(ICPC_TIME_LIMIT_REGEX_STR, &metadata, "time_limit")? {
+            Some(time_limit) => Some(time_limit),
+            None => fs::read_to_string(root.join(".timelimit")).ok(),
+        };
+        let time_limit_ms = time_limit.and_then(|t| t.trim().parse::<f64>().ok()).map(|t| (t * 1000.0) as u64);
+        let memory_limit_mb = capture(ICPC_MEMORY_LIMIT_REGEX_STR, &metadata, "memory_limit")?.and_then(|m| m.parse().ok());
+        let checker = find_checker(&root.join("output_validators"));
+        let mut cases = vec![];
+        for group in ["sample", "secret"] {
+            let group_dir = root.join("data").join(group);
+            if group_dir.is_dir() {
+                find_icpc_cases(&group_dir, group, &mut cases)?;
+            }
+        }
+        if cases.is_empty() {
+            return Err("No .in and .ans files found in data/sample or data/secret of the package".to_string());
+        }
+        Ok(Package {
+            package_type: PackageType::Icpc,
+            name: None,
+            description,
+            time_limit_ms,
+            memory_limit_mb,
+            checker,
+            input_io: IOType::STD,
+            output_io: IOType::STD,
+            cases,
+            subtasks: vec![],
+        })
+    }
+}
+
+// Packages are often zipped with a folder around them
+fn find_package_root(dir: &Path) -> Option<PathBuf> {
+    let is_root = |dir: &Path| dir.join(POLYGON_METADATA_FILE_NAME).is_file() || dir.join(ICPC_METADATA_FILE_NAME).is_file();
+    if is_root(dir) {
+        return Some(dir.to_path_buf());
+    }
+    dir.read_dir().ok()?.filter_map(|entry| entry.ok()).map(|entry| entry.path()).find(|path| path.is_dir() && is_root(path))
+}
+
+// Cases in subfolders get the subfolder in their name, like secret_group1_01
+fn find_icpc_cases(dir: &Path, prefix: &str, cases: &mut Vec<(String, PathBuf, PathBuf)>) -> Result<(), String> {
+    let files = handle_error!(dir.read_dir(), format!("Failed to read package folder({:?})", dir));
+    for file in files.filter_map(|file| file.ok()) {
+        let path = file.path();
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        if path.is_dir() {
+            find_icpc_cases(&path, &format!("{}_{}", prefix, stem), cases)?;
+        } else if path.extension().is_some_and(|extension| extension == "in") {
+            let answer_path = path.with_extension("ans");
+            if answer_path.is_file() {
+                cases.push((format!("{}_{}", prefix, stem), path, answer_path));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn find_checker(dir: &Path) -> Option<PathBuf> {
+    let mut files = vec![];
+    for entry in dir.read_dir().ok()?.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        if path.is_dir() {
+            files.extend(path.read_dir().ok()?.filter_map(|entry| entry.ok()).map(|entry| entry.path()));
+        } else {
+            files.push(path);
+        }
+    }
+    files.sort();
+    files.into_iter().find(|path| is_checker_source(path))
+}
+
+fn is_checker_source(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| CHECKER_EXTENSIONS.contains(&extension.to_string_lossy().as_ref()))
+}
+
+// A subtask for each group worth points, its pattern lists the cases of the group since they are named by test number
+fn polygon_subtasks(metadata: &str, test_groups: &[Option<String>], cases: &[(String, PathBuf, PathBuf)]) -> Result<Vec<Subtask>, String> {
+    let group_regex = handle_error!(Regex::new(POLYGON_GROUP_REGEX_STR), "Failed to create regex for groups");
+    let mut subtasks = vec![];
+    for group in group_regex.find_iter(metadata) {
+        let (Some(name), Some(points)) = (attribute(group.as_str(), "name")?, attribute(group.as_str(), "points")?) else {
+            continue;
+        };
+        // Polygon writes points like 30.0
+        let points = points.parse::<f64>().map_or(0, |points| points.round() as u64);
+        let group_cases = cases
+            .iter()
+            .map(|(case_name, _, _)| case_name)
+            .filter(|case_name| {
+                let number = case_name.rsplit('_').next().and_then(|number| number.parse::<usize>().ok());
+                number.and_then(|number| test_groups.get(number.wrapping_sub(1))).is_some_and(|test_group| test_group.as_ref() == Some(&name))
+            })
+            .cloned()
+            .collect::<Vec<String>>();
+        if points == 0 || group_cases.is_empty() {
+            continue;
+        }
+        subtasks.push(Subtask {
+            name: if name.parse::<u64>().is_ok() { format!("group{}", name) } else { name },
+            pattern: group_cases.join("|"),
+            points,
+        });
+    }
+    Ok(subtasks)
+}
+
+fn capture(regex: &str, text: &str, group: &str) -> Result<Option<String>, String> {
+    let regex = handle_error!(Regex::new(regex), format!("Failed to create regex from string - String is {}", regex));
+    let captures = regex.captures(text);
+    let value = match group.parse::<usize>() {
+        Ok(index) => captures.and_then(|captures| captures.get(index)),
+        Err(_) => captures.and_then(|captures| captures.name(group)),
+    };
+    Ok(value.map(|value| value.as_str().trim().to_string()))
+}
+
+fn attribute(tag: &str, name: &str) -> Result<Option<String>, String> {
+    capture(&format!(r#"\s{}="(?<value>[^"]*)""#, name), tag, "value")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn polygon_package(checker_path: &str) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let metadata = format!(
+            r#"<problem short-name="sum"><judging input-file="" output-file=""><testset><test sample="true"/><test/></testset></judging>
+<assets><checker type="testlib"><source path="{}" type="cpp.g++17"/></checker></assets></problem>"#,
+            checker_path
+        );
+        fs::write(dir.path().join(POLYGON_METADATA_FILE_NAME), metadata).unwrap();
+        fs::create_dir_all(dir.path().join("files")).unwrap();
+        fs::write(dir.path().join("files").join("check.cpp"), "").unwrap();
+        fs::write(dir.path().join("files").join("check.pas"), "").unwrap();
+        fs::create_dir_all(dir.path().join("tests")).unwrap();
+        for test in ["01", "02"] {
+            fs::write(dir.path().join("tests").join(test), "1 2\n").unwrap();
+            fs::write(dir.path().join("tests").join(format!("{}.a", test)), "3\n").unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn polygon_checker_must_be_compilable() {
+        let package = Package::from_dir(polygon_package("files/check.cpp").path()).unwrap();
+        assert_eq!(package.package_type, PackageType::Polygon);
+        assert_eq!(package.name.as_deref(), Some("sum"));
+        assert!(package.checker.is_some_and(|checker| checker.ends_with("files/check.cpp")));
+        let mut cases = package.cases.iter().map(|(name, _, _)| name.as_str()).collect::<Vec<&str>>();
+        cases.sort();
+        assert_eq!(cases, ["sample_01", "secret_02"]);
+
+        let package = Package::from_dir(polygon_package("files/check.pas").path()).unwrap();
+        assert!(package.checker.is_none());
+    }
+}
@@ -0,0 +1,62 @@
+use std::{
+    num::NonZeroUsize,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+// Reading and writing case files is mostly waiting on the disk, more threads than this don't help
+const MAX_DEFAULT_THREADS: usize = 8;
+
+// 0 until --threads is given, which uses the default
+static THREADS: AtomicUsize = AtomicUsize::new(0);
+
+// --threads, set before anything reads or writes cases
+pub fn set_threads(threads: Option<NonZeroUsize>) {
+    THREADS.store(threads.map_or(0, NonZeroUsize::get), Ordering::Relaxed);
+}
+
+fn threads() -> usize {
+    match THREADS.load(Ordering::Relaxed) {
+        0 => thread::available_parallelism()
+            .map_or(1, |threads| threads.get())
+            .min(MAX_DEFAULT_THREADS),
+        threads => threads,
+    }
+}
+
+// The results are in the same order as items, so case names don't depend on which thread finished first
+pub fn map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let threads = threads().min(items.len());
+    if threads <= 1 {
+        return items.iter().map(f).collect();
+    }
+    let chunk_size = items.len().div_ceil(threads);
+    let f = &f;
+    thread::scope(|scope| {
+        let handles = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<R>>()))
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+            .collect()
+    })
+}
+
+// Every error is reported instead of only the first, one per line
+pub fn collect_errors<R>(results: Vec<Result<R, String>>) -> Result<Vec<R>, String> {
+    let mut values = vec![];
+    let mut errors = vec![];
+    for result in results {
+        match result {
+            Ok(value) => values.push(value),
+            Err(error) => errors.push(error),
+        }
+    }
+    if errors.is_empty() {
+        Ok(values)
+    } else {
+        Err(errors.join("\n"))
+    }
+}
@@ -0,0 +1,161 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+// Windows keeps files of a process that just exited locked for a moment, so removing them is retried
+const REMOVE_ATTEMPTS: u32 = 5;
+const REMOVE_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+// output becomes output.exe on Windows
+pub fn executable_name(stem: &str) -> String {
+    format!("{}{}", stem, env::consts::EXE_SUFFIX)
+}
+
+// python3 only exists on Windows when Python is from the Microsoft Store, the python.org installer only adds python
+pub fn program_name(tool: &str) -> &str {
+    if cfg!(target_os = "windows") && tool == "python3" {
+        "python"
+    } else {
+        tool
+    }
+}
+
+// canonicalize on Windows gives \\?\C:\foo, which javac and some other tools reject
+pub fn strip_verbatim_prefix(path: &Path) -> PathBuf {
+    let path_str = path.to_string_lossy();
+    if let Some(share) = path_str.strip_prefix(r"\\?\UNC\") {
+        PathBuf::from(format!(r"\\{}", share))
+    } else if let Some(path) = path_str.strip_prefix(r"\\?\") {
+        PathBuf::from(path)
+    } else {
+        path.to_path_buf()
+    }
+}
+
+// Used instead of Path::canonicalize for paths that are given to other programs or shown to the user
+pub fn canonicalize(path: &Path) -> io::Result<PathBuf> {
+    Ok(strip_verbatim_prefix(&path.canonicalize()?))
+}
+
+pub fn remove_dir_all(path: &Path) -> io::Result<()> {
+    retry_remove(path, |path| fs::remove_dir_all(path))
+}
+
+fn retry_remove(path: &Path, remove: impl Fn(&Path) -> io::Result<()>) -> io::Result<()> {
+    let mut attempt = 1;
+    loop {
+        match remove(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound && attempt < REMOVE_ATTEMPTS => {
+                thread::sleep(REMOVE_RETRY_DELAY * attempt);
+                attempt += 1;
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            result => return result,
+        }
+    }
+}
+
+// The temp_dir setting and --temp-dir have to be a folder that files can be created in
+// Absolute, since programs are run with the temp directory as their working directory
+pub fn check_temp_dir(path: &Path) -> Result<PathBuf, String> {
+    if !path.is_dir() {
+        return Err(format!("Temporary directory {:?} doesn't exist or isn't a folder", path));
+    }
+    if let Err(e) = tempfile::tempfile_in(path) {
+        return Err(format!("Can't create files in temporary directory {:?}: {}", path, e));
+    }
+    canonicalize(path).map_err(|e| format!("Failed to get absolute path of temporary directory {:?}: {}", path, e))
+}
+
+// tempfile::TempDir, but removing it is retried, so a program that was just run doesn't leave it behind on Windows
+#[derive(Debug)]
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    // In the system temp directory unless parent is given(The temp_dir setting or --temp-dir)
+    pub fn new(parent: Option<&Path>) -> io::Result<TempDir> {
+        let temp_dir = match parent {
+            Some(parent) => tempfile::TempDir::new_in(parent)?,
+            None => tempfile::TempDir::new()?,
+        };
+        Ok(TempDir(strip_verbatim_prefix(&temp_dir.into_path())))
+    }
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = remove_dir_all(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn strips_verbatim_prefix() {
+        assert_eq!(strip_verbatim_prefix(Path::new(r"\\?\C:\cp\sol.java")), PathBuf::from(r"C:\cp\sol.java"));
+        assert_eq!(strip_verbatim_prefix(Path::new(r"\\?\UNC\server\share\sol.java")), PathBuf::from(r"\\server\share\sol.java"));
+        assert_eq!(strip_verbatim_prefix(Path::new(r"C:\cp\sol.java")), PathBuf::from(r"C:\cp\sol.java"));
+        assert_eq!(strip_verbatim_prefix(Path::new("/home/cp/sol.java")), PathBuf::from("/home/cp/sol.java"));
+    }
+
+    #[test]
+    fn remove_is_retried() {
+        let attempts = Cell::new(0);
+        let locked_twice = |_: &Path| {
+            attempts.set(attempts.get() + 1);
+            if attempts.get() <= 2 {
+                Err(io::Error::from(io::ErrorKind::PermissionDenied))
+            } else {
+                Ok(())
+            }
+        };
+        assert!(retry_remove(Path::new("dir"), locked_twice).is_ok());
+        assert_eq!(attempts.get(), 3);
+
+        attempts.set(0);
+        let always_locked = |_: &Path| {
+            attempts.set(attempts.get() + 1);
+            Err(io::Error::from(io::ErrorKind::PermissionDenied))
+        };
+        assert!(retry_remove(Path::new("dir"), always_locked).is_err());
+        assert_eq!(attempts.get(), REMOVE_ATTEMPTS);
+
+        attempts.set(0);
+        let missing = |_: &Path| {
+            attempts.set(attempts.get() + 1);
+            Err(io::Error::from(io::ErrorKind::NotFound))
+        };
+        assert!(retry_remove(Path::new("dir"), missing).is_ok());
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[test]
+    fn removes_dir() {
+        let dir = tempfile::tempdir().unwrap().into_path();
+        fs::create_dir_all(dir.join("case")).unwrap();
+        fs::write(dir.join("case").join("1.in"), "1").unwrap();
+        remove_dir_all(&dir).unwrap();
+        assert!(!dir.exists());
+        assert!(remove_dir_all(&dir).is_ok());
+    }
+
+    #[test]
+    fn executable_names() {
+        assert_eq!(executable_name("output"), format!("output{}", env::consts::EXE_SUFFIX));
+        if cfg!(windows) {
+            assert_eq!(program_name("python3"), "python");
+        } else {
+            assert_eq!(program_name("python3"), "python3");
+        }
+        assert_eq!(program_name("g++"), "g++");
+    }
+}
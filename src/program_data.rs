@@ -1,19 +1,33 @@
-use crate::commands::run::RunDir;
-use crate::config::Config;
+use crate::commands::add::{find_subtasks, print_dry_run, validate_test, AddArgs, AddSummary, SubmissionType, OJUZ_NO_CASES_ERROR};
+use crate::commands::duplicate::copy_test_dir;
+use crate::commands::listen;
+use crate::commands::report::write_report;
+use crate::commands::run::{output_line, print_summary, RunArgs, RunDir, RunReport};
+use crate::commands::set_checker::{copy_checker, save_checker};
+use crate::config::{self, Config};
+use crate::project_config::ProjectConfig;
 use crate::{
     cli::{CliData, Commands},
-    handle_option,
-    test_data::{EmptyTest, Test},
+    test_data::{EmptyTest, Test, ADHOC_CASE_NAME},
 };
-use crate::{handle_error, DEFAULT_FOLDER_NAME};
+use crate::error::{Context, Error};
+use crate::run_history::{RunHistory, Verdict};
+use crate::{color, data_dir, handle_error, json_file, lock, names, parallel, paths};
 use clap::Parser;
+use std::env::current_dir;
 use std::fs;
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+use tabled::Table;
 
 #[derive(Debug)]
 pub struct ProgramData {
     cli_data: CliData,
     tests: HashMap<String, Test>,
+    // Tests whose cases were added or replaced by this command, write_data only rewrites their directories
+    modified: HashSet<String>,
     pub temp_path: Option<PathBuf>,
 }
 
@@ -23,110 +37,402 @@ impl ProgramData {
         ProgramData {
             cli_data: cli,
             tests: HashMap::new(),
+            modified: HashSet::new(),
             temp_path: None,
         }
     }
-    pub fn run(&mut self) -> Result<(), String> {
-        let tests = handle_error!(ProgramData::load_empty_tests(), "Failed to load empty(Without input & output data) tests");
-        self.tests = tests;
+    pub fn run(&mut self) -> Result<(), Error> {
+        color::set_no_color_flag(self.cli_data.no_color);
+        parallel::set_threads(self.cli_data.threads);
+        // Config commands have to work when the config file can't be loaded, so it can be fixed, the data directory is only needed by set-data-dir
+        if let Some(Commands::CONFIG(args)) = &self.cli_data.command {
+            let _ = data_dir::init(self.cli_data.data_dir.clone());
+            return args.run();
+        }
+        config::init()?;
+        data_dir::init(self.cli_data.data_dir.clone())?;
+        // Held until the command finishes, listen only locks while writing each test since it runs until stopped
+        // note only touches notes.md, and the editor can be open for a long time
+        let _lock = match &self.cli_data.command {
+            Some(Commands::LISTEN(_)) | Some(Commands::NOTE(_)) => None,
+            // run --save changes test.json
+            Some(Commands::RUN(args)) if args.save => Some(lock::acquire(true)?),
+            Some(
+                Commands::LIST(_)
+                | Commands::RUN(_)
+                | Commands::REPORT(_)
+                | Commands::HISTORY(_)
+                | Commands::INIT(_)
+                | Commands::TEMPLATE(_)
+                | Commands::STATEMENT(_)
+                | Commands::OPEN(_),
+            ) => Some(lock::acquire(false)?),
+            _ => Some(lock::acquire(true)?),
+        };
+        self.load_tests()?;
+        // Only run has exit codes other than 1, for why the solution failed
+        if let Some(Commands::RUN(args)) = &self.cli_data.command {
+            return self.run_tests(&args.clone());
+        }
+        self.run_command()
+    }
+
+    fn load_tests(&mut self) -> Result<(), Error> {
+        self.tests = ProgramData::load_empty_tests().context("Failed to load empty(Without input & output data) tests")?;
+        Ok(())
+    }
+
+    fn run_command(&mut self) -> Result<(), Error> {
         match &self.cli_data.command {
+            Some(Commands::ADD(args)) if args.input_type_is_package() => {
+                let args = args.clone();
+                let (test_name, temp_path, mut test, checker) = args.test_from_package().context("Failed to import problem package")?;
+                self.temp_path = Some(temp_path);
+                test.add_tags(&args.tags);
+                validate_test(&test, args.strict)?;
+                if args.dry_run {
+                    print_dry_run(&test_name, &test);
+                    return Ok(());
+                }
+                self.save_test(&args, &test_name, test).context("Failed to write data for new test")?;
+                // The checker is copied after write_data, since it replaces the test's directory
+                if let Some(checker) = checker {
+                    let test_dir = data_dir::get()?.join("tests").join(&test_name);
+                    copy_checker(&checker, &test_dir).context("Failed to copy checker into test directory")?;
+                }
+                Ok(())
+            }
             Some(Commands::ADD(args)) => {
-                let (input_io, output_io) = handle_error!(args.get_io(), "Failed to get IO Data");
-                let (test_name, test_path, submission_data, description) = handle_error!(args.get_test_data(), "Failed to get test data");
-                if !args.input_type_is_folder() {
-                    self.temp_path = Some(test_path.clone());
-                }
-                let test = handle_error!(
-                    Test::from_folder(
-                        test_path,
-                        args.input_extension.clone(),
-                        args.output_extension.clone(),
-                        input_io,
-                        output_io,
-                        submission_data,
-                        description
-                    ),
-                    "Failed to create test from folder/zip"
+                let inputs = args.split_inputs()?;
+                if inputs.len() == 1 {
+                    self.add_test(&inputs[0])?;
+                    return Ok(());
+                }
+                // A failed problem shouldn't stop the rest, so errors are printed and summarized at the end
+                let mut summary = vec![];
+                for (i, input) in inputs.iter().enumerate() {
+                    println!("[{}/{}] Adding {}", i + 1, inputs.len(), input.input_label());
+                    let result = self.add_test(input);
+                    if let Err(e) = &result {
+                        eprintln!("{}: {}", color::red("ERROR"), e);
+                    }
+                    summary.push(AddSummary::new(input.input_label(), result, input.dry_run));
+                    self.clear_temp_files().context("Failed to clear temporary files")?;
+                    self.temp_path = None;
+                }
+                let failed = summary.iter().filter(|row| row.test_name.is_empty()).count();
+                println!("{}", Table::new(&summary));
+                if failed > 0 {
+                    return Err(format!("Failed to add {} of {} tests", failed, inputs.len()).into());
+                }
+                Ok(())
+            }
+            Some(Commands::LISTEN(args)) => {
+                let args = args.clone();
+                let listener = args.bind()?;
+                println!(
+                    "Listening for Competitive Companion on port {}, press Ctrl-C to stop",
+                    listener.local_addr().map(|a| a.port()).unwrap_or(0)
                 );
-                self.tests.insert(test_name, test);
-                handle_error!(self.write_data(), "Failed to write data for new test");
+                let mut added = 0;
+                while args.get_count().is_none_or(|count| added < count) {
+                    // A bad problem shouldn't stop the listener, so errors are printed instead of returned
+                    let (test_name, test) = match args.receive_test(&listener, &self.tests) {
+                        Ok(test) => test,
+                        Err(e) => {
+                            eprintln!("{}: {}", color::red("ERROR"), e);
+                            continue;
+                        }
+                    };
+                    let case_count = test.cases.len();
+                    // Other instances could have changed the tests while waiting for the problem
+                    let _lock = lock::acquire(true)?;
+                    self.tests = ProgramData::load_empty_tests().context("Failed to load empty(Without input & output data) tests")?;
+                    if let Err(e) = listen::check_name(&test_name, &self.tests) {
+                        eprintln!("{}: {}", color::red("ERROR"), e);
+                        continue;
+                    }
+                    self.modified.insert(test_name.clone());
+                    self.tests.insert(test_name.clone(), test);
+                    self.write_data().context("Failed to write data for new test")?;
+                    println!("Added test \"{}\" with {} cases", test_name, case_count);
+                    added += 1;
+                }
                 Ok(())
             }
-            Some(Commands::LIST(args)) => Ok(handle_error!(args.run(&mut self.tests), "Failed to list test/cases")),
+            Some(Commands::LIST(args)) => args.run(&mut self.tests).context("Failed to list test/cases"),
             Some(Commands::REMOVE(args)) => {
-                if args.all {
-                    if self.tests.is_empty() {
-                        return Err("There are no tests to remove".to_string());
+                let tests_path = data_dir::get()?.join("tests");
+                let Some(test_names) = args.test_names(&self.tests)? else {
+                    return Ok(());
+                };
+                if !args.confirm(&test_names, &tests_path)? {
+                    println!("No tests were removed");
+                    return Ok(());
+                }
+                let (all, keep_archive) = (args.all, args.keep_archive);
+                // Each directory is renamed out of the way before test.json is written, so a test is either removed or left how it was
+                // A renamed directory that fails to delete is only left behind, `cp-tester doctor` finds it
+                let mut renamed = vec![];
+                let mut failed = vec![];
+                for test_name in &test_names {
+                    let test_path = tests_path.join(test_name);
+                    let removing_path = tests_path.join(format!(".{}.removing", test_name));
+                    if test_path.exists() {
+                        if let Err(e) = fs::rename(&test_path, &removing_path) {
+                            failed.push(format!("\"{}\": {}", test_name, e));
+                            continue;
+                        }
                     }
-                    self.tests.clear();
-                    let test_path = handle_option!(dirs::data_local_dir(), "Failed to get data local dir, dirs crate issue");
-                    let test_path = test_path.join(DEFAULT_FOLDER_NAME).join("tests");
-                    handle_error!(fs::remove_dir_all(test_path), "Failed to remove test directory");
-                    println!("Successfully removed all tests");
-                    return self.write_data();
-                }
-                let test_names = args.test_name.as_ref().unwrap();
-                for test_name in test_names {
-                    if let Some(_) = self.tests.remove_entry(test_name) {
-                        let test_path = handle_option!(dirs::data_local_dir(), "Failed to get data local dir, dirs crate issue");
-                        let test_path = test_path.join(DEFAULT_FOLDER_NAME).join("tests").join(test_name);
-                        handle_error!(fs::remove_dir_all(test_path), "Failed to remove test directory");
+                    renamed.push((test_name, test_path, removing_path));
+                }
+                for (test_name, _, _) in &renamed {
+                    self.tests.remove(*test_name);
+                }
+                if let Err(e) = self.write_data() {
+                    for (_, test_path, removing_path) in &renamed {
+                        let _ = fs::rename(removing_path, test_path);
+                    }
+                    return Err(e);
+                }
+                for (test_name, _, removing_path) in &renamed {
+                    if removing_path.exists() {
+                        if let Err(e) = paths::remove_dir_all(removing_path) {
+                            eprintln!("{}: Failed to delete {:?}, remove it with `cp-tester doctor --delete-unknown`: {}", color::yellow("WARNING"), removing_path, e);
+                        }
+                    }
+                    let archive_path = data_dir::archive_path(test_name)?;
+                    if !keep_archive && archive_path.exists() {
+                        if let Err(e) = fs::remove_file(&archive_path) {
+                            eprintln!("{}: Failed to remove cached archive {:?}: {}", color::yellow("WARNING"), archive_path, e);
+                        }
+                    }
+                    if !all {
                         println!("Successfully removed test with name \"{}\" ", test_name);
-                    } else {
-                        return Err(format!("Test with name \"{}\" doesn't exist", test_name));
                     }
                 }
-
-                self.write_data()
-            }
-            Some(Commands::RUN(args)) => {
-                if args.example && args.cases.is_some() {
-                    return Err("Cannot run example and specified cases at the same time".to_string());
+                if !failed.is_empty() {
+                    return Err(format!(
+                        "Removed {} of {} tests, these were left how they were:\n{}",
+                        renamed.len(),
+                        test_names.len(),
+                        failed.join("\n")
+                    )
+                    .into());
+                }
+                if all {
+                    let archives_path = data_dir::get()?.join("archives");
+                    if !keep_archive && archives_path.exists() {
+                        fs::remove_dir_all(archives_path).context("Failed to remove archive directory")?;
+                    }
+                    println!("Successfully removed all tests");
                 }
-                let test_name = &args.test;
-                if !self.tests.contains_key(test_name) {
-                    return Err(format!("Test with name \"{}\" doesn't exist", test_name));
-                };
-                let config = handle_error!(Config::get(), "Failed to load in config");
-                let test = self.tests.get_mut(test_name).unwrap();
-                let folder = handle_option!(dirs::data_local_dir(), "Failed to get data local dir, dirs crate issue");
-                let folder = folder.join(DEFAULT_FOLDER_NAME).join("tests").join(test_name);
-                handle_error!(test.fill_cases(folder), "Failed to get config");
-                let mut run_dir = handle_error!(RunDir::new(test, &args, &config), "Failed to compile file and store in temp dir");
-                handle_error!(run_dir.run(), "Failed to run test");
                 Ok(())
             }
+            Some(Commands::REPORT(args)) => Ok(args.run(&mut self.tests)?),
             Some(Commands::RENAME(args)) => {
                 let old_name = &args.test_name;
                 let new_name = &args.new_name;
                 if !self.tests.contains_key(old_name) {
-                    return Err(format!("Test with name \"{}\" doesn't exist", old_name));
-                }
-                if self.tests.contains_key(new_name) {
-                    return Err(format!("Test with name \"{}\" already exists", new_name));
+                    return Err(Test::not_found(old_name, &self.tests));
                 }
+                self.check_new_name(new_name, old_name)?;
                 let (_, test) = self.tests.remove_entry(old_name).unwrap();
                 self.tests.insert(new_name.clone(), test);
-                let data_dir = handle_option!(
-                    dirs::data_local_dir(),
-                    "Failed to get data directory, not sure why this should happen, look into dirs::data_local_dir() to find more about error"
-                );
-                let test_dir = data_dir.join(DEFAULT_FOLDER_NAME).join("tests").join(old_name);
-                let new_test_dir = data_dir.join(DEFAULT_FOLDER_NAME).join("tests").join(new_name);
-                handle_error!(fs::rename(test_dir, new_test_dir), "Failed to rename test directory");
+                let data_dir = data_dir::get()?;
+                let test_dir = data_dir.join("tests").join(old_name);
+                let new_test_dir = data_dir.join("tests").join(new_name);
+                fs::rename(test_dir, new_test_dir).context("Failed to rename test directory")?;
+                let archive_path = data_dir::archive_path(old_name)?;
+                if archive_path.exists() {
+                    fs::rename(archive_path, data_dir::archive_path(new_name)?).context("Failed to rename cached archive")?;
+                }
+                self.write_data()
+            }
+            Some(Commands::DUPLICATE(args)) => {
+                let old_name = &args.test_name;
+                let new_name = &args.new_name;
+                if !self.tests.contains_key(old_name) {
+                    return Err(Test::not_found(old_name, &self.tests));
+                }
+                self.check_new_name(new_name, old_name)?;
+                let data_dir = data_dir::get()?;
+                let test_dir = data_dir.join("tests").join(old_name);
+                let new_test_dir = data_dir.join("tests").join(new_name);
+                let (files, bytes) = copy_test_dir(&test_dir, &new_test_dir).context("Failed to copy test directory")?;
+                let bytes_mb = (bytes as f64) / (1024_f64 * 1024_f64);
+                if bytes_mb < 1.0 {
+                    println!("Copied {} files({:.2} KB) from \"{}\" to \"{}\"", files, (bytes as f64) / 1024_f64, old_name, new_name);
+                } else {
+                    println!("Copied {} files({:.2} MB) from \"{}\" to \"{}\"", files, bytes_mb, old_name, new_name);
+                }
+                // Only added once its directory is there, so test.json never has a test without one
+                let test = self.tests.get(old_name).unwrap().clone();
+                self.tests.insert(new_name.clone(), test);
+                self.write_data()
+            }
+            Some(Commands::INIT(args)) => Ok(args.run(&self.tests)?),
+            Some(Commands::HISTORY(args)) => Ok(args.run(&self.tests)?),
+            Some(Commands::TEMPLATE(args)) => Ok(args.run(&self.tests)?),
+            Some(Commands::STATEMENT(args)) => Ok(args.run(&self.tests)?),
+            Some(Commands::NOTE(args)) => Ok(args.run(&self.tests)?),
+            Some(Commands::ADD_ALTERNATE(args)) => {
+                args.run(&mut self.tests).context("Failed to add alternate output")?;
+                self.write_data()
+            }
+            Some(Commands::SET_CHECKER(args)) => {
+                args.run(&mut self.tests).context("Failed to set checker")?;
+                self.write_data()
+            }
+            Some(Commands::SET_CONFIG_TAG(args)) => {
+                args.run(&mut self.tests).context("Failed to set config tag")?;
+                self.write_data()
+            }
+            Some(Commands::SET_SUBTASKS(args)) => {
+                if args.run(&mut self.tests).context("Failed to set subtasks")? {
+                    self.write_data()?;
+                }
+                Ok(())
+            }
+            Some(Commands::DESCRIBE(args)) => {
+                if args.run(&mut self.tests).context("Failed to update description")? {
+                    self.write_data()?;
+                }
+                Ok(())
+            }
+            Some(Commands::TAG(args)) => {
+                args.run(&mut self.tests).context("Failed to update tags")?;
+                self.write_data()
+            }
+            Some(Commands::MIGRATE_STORAGE(args)) => Ok(args.run(&mut self.tests)?),
+            Some(Commands::RESTORE(args)) => {
+                args.run(&mut self.tests).context("Failed to restore test")?;
                 self.write_data()
             }
-            Some(Commands::CONFIG(args)) => args.run(),
+            Some(Commands::DOCTOR(args)) => {
+                if args.run(&mut self.tests).context("Failed to check stored tests")? {
+                    self.write_data()?;
+                }
+                Ok(())
+            }
+            Some(Commands::OPEN(args)) => {
+                let Some(test) = self.tests.get(&args.test_name) else {
+                    return Err(Test::not_found(&args.test_name, &self.tests));
+                };
+                Ok(args.run(test)?)
+            }
             _ => unreachable!(),
         }
     }
 
-    pub fn load_empty_tests() -> Result<HashMap<String, Test>, String> {
-        let data_dir = handle_option!(
-            dirs::data_local_dir(),
-            "Failed to get data directory, not sure why this should happen, look into dirs::data_local_dir() to find more about error"
+    // For rename and duplicate, the test being renamed can differ from the new name only in capitalization
+    fn check_new_name(&self, new_name: &String, old_name: &String) -> Result<(), String> {
+        names::validate(new_name, "Test")?;
+        if self.tests.contains_key(new_name) {
+            return Err(format!("Test with name \"{}\" already exists", new_name));
+        }
+        let conflict = names::find_conflict(new_name, self.tests.keys().filter(|name| *name != old_name));
+        if let Some(conflict) = conflict {
+            return Err(format!(
+                "Test name \"{}\" is the same as the existing test \"{}\" except for capitalization, which would be the same directory on Windows and macOS",
+                new_name, conflict
+            ));
+        }
+        Ok(())
+    }
+
+    // Adds and writes a test from a single input, returns the name of the test and its amount of cases
+    fn add_test(&mut self, args: &AddArgs) -> Result<(String, usize), Error> {
+        let (test_name, test) = self.build_test(args)?;
+        let case_count = test.cases.len();
+        if args.dry_run {
+            print_dry_run(&test_name, &test);
+            return Ok((test_name, case_count));
+        }
+        self.save_test(args, &test_name, test).context("Failed to write data for new test")?;
+        // The cases are on disk now, so writing data for the next test doesn't write this one again
+        if let Some(test) = self.tests.get_mut(&test_name) {
+            test.cases.clear();
+        }
+        Ok((test_name, case_count))
+    }
+
+    // Downloads or scans the input of add into a test without writing it anywhere
+    fn build_test(&mut self, args: &AddArgs) -> Result<(String, Test), Error> {
+        let (input_io, output_io) = args.get_io().context("Failed to get IO Data")?;
+        let (test_name, test_path, submission_data, description) = args.get_test_data().context("Failed to get test data")?;
+        if !args.input_type_is_folder() {
+            self.temp_path = Some(test_path.clone());
+        }
+        // oj.uz problems without cases are usually grader based, which is more helpful to say than that no cases were found
+        let is_ojuz = submission_data.as_ref().is_some_and(|submission_data| submission_data.submission_type == SubmissionType::OJUZ);
+        let naming = args.get_case_naming(&test_path);
+        let mut test = Test::from_folder(test_path, &naming, input_io, output_io, submission_data, description, args.get_scan_options()?)
+            .context(if is_ojuz { OJUZ_NO_CASES_ERROR } else { "Failed to create test from folder/zip" })?;
+        // The limits are only extra info, so not finding them doesn't stop the add
+        if let Some(submission_data) = &test.submission_data {
+            (test.time_limit_ms, test.memory_limit_mb) = submission_data.get_limits().unwrap_or_default();
+            if let Some(time_limit_ms) = test.time_limit_ms {
+                println!("Time limit is {} ms", time_limit_ms);
+            }
+            if let Some(memory_limit_mb) = test.memory_limit_mb {
+                println!("Memory limit is {} MB", memory_limit_mb);
+            }
+        }
+        find_subtasks(&mut test, vec![]);
+        validate_test(&test, args.strict)?;
+        test.add_tags(&args.tags);
+        Ok((test_name, test))
+    }
+
+    // Writes a new test, or merges it into the existing test with the same name for add --update
+    fn save_test(&mut self, args: &AddArgs, test_name: &String, mut test: Test) -> Result<(), Error> {
+        if !args.update || !self.tests.contains_key(test_name) {
+            test.check_case_names()?;
+            test.record_metadata(args.source());
+            self.modified.insert(test_name.clone());
+            self.tests.insert(test_name.clone(), test);
+            return self.write_data();
+        }
+        let data_dir = data_dir::get()?;
+        let test_path = data_dir.join("tests").join(test_name);
+        let replace_io = args.io.is_some() || test.submission_data.is_some();
+        let existing = self.tests.get_mut(test_name).unwrap();
+        existing.fill_cases(test_path.clone()).context("Failed to load cases of existing test")?;
+        // New cases are stored the same way as the existing ones, migrate-storage converts all of them
+        let compress = existing.is_compressed();
+        let (changed, counts) = existing.merge(test, args.overwrite_cases, replace_io);
+        existing.check_case_names()?;
+        // When it was added and from where stay the same, only the size changes
+        existing.metadata.data_size_bytes = Some(existing.data_size());
+        // Only the changed cases are written, since write_data would replace the directory and lose the run history
+        for name in &changed {
+            let case = &existing.cases[name];
+            let (input_file, output_file) = existing.case_file_names(name, compress);
+            let (input_path, output_path) = (test_path.join(input_file), test_path.join(output_file));
+            if compress {
+                case.write_compressed(&input_path, &output_path, name)?;
+            } else {
+                case.write_data(&input_path, &output_path, name)?;
+            }
+        }
+        existing.cases.clear();
+        self.write_data()?;
+        println!(
+            "Updated test \"{}\": {} cases added, {} updated, {} unchanged",
+            test_name, counts.added, counts.updated, counts.unchanged
         );
-        let data_dir = data_dir.join(DEFAULT_FOLDER_NAME);
+        if counts.differing > 0 {
+            println!(
+                "{} cases have the same name as an existing case but different data and were kept, use --overwrite-cases to replace them",
+                counts.differing
+            );
+        }
+        Ok(())
+    }
+
+    pub fn load_empty_tests() -> Result<HashMap<String, Test>, Error> {
+        let data_dir = data_dir::get()?;
         if !data_dir.exists() {
             fs::create_dir_all(&data_dir).map_err(|e| "Error creating data directory:\n".to_string() + &e.to_string())?;
         }
@@ -143,20 +449,16 @@ impl ProgramData {
                 )
             })?;
             if !metadata.is_file() {
-                return Err(format!("test.json in {} is not a file", data_dir.to_str().unwrap()));
+                return Err(format!("test.json in {} is not a file", data_dir.to_str().unwrap()).into());
             }
-            let main_file = fs::read_to_string(&main_path).map_err(|e| "Error reading test.json:\n".to_string() + &e.to_string())?;
-            let main: HashMap<String, EmptyTest> =
-                serde_json::from_str(&main_file).map_err(|e| "Error parsing test.json in data dir:\n".to_string() + &e.to_string())?;
+            let main: HashMap<String, EmptyTest> = json_file::read(&main_path).context("Error loading test.json in data dir")?;
             for (name, empty_test) in main {
                 let test = Test::from(empty_test);
                 tests.insert(name, test);
             }
         } else {
             let main: HashMap<String, EmptyTest> = HashMap::new();
-            let main_file =
-                serde_json::to_string_pretty(&main).map_err(|e| "Error serializing test.json in data dir:\n".to_string() + &e.to_string())?;
-            fs::write(&main_path, main_file).map_err(|e| "Error writing test.json in data dir:\n".to_string() + &e.to_string())?;
+            json_file::write(&main_path, &main).context("Error writing test.json in data dir")?;
         }
         Ok(tests)
     }
@@ -164,31 +466,350 @@ impl ProgramData {
     pub fn clear_temp_files(&self) -> Result<(), String> {
         if let Some(temp_path) = &self.temp_path {
             if temp_path.exists() {
-                std::fs::remove_dir_all(temp_path).map_err(|e| e.to_string())?;
+                paths::remove_dir_all(temp_path).map_err(|e| e.to_string())?;
             }
         }
         Ok(())
     }
 
-    pub fn write_data(&self) -> Result<(), String> {
-        let data_dir = handle_option!(
-            dirs::data_local_dir(),
-            "Failed to get data directory, not sure why this should happen, look into dirs::data_local_dir() to find more about error"
-        );
-        let data_dir = data_dir.join(DEFAULT_FOLDER_NAME);
-        for (name, test) in self.tests.iter().filter(|(_, test)| !test.is_empty()) {
-            let test_path = data_dir.join("tests").join(name);
-            if test_path.exists() {
-                handle_error!(fs::remove_dir_all(&test_path), "Error removing test directory:")
+    // `cp-tester run`, the error decides the exit code
+    fn run_tests(&mut self, args: &RunArgs) -> Result<(), Error> {
+        let args = self.run_args(args)?;
+        if args.adhoc {
+            return ProgramData::run_adhoc(&args);
+        }
+        // The file is compiled by the first test, and the other tests reuse it
+        let mut run_dir = None;
+        let mut reports = vec![];
+        let mut errors = vec![];
+        for test_name in args.tests.clone() {
+            if args.tests.len() > 1 {
+                output_line(args.json, &format!("Running \"{}\"", test_name));
+            }
+            let report = match self.run_test(&args, &test_name, &mut run_dir) {
+                Ok(report) => report,
+                Err(e) if args.tests.len() > 1 && !args.fail_fast => {
+                    eprintln!("{}: {}", color::red("ERROR"), e);
+                    errors.push((test_name, e));
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            if let Some(diagnostics) = report.compile.diagnostics.clone() {
+                reports.push(report);
+                if args.json {
+                    RunReport::print_all(&reports)?;
+                }
+                return Err(Error::COMPILE_FAILED { diagnostics });
+            }
+            let failed = report.failed_cases() > 0;
+            if args.tests.len() > 1 {
+                output_line(args.json, &format!("{}\n", report.summary()));
+            }
+            reports.push(report);
+            if failed && args.fail_fast {
+                break;
+            }
+        }
+        if args.json {
+            RunReport::print_all(&reports)?;
+        }
+        if args.tests.len() > 1 {
+            let not_run = errors.iter().map(|(test_name, _)| test_name.as_str()).collect::<Vec<&str>>();
+            print_summary(&reports, &not_run, args.json);
+        }
+        ProgramData::run_result(&reports, errors.len())
+    }
+
+    // The args of `cp-tester run` with the project config applied
+    fn run_args(&self, args: &RunArgs) -> Result<RunArgs, String> {
+        if args.example && args.cases.is_some() {
+            return Err("Cannot run example and specified cases at the same time".to_string());
+        }
+        let project_config = handle_error!(ProjectConfig::find(), "Failed to load project config file(.cptester.json)");
+        let mut args = args.clone();
+        args.apply_project_config(project_config)?;
+        let last_file = match args.tests.first().and_then(|test_name| Test::resolve_prefix(test_name, &self.tests).ok()) {
+            Some(test_name) => ProgramData::last_run_file(&data_dir::get()?.join("tests").join(test_name)),
+            None => None,
+        };
+        args.resolve_file(last_file)?;
+        if args.tests.len() > 1 && args.report.is_some() {
+            return Err("--report can only be used when running one test".to_string());
+        }
+        Ok(args)
+    }
+
+    // The file the test was last run with, a history that can't be read is the same as no runs
+    fn last_run_file(test_dir: &Path) -> Option<PathBuf> {
+        RunHistory::load(test_dir).ok()?.last_run().map(|run| run.file.clone())
+    }
+
+    // not_run is the amount of tests that didn't exist or failed to run
+    fn run_result(reports: &[RunReport], not_run: usize) -> Result<(), Error> {
+        let wrong_answers = reports.iter().map(|report| report.wrong_answers()).sum::<usize>();
+        let timeouts_and_crashes = reports.iter().map(|report| report.timeouts_and_crashes()).sum::<usize>();
+        let mut problems = vec![];
+        if wrong_answers > 0 {
+            problems.push(format!("{} cases had a wrong answer", wrong_answers));
+        }
+        if timeouts_and_crashes > 0 {
+            problems.push(format!("{} cases timed out or crashed", timeouts_and_crashes));
+        }
+        if not_run > 0 {
+            problems.push(format!("{} tests couldn't be run", not_run));
+        }
+        let message = problems.join(", ");
+        if not_run > 0 {
+            Err(Error::OTHER(message))
+        } else if wrong_answers > 0 {
+            Err(Error::CASE_FAILED { verdict: Verdict::FAILED, message })
+        } else if timeouts_and_crashes > 0 {
+            Err(Error::CASE_FAILED { verdict: Verdict::TIMEOUT, message })
+        } else {
+            Ok(())
+        }
+    }
+
+    // `cp-tester run --adhoc`
+    fn run_adhoc(args: &RunArgs) -> Result<(), Error> {
+        let report = ProgramData::adhoc_report(args)?;
+        if args.json {
+            RunReport::print_all(std::slice::from_ref(&report))?;
+        }
+        if let Some(diagnostics) = report.compile.diagnostics {
+            return Err(Error::COMPILE_FAILED { diagnostics });
+        }
+        ProgramData::run_result(&[report], 0)
+    }
+
+    // Nothing is stored for --adhoc, so there is no history or report
+    fn adhoc_report(args: &RunArgs) -> Result<RunReport, Error> {
+        let mut args = args.clone();
+        let test = args.adhoc_test()?;
+        let config = args.load_config(&test).context("Failed to load in config")?;
+        // The test has no directory, --checker is the only checker it can have
+        let test_dir = current_dir().context("Failed to get current directory")?;
+        let mut run_dir = RunDir::new(&test, &test_dir, &args, &config).context("Failed to compile file and store in temp dir")?;
+        let file = args.file.clone().unwrap();
+        if let Some(diagnostics) = run_dir.get_compile_diagnostics() {
+            return Ok(RunReport::new(ADHOC_CASE_NAME, &file, Some(diagnostics), &[]));
+        }
+        let results = run_dir.run().context("Failed to run file")?;
+        Ok(RunReport::new(ADHOC_CASE_NAME, &file, None, &results))
+    }
+
+    // One test of `cp-tester run`, the file is compiled into run_dir by the first test
+    fn run_test(&mut self, args: &RunArgs, test_name: &str, run_dir: &mut Option<RunDir>) -> Result<RunReport, Error> {
+        let test_name = &Test::resolve_prefix(test_name, &self.tests)?;
+        // Every test uses the defaults of its own config tag
+        let mut args = args.clone();
+        let config = args.load_config(&self.tests[test_name]).context("Failed to load in config")?;
+        let folder = data_dir::get()?;
+        let folder = folder.join("tests").join(test_name);
+        if args.save {
+            let checker = args.checker.clone().unwrap();
+            save_checker(self.tests.get_mut(test_name).unwrap(), &folder, &checker).context("Failed to store checker")?;
+            self.write_data()?;
+            let message = format!("Stored checker {:?} with \"{}\"", checker.file_name().unwrap_or_default(), test_name);
+            if args.json {
+                eprintln!("{}", message);
+            } else {
+                println!("{}", message);
+            }
+        }
+        let test = self.tests.get_mut(test_name).unwrap();
+        test.fill_cases(folder.clone()).context("Failed to load test cases")?;
+        let run_dir = match run_dir {
+            Some(run_dir) => {
+                run_dir.set_test(test, &folder, &args, &config).context("Failed to compile file and store in temp dir")?;
+                run_dir
+            }
+            None => run_dir.insert(RunDir::new(test, &folder, &args, &config).context("Failed to compile file and store in temp dir")?),
+        };
+        let file = args.file.clone().unwrap();
+        if let Some(diagnostics) = run_dir.get_compile_diagnostics() {
+            return Ok(RunReport::new(test_name, &file, Some(diagnostics), &[]));
+        }
+        let results = run_dir.run().context(format!("Failed to run test \"{}\"", test_name))?;
+        let mut report = RunReport::new(test_name, &file, None, &results);
+        report.score_subtasks(&test.subtasks, &test.get_sorted_case_names());
+        report.print_score(args.json);
+        let _history_lock = lock::acquire_history()?;
+        let mut history = RunHistory::load(&folder).context("Failed to load run history")?;
+        let compile_command = run_dir.get_compile_description().clone();
+        let run = history.add_run(file, compile_command, results, config.history_limit);
+        if let Some(report_path) = &args.report {
+            write_report(report_path, test_name, test, run)?;
+            if args.json {
+                eprintln!("Wrote report to {:?}", report_path);
             } else {
-                handle_error!(fs::create_dir_all(&test_path), "Error creating test directory:");
+                println!("Wrote report to {:?}", report_path);
+            }
+        }
+        history.save(&folder).context("Failed to save run history")?;
+        // Cases are only loaded while the test runs, so running many tests doesn't keep all of them in memory
+        test.cases.clear();
+        Ok(report)
+    }
+
+    // Other tests' directories are never touched, so a crash while writing can only affect the tests being changed
+    pub fn write_data(&mut self) -> Result<(), Error> {
+        let data_dir = data_dir::get()?;
+        // The config is only needed when cases are written
+        let compress = !self.modified.is_empty() && Config::get().context("Failed to load config")?.compress_test_data;
+        self.write_tests(&data_dir, compress)
+    }
+
+    fn write_tests(&mut self, data_dir: &Path, compress: bool) -> Result<(), Error> {
+        for name in self.modified.drain() {
+            let Some(test) = self.tests.get(&name).filter(|test| !test.is_empty()) else {
+                continue;
+            };
+            let test_path = data_dir.join("tests").join(&name);
+            if test_path.exists() {
+                paths::remove_dir_all(&test_path).context("Error removing test directory:")?;
             }
-            handle_error!(test.write_data(&test_path), "Error writing test data");
+            fs::create_dir_all(&test_path).context("Error creating test directory:")?;
+            test.write_data(&test_path, compress).context("Error writing test data")?;
         }
         let main_path = data_dir.join("test.json");
         let main: HashMap<String, EmptyTest> = self.tests.iter().map(|(name, test)| (name.clone(), test.into())).collect();
-        let main_file = serde_json::to_string_pretty(&main).map_err(|e| "Error serializing test.json in data dir:\n".to_string() + &e.to_string())?;
-        fs::write(&main_path, main_file).map_err(|e| "Error writing test.json in data dir:\n".to_string() + &e.to_string())?;
+        json_file::write(&main_path, &main).context("Error writing test.json in data dir")?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::run_history::CaseResult;
+    use crate::test_data::TestCase;
+    use std::{
+        fs::File,
+        time::{Duration, SystemTime},
+    };
+
+    fn program_data() -> ProgramData {
+        ProgramData {
+            cli_data: CliData::parse_from(["cp-tester", "list"]),
+            tests: HashMap::new(),
+            modified: HashSet::new(),
+            temp_path: None,
+        }
+    }
+
+    fn add_test(program_data: &mut ProgramData, name: &str) {
+        let mut test = Test::adhoc(TestCase::new(b"1 2\n".to_vec(), Some(b"3\n".to_vec())));
+        test.cases.insert("2".to_string(), TestCase::new(b"5 5\n".to_vec(), Some(b"10\n".to_vec())));
+        program_data.tests.insert(name.to_string(), test);
+        program_data.modified.insert(name.to_string());
+    }
+
+    fn read_files(dir: &Path) -> Vec<(PathBuf, Vec<u8>, SystemTime)> {
+        let mut files = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| {
+                let path = entry.unwrap().path();
+                let modified = fs::metadata(&path).unwrap().modified().unwrap();
+                (path.clone(), fs::read(&path).unwrap(), modified)
+            })
+            .collect::<Vec<_>>();
+        files.sort();
+        files
+    }
+
+    // Far enough in the past that a rewritten file can't get the same mtime, even on filesystems that only keep seconds
+    fn set_past_mtimes(dir: &Path) {
+        let past = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        for entry in fs::read_dir(dir).unwrap() {
+            File::options().write(true).open(entry.unwrap().path()).unwrap().set_modified(past).unwrap();
+        }
+    }
+
+    fn report(verdicts: &[Verdict]) -> RunReport {
+        let results = verdicts
+            .iter()
+            .enumerate()
+            .map(|(i, verdict)| CaseResult::new(&i.to_string(), verdict.clone(), 1.0, None))
+            .collect::<Vec<CaseResult>>();
+        RunReport::new("sum", Path::new("sol.cpp"), None, &results)
+    }
+
+    fn exit_code(result: Result<(), Error>) -> i32 {
+        result.err().map_or(0, |error| error.exit_code())
+    }
+
+    #[test]
+    fn run_exit_codes() {
+        assert_eq!(exit_code(ProgramData::run_result(&[report(&[Verdict::PASSED, Verdict::NOT_JUDGED])], 0)), 0);
+        assert_eq!(exit_code(ProgramData::run_result(&[report(&[Verdict::PASSED, Verdict::FAILED])], 0)), 3);
+        assert_eq!(exit_code(ProgramData::run_result(&[report(&[Verdict::RUNTIME_ERROR])], 0)), 4);
+        // The lowest code is used
+        assert_eq!(exit_code(ProgramData::run_result(&[report(&[Verdict::TIMEOUT]), report(&[Verdict::FAILED])], 0)), 3);
+        assert_eq!(exit_code(ProgramData::run_result(&[report(&[Verdict::FAILED])], 1)), 1);
+        assert_eq!(Error::COMPILE_FAILED { diagnostics: String::new() }.exit_code(), 2);
+    }
+
+    // The kind of error is kept through the context the command adds, only the message changes
+    #[test]
+    fn missing_tests_are_not_found_errors() {
+        let mut program_data = program_data();
+        add_test(&mut program_data, "two_sum");
+        program_data.cli_data = CliData::parse_from(["cp-tester", "list", "test", "two_sun"]);
+        let error = program_data.run_command().unwrap_err();
+        let Error::CONTEXT { source, .. } = &error else {
+            panic!("Expected the context of list, got {:?}", error);
+        };
+        assert!(matches!(**source, Error::TEST_NOT_FOUND(_)));
+        assert!(error.to_string().starts_with("Failed to list test/cases: \nError Data: Test with name \"two_sun\" doesn't exist"));
+        assert!(error.to_string().contains("two_sum"));
+
+        program_data.cli_data = CliData::parse_from(["cp-tester", "rename", "two_sun", "sum"]);
+        assert!(matches!(program_data.run_command(), Err(Error::TEST_NOT_FOUND(_))));
+        program_data.cli_data = CliData::parse_from(["cp-tester", "remove", "two_sum", "two_sun"]);
+        let Some(Commands::REMOVE(args)) = &program_data.cli_data.command else {
+            unreachable!()
+        };
+        assert!(matches!(args.test_names(&program_data.tests), Err(Error::TEST_NOT_FOUND(_))));
+    }
+
+    #[test]
+    fn write_leaves_unmodified_tests_alone() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let mut program_data = program_data();
+        add_test(&mut program_data, "a");
+        program_data.write_tests(data_dir.path(), false).unwrap();
+        let test_a = data_dir.path().join("tests").join("a");
+        set_past_mtimes(&test_a);
+        let before = read_files(&test_a);
+        assert_eq!(before.len(), 4);
+        assert!(program_data.modified.is_empty());
+
+        add_test(&mut program_data, "b");
+        program_data.write_tests(data_dir.path(), false).unwrap();
+        assert_eq!(read_files(&test_a), before);
+        assert_eq!(read_files(&data_dir.path().join("tests").join("b")).len(), 4);
+
+        program_data.write_tests(data_dir.path(), false).unwrap();
+        assert_eq!(read_files(&test_a), before);
+        let stored: HashMap<String, EmptyTest> = json_file::read(&data_dir.path().join("test.json")).unwrap();
+        assert_eq!(stored.len(), 2);
+    }
+
+    #[test]
+    fn write_recreates_modified_test_dir() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let mut program_data = program_data();
+        add_test(&mut program_data, "a");
+        program_data.write_tests(data_dir.path(), false).unwrap();
+        let test_a = data_dir.path().join("tests").join("a");
+        fs::write(test_a.join("stale.in"), "stale").unwrap();
+
+        program_data.tests.get_mut("a").unwrap().cases.remove("2");
+        program_data.modified.insert("a".to_string());
+        program_data.write_tests(data_dir.path(), false).unwrap();
+        let files = read_files(&test_a).into_iter().map(|(path, _, _)| path).collect::<Vec<PathBuf>>();
+        assert_eq!(files, vec![test_a.join("adhoc.in"), test_a.join("adhoc.out")]);
+    }
+}
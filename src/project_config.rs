@@ -0,0 +1,54 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::handle_error;
+
+pub const PROJECT_CONFIG_FILE_NAME: &str = ".cptester.json";
+
+// Per-directory defaults for tester run, found by walking up from the current directory
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct ProjectConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpp_ver: Option<String>,
+    #[serde(skip)]
+    pub dir: PathBuf,
+}
+
+impl ProjectConfig {
+    pub fn find() -> Result<Option<ProjectConfig>, String> {
+        let current_dir = handle_error!(env::current_dir(), "Failed to get current directory");
+        let mut dir: Option<&Path> = Some(&current_dir);
+        while let Some(search_dir) = dir {
+            let path = search_dir.join(PROJECT_CONFIG_FILE_NAME);
+            if path.is_file() {
+                let project_file = handle_error!(fs::read_to_string(&path), format!("Failed to read project config file({:?})", path));
+                let mut project_config: ProjectConfig =
+                    handle_error!(serde_json::from_str(&project_file), format!("Failed to parse project config file({:?})", path));
+                project_config.dir = search_dir.to_path_buf();
+                return Ok(Some(project_config));
+            }
+            dir = search_dir.parent();
+        }
+        Ok(None)
+    }
+
+    // The file is stored relative to the directory the project config is in
+    pub fn get_file(&self) -> Option<PathBuf> {
+        self.file.as_ref().map(|file| self.dir.join(file))
+    }
+
+    pub fn write(&self, dir: &Path) -> Result<PathBuf, String> {
+        let path = dir.join(PROJECT_CONFIG_FILE_NAME);
+        let project_file = handle_error!(serde_json::to_string_pretty(&self), "Failed to serialize project config file");
+        handle_error!(fs::write(&path, project_file), format!("Failed to write project config file({:?})", path));
+        Ok(path)
+    }
+}
@@ -0,0 +1,187 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Error, json_file};
+
+pub const HISTORY_FILE_NAME: &str = "history.json";
+// Amount of program output kept for a failed case, so history.json doesn't grow with huge outputs
+const MAX_OUTPUT_CHARS: usize = 1000;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+pub enum Verdict {
+    PASSED,
+    FAILED,
+    TIMEOUT,
+    // The case has no expected output, so it was run but not compared
+    NOT_JUDGED,
+    // The program exited with a non-zero exit code
+    RUNTIME_ERROR,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CaseResult {
+    pub name: String,
+    pub verdict: Verdict,
+    pub time_ms: f64,
+    pub details: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_diff_line: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stderr_tail: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RunRecord {
+    pub id: u32,
+    pub timestamp: DateTime<Local>,
+    pub file: PathBuf,
+    #[serde(default)]
+    pub compile_command: Option<String>,
+    pub cases: Vec<CaseResult>,
+}
+
+// Past runs of a test, stored in the test's directory so renames and removes carry it along
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct RunHistory {
+    runs: Vec<RunRecord>,
+}
+
+impl Verdict {
+    pub fn is_pass(&self) -> bool {
+        *self == Verdict::PASSED
+    }
+
+    // Not judged cases are neither passes nor failures
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Verdict::FAILED | Verdict::TIMEOUT | Verdict::RUNTIME_ERROR)
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let string = match self {
+            Verdict::PASSED => "PASSED",
+            Verdict::FAILED => "FAILED",
+            Verdict::TIMEOUT => "TIMED OUT",
+            Verdict::NOT_JUDGED => "NOT JUDGED",
+            Verdict::RUNTIME_ERROR => "RUNTIME ERROR",
+        };
+        write!(f, "{}", string)
+    }
+}
+
+impl CaseResult {
+    pub fn new(name: &str, verdict: Verdict, time_ms: f64, details: Option<String>) -> CaseResult {
+        CaseResult {
+            name: name.to_string(),
+            verdict,
+            time_ms,
+            details,
+            output: None,
+            first_diff_line: None,
+            stderr_tail: None,
+        }
+    }
+
+    // Only the start of the output is kept
+    pub fn set_output(&mut self, output: &str) {
+        let output = if output.chars().count() > MAX_OUTPUT_CHARS {
+            format!("{}...(Truncated)", output.chars().take(MAX_OUTPUT_CHARS).collect::<String>())
+        } else {
+            output.to_string()
+        };
+        self.output = Some(output);
+    }
+}
+
+impl RunRecord {
+    pub fn passed(&self) -> usize {
+        self.cases.iter().filter(|case| case.verdict.is_pass()).count()
+    }
+
+    pub fn slowest(&self) -> Option<&CaseResult> {
+        self.cases.iter().max_by(|a, b| a.time_ms.total_cmp(&b.time_ms))
+    }
+
+    pub fn verdict(&self) -> Verdict {
+        if self.cases.iter().any(|case| case.verdict == Verdict::TIMEOUT) {
+            Verdict::TIMEOUT
+        } else if self.cases.iter().any(|case| case.verdict == Verdict::RUNTIME_ERROR) {
+            Verdict::RUNTIME_ERROR
+        } else if self.cases.iter().any(|case| case.verdict.is_failure()) {
+            Verdict::FAILED
+        } else if self.passed() == self.cases.len() {
+            Verdict::PASSED
+        } else {
+            Verdict::NOT_JUDGED
+        }
+    }
+}
+
+impl RunHistory {
+    pub fn load(test_dir: &Path) -> Result<RunHistory, Error> {
+        let path = test_dir.join(HISTORY_FILE_NAME);
+        if !path.exists() {
+            return Ok(RunHistory::default());
+        }
+        json_file::read(&path)
+    }
+
+    pub fn save(&self, test_dir: &Path) -> Result<(), Error> {
+        json_file::write(&test_dir.join(HISTORY_FILE_NAME), self)
+    }
+
+    // Oldest runs are removed once there are more than limit runs
+    pub fn add_run(&mut self, file: PathBuf, compile_command: String, cases: Vec<CaseResult>, limit: usize) -> &RunRecord {
+        let id = self.runs.last().map(|run| run.id + 1).unwrap_or(1);
+        self.runs.push(RunRecord {
+            id,
+            timestamp: Local::now(),
+            file,
+            compile_command: Some(compile_command),
+            cases,
+        });
+        let limit = limit.max(1);
+        if self.runs.len() > limit {
+            let excess = self.runs.len() - limit;
+            self.runs.drain(0..excess);
+        }
+        self.runs.last().unwrap()
+    }
+
+    pub fn get_run(&self, id: u32) -> Option<&RunRecord> {
+        self.runs.iter().find(|run| run.id == id)
+    }
+
+    pub fn last_run(&self) -> Option<&RunRecord> {
+        self.runs.last()
+    }
+
+    // The verdict of each case in the newest run that ran it, runs with --cases only run some of them
+    pub fn last_verdicts(&self) -> HashMap<&str, &Verdict> {
+        let mut verdicts = HashMap::new();
+        for run in self.runs.iter() {
+            for case in run.cases.iter() {
+                verdicts.insert(case.name.as_str(), &case.verdict);
+            }
+        }
+        verdicts
+    }
+
+    pub fn newest_first(&self) -> impl Iterator<Item = &RunRecord> {
+        self.runs.iter().rev()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+}
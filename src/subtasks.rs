@@ -0,0 +1,169 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::handle_error;
+
+// Cases named like subtask1_01, sub2-05, or group3_a are grouped by that prefix when a test is added
+// secret_ is the start of every secret case from ICPC packages, whose groups are subfolders
+const INFERRED_PREFIX_REGEX_STR: &str = r"(?i)^((?:secret_)?(?:subtask|sub|group)\d+)([_-])";
+// Inferred subtasks split this evenly, since case names don't say how many points they are worth
+const INFERRED_TOTAL_POINTS: u64 = 100;
+
+// A group of cases that only scores its points if every case in it passes, like IOI subtasks
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Subtask {
+    pub name: String,
+    // Globs(* and ?) matched against case names without case, more than one can be given separated by |
+    pub pattern: String,
+    pub points: u64,
+}
+
+// Schema of --json output, fields should only be added, not renamed or removed
+#[derive(Debug, Serialize)]
+pub struct SubtaskScore {
+    pub name: String,
+    pub points: u64,
+    pub score: u64,
+    pub passed: usize,
+    pub cases: usize,
+    // Cases of the subtask that weren't run, like with --cases, the subtask doesn't score if there are any
+    pub not_run: usize,
+}
+
+impl Subtask {
+    // Like subtask1=*:10,subtask2=big_*:40
+    pub fn parse_all(spec: &str) -> Result<Vec<Subtask>, String> {
+        let mut subtasks: Vec<Subtask> = vec![];
+        for part in spec.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+            let invalid = || format!("Invalid subtask \"{}\", subtasks are written like name=pattern:points", part);
+            let (name, rest) = part.split_once('=').ok_or_else(invalid)?;
+            let (pattern, points) = rest.rsplit_once(':').ok_or_else(invalid)?;
+            let (name, pattern) = (name.trim(), pattern.trim());
+            if name.is_empty() || pattern.is_empty() {
+                return Err(invalid());
+            }
+            let points = handle_error!(
+                points.trim().parse::<u64>(),
+                format!("Invalid points \"{}\" for subtask \"{}\"", points, name)
+            );
+            if subtasks.iter().any(|subtask| subtask.name == name) {
+                return Err(format!("Subtask \"{}\" is given more than once", name));
+            }
+            subtasks.push(Subtask {
+                name: name.to_string(),
+                pattern: pattern.to_string(),
+                points,
+            });
+        }
+        if subtasks.is_empty() {
+            return Err("No subtasks given".to_string());
+        }
+        Ok(subtasks)
+    }
+
+    // The same format parse_all takes
+    pub fn format_all(subtasks: &[Subtask]) -> String {
+        subtasks
+            .iter()
+            .map(|subtask| format!("{}={}:{}", subtask.name, subtask.pattern, subtask.points))
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+
+    // Only when there are at least 2 prefixes, cases without one(Like samples) aren't in any subtask
+    pub fn infer(case_names: &[&String]) -> Vec<Subtask> {
+        let prefix_regex = Regex::new(INFERRED_PREFIX_REGEX_STR).unwrap();
+        // Prefix and the separator after it
+        let mut prefixes: Vec<(String, String)> = vec![];
+        for name in case_names {
+            let Some(captures) = prefix_regex.captures(name) else {
+                continue;
+            };
+            if !prefixes.iter().any(|(other, _)| other.eq_ignore_ascii_case(&captures[1])) {
+                prefixes.push((captures[1].to_string(), captures[2].to_string()));
+            }
+        }
+        if prefixes.len() < 2 {
+            return vec![];
+        }
+        // The case names are sorted, so subtask2 comes before subtask10, and the last subtasks get the points left over
+        let count = prefixes.len() as u64;
+        prefixes
+            .into_iter()
+            .enumerate()
+            .map(|(i, (prefix, separator))| Subtask {
+                pattern: format!("{}{}*", prefix, separator),
+                points: INFERRED_TOTAL_POINTS / count + u64::from((i as u64) >= count - INFERRED_TOTAL_POINTS % count),
+                name: prefix,
+            })
+            .collect()
+    }
+
+    // The globs are compiled once for all of the cases
+    fn matcher(&self) -> impl Fn(&str) -> bool {
+        let globs = self.pattern.split('|').map(|glob| glob_regex(glob.trim())).collect::<Vec<Regex>>();
+        move |case_name| globs.iter().any(|glob| glob.is_match(case_name))
+    }
+
+    // results are the name of each case that was run and whether it passed
+    pub fn score_all(subtasks: &[Subtask], case_names: &[&String], results: &[(&str, bool)]) -> Vec<SubtaskScore> {
+        subtasks
+            .iter()
+            .map(|subtask| {
+                let matches = subtask.matcher();
+                let cases = case_names.iter().filter(|name| matches(name)).count();
+                let run = results.iter().filter(|(name, _)| matches(name)).collect::<Vec<_>>();
+                let passed = run.iter().filter(|(_, passed)| *passed).count();
+                let not_run = cases.saturating_sub(run.len());
+                SubtaskScore {
+                    name: subtask.name.clone(),
+                    points: subtask.points,
+                    score: if cases > 0 && not_run == 0 && passed == run.len() {
+                        subtask.points
+                    } else {
+                        0
+                    },
+                    passed,
+                    cases,
+                    not_run,
+                }
+            })
+            .collect()
+    }
+}
+
+impl SubtaskScore {
+    // Like: subtask2: 0/40 (3/5 passed, 2 not run)
+    pub fn describe(&self) -> String {
+        let not_run = if self.not_run > 0 {
+            format!(", {} not run", self.not_run)
+        } else {
+            String::new()
+        };
+        format!(
+            "{}: {}/{} ({}/{} passed{})",
+            self.name, self.score, self.points, self.passed, self.cases, not_run
+        )
+    }
+}
+
+// Like "Score: 60/100"
+pub fn total_score(scores: &[SubtaskScore]) -> (u64, u64) {
+    (
+        scores.iter().map(|score| score.score).sum(),
+        scores.iter().map(|score| score.points).sum(),
+    )
+}
+
+// Same as the globs of list --filter, but the whole name has to match
+fn glob_regex(glob: &str) -> Regex {
+    let pattern = glob
+        .chars()
+        .map(|c| match c {
+            '*' => ".*".to_string(),
+            '?' => ".".to_string(),
+            c => regex::escape(&c.to_string()),
+        })
+        .collect::<String>();
+    Regex::new(&format!("(?i)^{}$", pattern)).unwrap()
+}
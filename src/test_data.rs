@@ -1,9 +1,33 @@
-use crate::commands::add::SubmissionData;
+use crate::case_naming::{self, CaseNaming};
+use crate::commands::add::{format_file_size, SubmissionData};
+use crate::config::{DEFAULT_INPUT_EXTENSION, DEFAULT_OUTPUT_EXTENSION};
+use crate::error::{Context, Error};
+use crate::names;
+use crate::parallel;
+use crate::subtasks::Subtask;
 use crate::{handle_error, handle_option};
+use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::fs::{self, DirEntry};
-use std::path::PathBuf;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use zstd::zstd_safe::{self, CONTENTSIZE_ERROR, CONTENTSIZE_UNKNOWN};
+
+const INPUT_DIR_NAMES: [&str; 3] = ["in", "input", "inputs"];
+const OUTPUT_DIR_NAMES: [&str; 6] = ["out", "output", "outputs", "ans", "answer", "answers"];
+// Stored cases are named like 1.in.zst when compress_test_data is set
+pub const COMPRESSED_EXTENSION: &str = "zst";
+// Alternate outputs are stored as 1.out.alt1, 1.out.alt2, ...
+const ALTERNATE_SUFFIX: &str = "alt";
+const COMPRESSION_LEVEL: i32 = 3;
+pub const ADHOC_CASE_NAME: &str = "adhoc";
+// The most bytes a zstd frame header can take, it has the uncompressed size
+const MAX_FRAME_HEADER_SIZE: usize = 18;
+// Characters a previewed line can have, so an input on one huge line is cut too
+pub const PREVIEW_LINE_CHARS: usize = 200;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Test {
@@ -14,6 +38,67 @@ pub struct Test {
     pub(crate) output_io: IOType,
     pub(crate) submission_data: Option<SubmissionData>,
     pub(crate) description: Option<String>,
+    #[serde(default)]
+    pub(crate) tags: Vec<String>,
+    #[serde(default)]
+    pub(crate) time_limit_ms: Option<u64>,
+    #[serde(default)]
+    pub(crate) memory_limit_mb: Option<u64>,
+    // File name of the checker source, stored in the test's directory
+    #[serde(default)]
+    pub(crate) checker: Option<PathBuf>,
+    // Config tag runs use unless --config-tag is given
+    #[serde(default)]
+    pub(crate) config_tag: Option<String>,
+    // Scored in the run summary, tests without subtasks are only counted by cases
+    #[serde(default)]
+    pub(crate) subtasks: Vec<Subtask>,
+    #[serde(default)]
+    pub(crate) metadata: TestMetadata,
+    // Files found by fill_cases that couldn't be paired, only used to warn about them when adding
+    #[serde(skip)]
+    pub(crate) unmatched: UnmatchedFiles,
+}
+
+// Where and when a test was added, tests added before this was recorded have None for everything
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TestMetadata {
+    // Stored as RFC3339
+    #[serde(default)]
+    pub added_at: Option<DateTime<Local>>,
+    // The link, or the path of the folder, archive, file, or package the test was added from
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub data_size_bytes: Option<u64>,
+}
+
+// Paths are relative to the folder the cases were read from
+#[derive(Debug, Clone, Default)]
+pub struct UnmatchedFiles {
+    pub inputs: Vec<PathBuf>,
+    pub outputs: Vec<PathBuf>,
+    // Files over the size limit with their size, their cases were skipped without reading them
+    pub oversized: Vec<(PathBuf, u64)>,
+}
+
+// How the cases of a test being added are found
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScanOptions {
+    // Inputs without an output become cases with no expected output
+    pub allow_missing_outputs: bool,
+    // In bytes, cases with a bigger input or output are skipped
+    pub max_case_size: Option<u64>,
+}
+
+// What happened to the cases of a test updated with add --update
+#[derive(Debug, Default)]
+pub struct MergeCounts {
+    pub added: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    // Same name but different data, kept since --overwrite-cases wasn't given
+    pub differing: usize,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -24,12 +109,41 @@ pub struct EmptyTest {
     output_io: IOType,
     submission_data: Option<SubmissionData>,
     description: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    time_limit_ms: Option<u64>,
+    #[serde(default)]
+    memory_limit_mb: Option<u64>,
+    #[serde(default)]
+    checker: Option<PathBuf>,
+    #[serde(default)]
+    config_tag: Option<String>,
+    #[serde(default)]
+    subtasks: Vec<Subtask>,
+    #[serde(default)]
+    metadata: TestMetadata,
 }
 
+// Data is kept as bytes so archives with other encodings(Like Windows-1251) are written back exactly
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TestCase {
-    pub(crate) input: String,
-    pub(crate) output: String,
+    pub(crate) input: CaseData,
+    // None for inputs without an expected output, like ones from a generator
+    pub(crate) output: Option<CaseData>,
+    // Other accepted outputs, for problems with more than one correct answer
+    #[serde(default)]
+    pub(crate) alternates: Vec<CaseData>,
+}
+
+// Cases being added are read right away, stored cases are only read when they are run or shown
+// so running one case of a test with hundreds of MB of data doesn't load all of it
+// Sizes are of the uncompressed data
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum CaseData {
+    Bytes(Vec<u8>),
+    File(PathBuf, u64),
+    Compressed(PathBuf, u64),
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -39,121 +153,511 @@ pub enum IOType {
 }
 
 impl Test {
-    pub fn get_submission_type(&self) -> String {
-        if let Some(submission_data) = &self.submission_data {
-            format!("{}", submission_data.submission_type)
-        } else {
-            "None".to_string()
+    // The error for a test name that isn't in tests, with the names it could be a typo of
+    pub fn not_found(name: &str, tests: &HashMap<String, Test>) -> Error {
+        Error::TEST_NOT_FOUND(names::did_you_mean(format!("Test with name \"{}\" doesn't exist", name), name, tests.keys()))
+    }
+
+    // A test of one case that isn't stored, for `cp-tester run --adhoc`
+    pub fn adhoc(case: TestCase) -> Test {
+        Test {
+            cases: HashMap::from([(ADHOC_CASE_NAME.to_string(), case)]),
+            input_extension: DEFAULT_INPUT_EXTENSION.to_string(),
+            output_extension: DEFAULT_OUTPUT_EXTENSION.to_string(),
+            input_io: IOType::STD,
+            output_io: IOType::STD,
+            submission_data: None,
+            description: None,
+            tags: vec![],
+            time_limit_ms: None,
+            memory_limit_mb: None,
+            checker: None,
+            config_tag: None,
+            subtasks: vec![],
+            metadata: TestMetadata::default(),
+            unmatched: UnmatchedFiles::default(),
         }
     }
 
+    // name, or the only test whose name starts with it, so a long test name doesn't have to be typed out
+    pub fn resolve_prefix(name: &str, tests: &HashMap<String, Test>) -> Result<String, Error> {
+        if tests.contains_key(name) {
+            return Ok(name.to_string());
+        }
+        let mut candidates = tests.keys().filter(|other| other.starts_with(name)).collect::<Vec<&String>>();
+        candidates.sort();
+        match candidates.as_slice() {
+            [] => Err(Test::not_found(name, tests)),
+            [test_name] => Ok(test_name.to_string()),
+            _ => Err(Error::OTHER(format!(
+                "\"{}\" is the start of more than one test, pass more of the name: {}",
+                name,
+                candidates.iter().map(|candidate| candidate.as_str()).collect::<Vec<&str>>().join(", ")
+            ))),
+        }
+    }
+
+    pub fn case_not_found(&self, case: &str) -> String {
+        names::did_you_mean(format!("Test case with name \"{}\" does not exist", case), case, self.cases.keys())
+    }
+
+    // The order cases are run and listed in, case2 comes before case10
     pub fn get_sorted_case_names(&self) -> Vec<&String> {
         let mut case_names = self.cases.keys().collect::<Vec<&String>>();
-        let mut case_int: Vec<Option<i32>> = case_names.iter().map(|c| c.parse::<i32>().ok()).collect();
-        let all_parsed = case_int.iter().all(|c| c.is_some());
-        if all_parsed {
-            case_int.sort();
-            case_names.sort_by_key(|c| c.parse::<i32>().unwrap());
-        } else {
-            case_names.sort_by_key(|c| c.parse::<i32>().unwrap_or((c.chars().next().unwrap() as i32) - 4000));
-        }
+        // Kattis and Polygon data has sample and secret cases, the samples are run first
+        let grouped = ["sample", "secret"]
+            .iter()
+            .all(|prefix| case_names.iter().any(|name| name.to_lowercase().starts_with(prefix)));
+        let is_secret = |name: &str| grouped && !name.to_lowercase().starts_with("sample");
+        case_names.sort_by(|a, b| is_secret(a).cmp(&is_secret(b)).then_with(|| natural_cmp(a, b)));
         case_names
     }
 
+    // Tags are stored lowercase and without duplicates
+    pub fn add_tags(&mut self, tags: &[String]) -> Vec<String> {
+        let mut added = vec![];
+        for tag in tags {
+            let tag = tag.trim().to_lowercase();
+            if tag.is_empty() || self.tags.contains(&tag) {
+                continue;
+            }
+            self.tags.push(tag.clone());
+            added.push(tag);
+        }
+        self.tags.sort();
+        added
+    }
+
+    pub fn remove_tags(&mut self, tags: &[String]) -> Vec<String> {
+        let mut removed = vec![];
+        for tag in tags {
+            let tag = tag.trim().to_lowercase();
+            if let Some(index) = self.tags.iter().position(|t| t == &tag) {
+                self.tags.remove(index);
+                removed.push(tag);
+            }
+        }
+        removed
+    }
+
+    pub fn has_tags(&self, tags: &[String]) -> bool {
+        tags.iter().all(|tag| self.tags.contains(&tag.trim().to_lowercase()))
+    }
+
     pub fn is_empty(&self) -> bool {
         self.cases.is_empty()
     }
 
     pub fn from_folder(
         folder: PathBuf,
-        input_type: String,
-        output_type: String,
+        naming: &CaseNaming,
         input_io: IOType,
         output_io: IOType,
         submission_type: Option<SubmissionData>,
         description: Option<String>,
+        options: ScanOptions,
     ) -> Result<Test, String> {
+        let (input_extension, output_extension) = naming.extensions();
         let mut test = Test {
             cases: HashMap::new(),
-            input_extension: input_type,
-            output_extension: output_type,
+            input_extension,
+            output_extension,
             input_io,
             output_io,
             submission_data: submission_type,
             description,
+            tags: vec![],
+            time_limit_ms: None,
+            memory_limit_mb: None,
+            checker: None,
+            config_tag: None,
+            subtasks: vec![],
+            metadata: TestMetadata::default(),
+            unmatched: UnmatchedFiles::default(),
         };
-        test.fill_cases(folder)?;
+        test.fill_cases_with_naming(folder, naming, options)?;
 
         Ok(test)
     }
-    pub fn fill_cases(&mut self, folder: PathBuf) -> Result<(), String> {
-        let files = handle_error!(folder.read_dir(), "Invalid folder, can't read directory");
-        let mut test_case_files: Vec<(PathBuf, PathBuf)> = Vec::new();
-        let files = files
-            .filter_map(|file| {
-                if file.is_err() {
-                    println!("Invalid file in folder: {}", file.as_ref().err().unwrap().to_string());
-                }
+    // Only finds the files of the stored cases, their data is read when it is needed
+    pub fn fill_cases(&mut self, folder: PathBuf) -> Result<(), Error> {
+        if !folder.is_dir() {
+            return Err(format!(
+                "The directory of the test is missing({:?}), `cp-tester doctor --prune` removes tests without one",
+                folder
+            )
+            .into());
+        }
+        // migrate-storage removes the old files after writing all of the new ones, so if it was stopped the complete set is the larger one
+        let files = case_naming::read_files(&folder)?;
+        let count_inputs = |suffix: String| files.iter().filter(|file| file.to_string_lossy().ends_with(&suffix)).count();
+        let compressed_inputs = count_inputs(format!(".{}.{}", self.input_extension, COMPRESSED_EXTENSION));
+        let compressed = compressed_inputs > 0 && compressed_inputs >= count_inputs(format!(".{}", self.input_extension));
+        let naming = if compressed {
+            CaseNaming::EXTENSIONS(
+                format!("{}.{}", self.input_extension, COMPRESSED_EXTENSION),
+                format!("{}.{}", self.output_extension, COMPRESSED_EXTENSION),
+            )
+        } else {
+            CaseNaming::EXTENSIONS(self.input_extension.clone(), self.output_extension.clone())
+        };
+        // Stored inputs without an output were added with --allow-missing-outputs, and stored cases aren't read here so they have no size limit
+        let options = ScanOptions {
+            allow_missing_outputs: true,
+            max_case_size: None,
+        };
+        Ok(self.find_cases(folder, &naming, true, options)?)
+    }
 
-                file.ok()
+    // For a test directory that isn't in test.json, the extensions are the pair of a naming convention that the most files have
+    // The IO can't be known, so it is stdin and stdout
+    pub fn from_stored_dir(folder: &Path) -> Result<Test, Error> {
+        let files = case_naming::read_files(folder)?;
+        let extensions = files
+            .iter()
+            .filter_map(|file| {
+                let file_name = file.file_name()?.to_str()?;
+                let file_name = file_name.strip_suffix(&format!(".{}", COMPRESSED_EXTENSION)).unwrap_or(file_name);
+                Some(file_name.rsplit_once('.')?.1.to_string())
             })
-            .collect::<Vec<DirEntry>>();
-        for file in files {
-            let file_path = file.path();
-            let extension = file_path.extension();
-            if let Some(extension) = extension {
-                let extension = handle_option!(extension.to_str(), format!("Invalid file extension, {:?}, is not valid utf-8", extension));
-                if extension == &self.input_extension {
-                    let file_name = file_path.file_stem();
-                    if let Some(file_name) = file_name {
-                        let mut output_path = folder.join(PathBuf::from(file_name));
-                        output_path.set_extension(&self.output_extension);
-                        if output_path.exists() {
-                            test_case_files.push((file_path, output_path));
-                        }
+            .collect::<Vec<String>>();
+        let count = |extension: &String| extensions.iter().filter(|other| *other == extension).count();
+        let (input_extension, output_extension) = CaseNaming::conventions(Some("extensions"))
+            .iter()
+            .map(|naming| naming.extensions())
+            .filter(|(input, output)| count(input) > 0 && count(output) > 0)
+            .max_by_key(|(input, output)| count(input).min(count(output)))
+            .context(format!("No test cases found in {:?}", folder))?;
+        let mut test = Test {
+            cases: HashMap::new(),
+            input_extension,
+            output_extension,
+            input_io: IOType::STD,
+            output_io: IOType::STD,
+            submission_data: None,
+            description: None,
+            tags: vec![],
+            time_limit_ms: None,
+            memory_limit_mb: None,
+            checker: None,
+            config_tag: None,
+            subtasks: vec![],
+            metadata: TestMetadata::default(),
+            unmatched: UnmatchedFiles::default(),
+        };
+        test.fill_cases(folder.to_path_buf())?;
+        test.metadata.data_size_bytes = Some(test.data_size());
+        Ok(test)
+    }
+
+    // Whether the loaded cases are stored compressed, so cases written into the same directory match them
+    pub fn is_compressed(&self) -> bool {
+        self.cases.values().any(|case| matches!(case.input, CaseData::Compressed(_, _)))
+    }
+
+    // Names of the files a case is stored in
+    pub fn case_file_names(&self, name: &str, compress: bool) -> (String, String) {
+        if compress {
+            (
+                format!("{}.{}.{}", name, self.input_extension, COMPRESSED_EXTENSION),
+                format!("{}.{}.{}", name, self.output_extension, COMPRESSED_EXTENSION),
+            )
+        } else {
+            (format!("{}.{}", name, self.input_extension), format!("{}.{}", name, self.output_extension))
+        }
+    }
+
+    pub fn fill_cases_with_naming(&mut self, folder: PathBuf, naming: &CaseNaming, options: ScanOptions) -> Result<(), String> {
+        self.find_cases(folder, naming, false, options)
+    }
+
+    fn find_cases(&mut self, folder: PathBuf, naming: &CaseNaming, lazy: bool, options: ScanOptions) -> Result<(), String> {
+        let dirs = case_naming::find_folders(&folder);
+        // Folder the case is in(For naming when stems collide), case name, input file, output file
+        let mut test_case_files: Vec<(PathBuf, String, PathBuf, Option<PathBuf>)> = Vec::new();
+        for dir in &dirs {
+            let files = case_naming::read_files(dir)?;
+            for (name, input_path, output_path) in naming.pair_files(dir, &files) {
+                test_case_files.push((dir.clone(), name, input_path, Some(output_path)));
+            }
+            // Inputs and outputs in sibling folders, like in/1.txt and out/1.txt
+            if !is_dir_named(dir, &INPUT_DIR_NAMES) {
+                continue;
+            }
+            let parent = handle_option!(dir.parent(), "Input folder has no parent, this error shouldn't happen");
+            let output_dirs = dirs
+                .iter()
+                .filter(|other| other.parent() == Some(parent) && is_dir_named(other, &OUTPUT_DIR_NAMES));
+            for output_dir in output_dirs {
+                for file_path in &files {
+                    let (Some(file_name), Some(stem)) = (file_path.file_name().and_then(|name| name.to_str()), file_path.file_stem()) else {
+                        continue;
+                    };
+                    let name = naming.case_name(file_name).unwrap_or(stem.to_string_lossy().to_string());
+                    let output_path = naming
+                        .output_file_name(file_name)
+                        .map(|output_file_name| output_dir.join(output_file_name))
+                        .into_iter()
+                        .chain([output_dir.join(file_name)])
+                        .find(|output_path| output_path.is_file());
+                    if let Some(output_path) = output_path {
+                        test_case_files.push((parent.to_path_buf(), name, file_path.clone(), Some(output_path)));
                     }
                 }
             }
         }
+        let alternates = find_alternates(&dirs, &test_case_files)?;
+        self.unmatched = find_unmatched(&folder, &dirs, naming, &test_case_files, &alternates)?;
+        if options.allow_missing_outputs {
+            for input in std::mem::take(&mut self.unmatched.inputs) {
+                let input_path = folder.join(&input);
+                let file_name = input.file_name().unwrap_or_default().to_string_lossy().to_string();
+                let stem = input.file_stem().unwrap_or_default().to_string_lossy().to_string();
+                let name = naming.case_name(&file_name).unwrap_or(stem);
+                // Named like the cases with outputs next to them, in/1.txt is in the folder above in/
+                let case_dir = handle_option!(input_path.parent(), "Input file has no parent, this error shouldn't happen");
+                let case_dir = match case_dir.parent() {
+                    Some(parent) if is_dir_named(case_dir, &INPUT_DIR_NAMES) => parent,
+                    _ => case_dir,
+                };
+                test_case_files.push((case_dir.to_path_buf(), name, input_path, None));
+            }
+        }
         if test_case_files.is_empty() {
-            return Err(format!(
-                "No test cases found(Input extension is \".{}\", Output extension is \".{}\")",
-                &self.input_extension, &self.output_extension
-            ));
+            let extensions = case_naming::present_extensions(&folder);
+            let found = if extensions.is_empty() {
+                "The folder has no files with extensions".to_string()
+            } else {
+                format!("Extensions in the folder are .{}", extensions.join(", ."))
+            };
+            return Err(format!("No test cases found(Looked for files named like {}). {}", naming, found));
+        }
+        let mut stem_counts: HashMap<String, usize> = HashMap::new();
+        for (_, name, _, _) in &test_case_files {
+            *stem_counts.entry(name.clone()).or_default() += 1;
+        }
+        let mut seen_inputs = vec![];
+        let mut to_read = vec![];
+        for (case_dir, name, input_path, output_path) in test_case_files {
+            // The same input can be paired in both layouts
+            if seen_inputs.contains(&input_path) {
+                continue;
+            }
+            seen_inputs.push(input_path.clone());
+            // Checked before reading, so a huge file from a broken generator doesn't fill up memory
+            if let Some(max_case_size) = options.max_case_size {
+                let case_alternates = output_path.as_ref().and_then(|output_path| alternates.get(output_path));
+                let oversized = [Some(&input_path), output_path.as_ref()]
+                    .into_iter()
+                    .flatten()
+                    .chain(case_alternates.into_iter().flatten())
+                    .map(|path| (path, fs::metadata(path).map_or(0, |metadata| metadata.len())))
+                    .filter(|(_, size)| *size > max_case_size)
+                    .map(|(path, size)| (path.strip_prefix(&folder).unwrap_or(path).to_path_buf(), size))
+                    .collect::<Vec<(PathBuf, u64)>>();
+                if !oversized.is_empty() {
+                    self.unmatched.oversized.extend(oversized);
+                    continue;
+                }
+            }
+            to_read.push((case_dir, name, input_path, output_path));
         }
-        for file_set in test_case_files {
-            let name = &file_set.0.file_stem();
-            let name = handle_option!(name, "Invalid file, can't get file name, this error shouldn't happen");
-            let name = handle_option!(name.to_str(), "Invalid file name, is not valid utf-8, this error shouldn't happen");
-            let name = name.to_string();
-            let input_data = handle_error!(fs::read(file_set.0), "Invalid input file, can't read file");
-            let output_data = handle_error!(fs::read(file_set.1), "Invalid output file, can't read file");
-            let test_case = TestCase::new(input_data, output_data)?;
+        // Files are read in parallel, but named in the order they were found, so copies in different folders are named the same every time
+        let read_case = |(_, _, input_path, output_path): &(PathBuf, String, PathBuf, Option<PathBuf>)| {
+            let alternate_paths = output_path.as_ref().and_then(|output_path| alternates.get(output_path)).cloned().unwrap_or_default();
+            if lazy {
+                return TestCase::from_files(input_path.clone(), output_path.clone(), alternate_paths);
+            }
+            let input_data = handle_error!(fs::read(input_path), format!("Invalid input file({:?}), can't read file", input_path));
+            let output_data = match output_path {
+                Some(output_path) => Some(handle_error!(fs::read(output_path), format!("Invalid output file({:?}), can't read file", output_path))),
+                None => None,
+            };
+            let mut test_case = TestCase::new(input_data, output_data);
+            for alternate_path in alternate_paths {
+                let alternate = handle_error!(fs::read(&alternate_path), format!("Invalid alternate output file({:?}), can't read file", alternate_path));
+                test_case.alternates.push(CaseData::Bytes(alternate));
+            }
+            Ok(test_case)
+        };
+        let test_cases = parallel::collect_errors(parallel::map(&to_read, read_case))?;
+        for ((case_dir, name, _, _), test_case) in to_read.into_iter().zip(test_cases) {
+            let name = if stem_counts[&name] > 1 && case_dir != folder {
+                // Copies of the same case in different folders are only added once
+                let is_copy = self
+                    .cases
+                    .iter()
+                    .any(|(case_name, case)| (case_name == &name || case_name.ends_with(&format!("_{}", name))) && case.same_data(&test_case));
+                if is_copy {
+                    continue;
+                }
+                let relative_dir = case_dir.strip_prefix(&folder).unwrap_or(&case_dir);
+                let prefix = relative_dir
+                    .iter()
+                    .map(|part| part.to_string_lossy().to_string())
+                    .collect::<Vec<String>>()
+                    .join("_");
+                format!("{}_{}", prefix, name)
+            } else {
+                name
+            };
             self.cases.insert(name, test_case);
         }
+        if self.cases.is_empty() && !self.unmatched.oversized.is_empty() {
+            return Err("Every case is over the case size limit(--no-size-limit adds them anyway)".to_string());
+        }
         Ok(())
     }
 
-    pub fn write_data(&self, path: &PathBuf) -> Result<(), String> {
-        for (name, test_case) in &self.cases {
-            let input_file = format!("{}.{}", name, self.input_extension);
-            let output_file = format!("{}.{}", name, self.output_extension);
-            let input_path = path.join(PathBuf::from(input_file));
-            let output_path = path.join(PathBuf::from(output_file));
-            test_case.write_data(&input_path, &output_path, name)?;
+    // Case names are file names, so they are checked the same way as test names
+    pub fn check_case_names(&self) -> Result<(), String> {
+        for name in self.get_sorted_case_names() {
+            names::validate(name, "Case")?;
+            if let Some(conflict) = names::find_conflict(name, self.cases.keys()) {
+                return Err(format!(
+                    "Case \"{}\" and case \"{}\" only differ in capitalization, so they would be the same file on Windows and macOS",
+                    conflict, name
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // Warnings about cases that are probably broken, and files that weren't used as cases
+    pub fn validate(&self) -> Vec<String> {
+        let mut warnings = vec![];
+        for name in self.get_sorted_case_names() {
+            let case = &self.cases[name];
+            if case.input.size() == 0 {
+                warnings.push(format!("Case {} has an empty input", name));
+            }
+            match &case.output {
+                Some(output) if output.size() == 0 => warnings.push(format!("Case {} has an empty output", name)),
+                None => warnings.push(format!("Case {} has no expected output, so it won't be judged", name)),
+                _ => (),
+            }
+        }
+        for input in &self.unmatched.inputs {
+            warnings.push(format!("Input {:?} has no output, so it was skipped(--allow-missing-outputs adds it without one)", input));
+        }
+        for output in &self.unmatched.outputs {
+            warnings.push(format!("Output {:?} has no input, so it was skipped", output));
+        }
+        for (path, size) in &self.unmatched.oversized {
+            warnings.push(format!(
+                "{:?} is {}, which is over the case size limit, so its case was skipped(--no-size-limit adds it anyway)",
+                path,
+                format_file_size(*size as usize)
+            ));
+        }
+        let names = self.get_sorted_case_names();
+        let mut reported = vec![];
+        for (i, name) in names.iter().enumerate() {
+            if reported.contains(name) {
+                continue;
+            }
+            let case = &self.cases[*name];
+            let identical = names[i + 1..]
+                .iter()
+                .filter(|other| {
+                    let other_case = &self.cases[**other];
+                    other_case.same_data(case)
+                })
+                .copied()
+                .collect::<Vec<&String>>();
+            if !identical.is_empty() {
+                let others = identical.iter().map(|other| other.to_string()).collect::<Vec<String>>().join(", ");
+                warnings.push(format!("Case {} is identical to {}", name, others));
+                reported.extend(identical);
+            }
+        }
+        warnings
+    }
+
+    // Adds the cases of a newer version of the test, cases with the same name are only replaced with overwrite_cases
+    // The cases of both tests have to be loaded, returns the names of the cases that have to be written
+    pub fn merge(&mut self, new_test: Test, overwrite_cases: bool, replace_io: bool) -> (Vec<String>, MergeCounts) {
+        let mut changed = vec![];
+        let mut counts = MergeCounts::default();
+        for (name, case) in new_test.cases {
+            match self.cases.get(&name) {
+                None => counts.added += 1,
+                Some(existing) if existing.same_data(&case) => {
+                    counts.unchanged += 1;
+                    continue;
+                }
+                Some(_) if overwrite_cases => counts.updated += 1,
+                Some(_) => {
+                    counts.differing += 1;
+                    continue;
+                }
+            }
+            changed.push(name.clone());
+            self.cases.insert(name, case);
+        }
+        if new_test.submission_data.is_some() {
+            self.submission_data = new_test.submission_data;
+        }
+        if new_test.description.is_some() {
+            self.description = new_test.description;
         }
+        if replace_io {
+            self.input_io = new_test.input_io;
+            self.output_io = new_test.output_io;
+        }
+        self.add_tags(&new_test.tags);
+        self.time_limit_ms = new_test.time_limit_ms.or(self.time_limit_ms);
+        self.memory_limit_mb = new_test.memory_limit_mb.or(self.memory_limit_mb);
+        self.checker = new_test.checker.or(self.checker.take());
+        // Subtasks set with set-subtasks aren't replaced by inferred ones
+        if self.subtasks.is_empty() {
+            self.subtasks = new_test.subtasks;
+        }
+        (changed, counts)
+    }
+
+    // Bytes of input and output of the loaded cases
+    pub fn data_size(&self) -> u64 {
+        self.cases
+            .values()
+            .map(|case| case.input.size() + case.output_size() + case.alternates.iter().map(CaseData::size).sum::<u64>())
+            .sum()
+    }
+
+    // Called when a test is first saved, the cases have to be loaded
+    pub fn record_metadata(&mut self, source: Option<String>) {
+        self.metadata = TestMetadata {
+            added_at: Some(Local::now()),
+            source,
+            data_size_bytes: Some(self.data_size()),
+        };
+    }
 
+    // Every case has its own files, so they are written in parallel
+    pub fn write_data(&self, path: &Path, compress: bool) -> Result<(), Error> {
+        let cases = self.cases.iter().collect::<Vec<(&String, &TestCase)>>();
+        let results = parallel::map(&cases, |(name, test_case)| {
+            let (input_file, output_file) = self.case_file_names(name, compress);
+            let input_path = path.join(PathBuf::from(input_file));
+            let output_path = path.join(PathBuf::from(output_file));
+            if compress {
+                test_case.write_compressed(&input_path, &output_path, name)
+            } else {
+                test_case.write_data(&input_path, &output_path, name)
+            }
+        });
+        parallel::collect_errors(results)?;
         Ok(())
     }
 
-    pub fn set_cases(&mut self, cases: &Option<Vec<String>>, example: bool) -> Result<(), String> {
+    pub fn set_cases(&mut self, cases: &Option<Vec<String>>, example: bool) -> Result<(), Error> {
         if let Some(cases) = cases {
             let mut new_cases = HashMap::new();
             for case in cases {
                 if let Some(test_case) = self.cases.get(case) {
                     new_cases.insert(case.clone(), test_case.clone());
                 } else {
-                    return Err(format!("Test case with name \"{}\" does not exist", case));
+                    return Err(self.case_not_found(case).into());
                 }
             }
             self.cases = new_cases;
@@ -165,8 +669,11 @@ impl Test {
                 }
             }
             if new_cases.is_empty() {
-                return Err("No example test cases found(Test cases with \"example\" in their name with any capitalization)".to_string());
+                return Err(Error::OTHER(
+                    "No example test cases found(Test cases with \"example\" in their name with any capitalization)".to_string(),
+                ));
             }
+            self.cases = new_cases;
         }
         Ok(())
     }
@@ -192,39 +699,358 @@ impl Test {
 }
 
 impl TestCase {
-    pub fn new(input_data: Vec<u8>, output_data: Vec<u8>) -> Result<TestCase, String> {
-        let input = handle_error!(String::from_utf8(input_data), "Invalid input data for a test case");
-        let output = handle_error!(String::from_utf8(output_data), "Invalid output data for a test case");
-        Ok(TestCase { input, output })
+    pub fn new(input: Vec<u8>, output: Option<Vec<u8>>) -> TestCase {
+        TestCase {
+            input: CaseData::Bytes(input),
+            output: output.map(CaseData::Bytes),
+            alternates: vec![],
+        }
+    }
+
+    pub fn from_files(input_path: PathBuf, output_path: Option<PathBuf>, alternate_paths: Vec<PathBuf>) -> Result<TestCase, String> {
+        Ok(TestCase {
+            input: CaseData::from_file(input_path)?,
+            output: output_path.map(CaseData::from_file).transpose()?,
+            alternates: alternate_paths.into_iter().map(CaseData::from_file).collect::<Result<Vec<CaseData>, String>>()?,
+        })
     }
 
     pub fn write_data(&self, input_path: &PathBuf, output_path: &PathBuf, name: &String) -> Result<(), String> {
         self.write_input(input_path, name)?;
         self.write_output(output_path, name)?;
-        Ok(())
+        self.write_alternates(output_path, false, name)
     }
     pub fn write_input(&self, input_path: &PathBuf, name: &String) -> Result<(), String> {
         handle_error!(
-            fs::write(input_path, &self.input),
+            self.input.write_to(input_path),
             format!("Failed to write test case input to file({:?}) for test case \"{}\"", input_path, name)
         );
         Ok(())
     }
+    // An old output file is removed when there is no output, so it isn't paired with the input when the cases are read again
     pub fn write_output(&self, output_path: &PathBuf, name: &String) -> Result<(), String> {
+        let Some(output) = &self.output else {
+            return remove_stale_output(output_path, name);
+        };
         handle_error!(
-            fs::write(output_path, &self.output),
+            output.write_to(output_path),
             format!("Failed to write test case output to file({:?}) for test case \"{}\"", output_path, name)
         );
         Ok(())
     }
-    pub fn get_input(&self) -> &String {
-        &self.input
+    pub fn write_compressed(&self, input_path: &Path, output_path: &Path, name: &String) -> Result<(), String> {
+        handle_error!(
+            self.input.write_compressed_to(input_path),
+            format!("Failed to write compressed test case input to file({:?}) for test case \"{}\"", input_path, name)
+        );
+        match &self.output {
+            Some(output) => handle_error!(
+                output.write_compressed_to(output_path),
+                format!("Failed to write compressed test case output to file({:?}) for test case \"{}\"", output_path, name)
+            ),
+            None => remove_stale_output(output_path, name)?,
+        }
+        self.write_alternates(output_path, true, name)
+    }
+    // Numbered from 1 in order, so writing a case onto its own files only moves alternates to lower numbers
+    // Alternates left over from an older version of the case are removed
+    pub fn write_alternates(&self, output_path: &Path, compress: bool, name: &String) -> Result<(), String> {
+        for (i, alternate) in self.alternates.iter().enumerate() {
+            let alternate_path = alternate_path(output_path, i + 1);
+            let result = if compress { alternate.write_compressed_to(&alternate_path) } else { alternate.write_to(&alternate_path) };
+            handle_error!(
+                result,
+                format!("Failed to write alternate output to file({:?}) for test case \"{}\"", alternate_path, name)
+            );
+        }
+        let mut index = self.alternates.len() + 1;
+        while alternate_path(output_path, index).is_file() {
+            remove_stale_output(&alternate_path(output_path, index), name)?;
+            index += 1;
+        }
+        Ok(())
+    }
+    // Alternates of the stored version of the case that this one doesn't have, like ones from add-alternate when the case is restored
+    // They are read into memory, since writing this case writes over their files
+    pub fn keep_alternates(&mut self, stored: &TestCase) -> Result<(), String> {
+        let output = self.get_output()?.map(|output| output.trim_ascii().to_vec());
+        for alternate in &stored.alternates {
+            let alternate = alternate.read()?.into_owned();
+            if output.as_deref() == Some(alternate.trim_ascii()) || self.matching_alternate(&alternate)?.is_some() {
+                continue;
+            }
+            self.alternates.push(CaseData::Bytes(alternate));
+        }
+        Ok(())
+    }
+    // None if the case has no expected output
+    pub fn get_output(&self) -> Result<Option<Cow<'_, [u8]>>, String> {
+        self.output.as_ref().map(|output| output.read()).transpose()
+    }
+    // Appended to the output file or size where cases are listed, like " (+2 alternates)"
+    pub fn alternates_note(&self) -> String {
+        match self.alternates.len() {
+            0 => String::new(),
+            1 => " (+1 alternate)".to_string(),
+            count => format!(" (+{} alternates)", count),
+        }
+    }
+    // Which alternate the output matches(Numbered from 1), compared like the expected output with ASCII whitespace at the ends trimmed
+    pub fn matching_alternate(&self, output: &[u8]) -> Result<Option<usize>, String> {
+        for (i, alternate) in self.alternates.iter().enumerate() {
+            if alternate.read()?.trim_ascii() == output.trim_ascii() {
+                return Ok(Some(i + 1));
+            }
+        }
+        Ok(None)
+    }
+    pub fn output_size(&self) -> u64 {
+        self.output.as_ref().map_or(0, |output| output.size())
+    }
+    pub fn display_input(&self) -> Result<String, String> {
+        Ok(display_bytes(&self.input.read()?))
+    }
+    // The first lines of the input, or all of it if max_lines is None
+    pub fn preview_input(&self, max_lines: Option<usize>) -> Result<String, String> {
+        match max_lines {
+            Some(max_lines) => Ok(preview_bytes(&self.input.read_start(max_lines * PREVIEW_LINE_CHARS)?, Some(max_lines), self.input.size())),
+            None => self.display_input(),
+        }
+    }
+    pub fn preview_output(&self, max_lines: Option<usize>) -> Result<String, String> {
+        match (&self.output, max_lines) {
+            (Some(output), Some(max_lines)) => Ok(preview_bytes(&output.read_start(max_lines * PREVIEW_LINE_CHARS)?, Some(max_lines), output.size())),
+            _ => self.display_output(),
+        }
+    }
+    pub fn display_output(&self) -> Result<String, String> {
+        match self.get_output()? {
+            Some(output) => Ok(display_bytes(&output)),
+            None => Ok("(none)".to_string()),
+        }
+    }
+    // Sizes are compared first, so most different cases aren't read
+    pub fn same_data(&self, other: &TestCase) -> bool {
+        let same_output = match (&self.output, &other.output) {
+            (Some(output), Some(other_output)) => output.same_data(other_output),
+            (None, None) => true,
+            _ => false,
+        };
+        let same_alternates = self.alternates.len() == other.alternates.len()
+            && self.alternates.iter().zip(&other.alternates).all(|(alternate, other_alternate)| alternate.same_data(other_alternate));
+        self.input.same_data(&other.input) && same_output && same_alternates
+    }
+}
+
+impl CaseData {
+    fn from_file(path: PathBuf) -> Result<CaseData, String> {
+        if path.extension().is_some_and(|extension| extension == COMPRESSED_EXTENSION) {
+            let size = handle_error!(compressed_size(&path), format!("Failed to read compressed test case file({:?})", path));
+            return Ok(CaseData::Compressed(path, size));
+        }
+        let metadata = handle_error!(fs::metadata(&path), format!("Failed to read test case file({:?})", path));
+        Ok(CaseData::File(path, metadata.len()))
+    }
+
+    // The file a stored case is read from
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            CaseData::Bytes(_) => None,
+            CaseData::File(path, _) | CaseData::Compressed(path, _) => Some(path),
+        }
+    }
+
+    pub fn size(&self) -> u64 {
+        match self {
+            CaseData::Bytes(bytes) => bytes.len() as u64,
+            CaseData::File(_, size) | CaseData::Compressed(_, size) => *size,
+        }
+    }
+
+    pub fn read(&self) -> Result<Cow<'_, [u8]>, String> {
+        match self {
+            CaseData::Bytes(bytes) => Ok(Cow::Borrowed(bytes)),
+            CaseData::File(path, _) => Ok(Cow::Owned(handle_error!(fs::read(path), format!("Failed to read test case file({:?})", path)))),
+            CaseData::Compressed(path, _) => {
+                let file = handle_error!(File::open(path), format!("Failed to read test case file({:?})", path));
+                Ok(Cow::Owned(handle_error!(
+                    zstd::stream::decode_all(file),
+                    format!("Failed to decompress test case file({:?})", path)
+                )))
+            }
+        }
+    }
+
+    // Only the start of the file is read(Or decompressed), for previews of big cases
+    fn read_start(&self, max_bytes: usize) -> Result<Cow<'_, [u8]>, String> {
+        let mut start = vec![];
+        match self {
+            CaseData::Bytes(bytes) => return Ok(Cow::Borrowed(&bytes[..max_bytes.min(bytes.len())])),
+            CaseData::File(path, _) => {
+                let file = handle_error!(File::open(path), format!("Failed to read test case file({:?})", path));
+                handle_error!(file.take(max_bytes as u64).read_to_end(&mut start), format!("Failed to read test case file({:?})", path));
+            }
+            CaseData::Compressed(path, _) => {
+                let file = handle_error!(File::open(path), format!("Failed to read test case file({:?})", path));
+                let decoder = handle_error!(zstd::stream::Decoder::new(file), format!("Failed to decompress test case file({:?})", path));
+                handle_error!(decoder.take(max_bytes as u64).read_to_end(&mut start), format!("Failed to decompress test case file({:?})", path));
+            }
+        }
+        Ok(Cow::Owned(start))
+    }
+
+    // Stored cases are copied(Or decompressed while copying), so their data never has to be in memory
+    fn write_to(&self, path: &Path) -> io::Result<()> {
+        match self {
+            CaseData::Bytes(bytes) => fs::write(path, bytes),
+            CaseData::File(source, _) if source == path => Ok(()),
+            CaseData::File(source, _) => fs::copy(source, path).map(|_| ()),
+            CaseData::Compressed(source, _) => zstd::stream::copy_decode(File::open(source)?, File::create(path)?),
+        }
+    }
+
+    // The uncompressed size is put in the frame header, so it can be known without decompressing
+    fn write_compressed_to(&self, path: &Path) -> io::Result<()> {
+        let mut source: Box<dyn Read> = match self {
+            CaseData::Compressed(source, _) if source == path => return Ok(()),
+            CaseData::Compressed(source, _) => return fs::copy(source, path).map(|_| ()),
+            CaseData::Bytes(bytes) => Box::new(bytes.as_slice()),
+            CaseData::File(source, _) => Box::new(File::open(source)?),
+        };
+        let mut encoder = zstd::stream::Encoder::new(File::create(path)?, COMPRESSION_LEVEL)?;
+        encoder.set_pledged_src_size(Some(self.size()))?;
+        io::copy(&mut source, &mut encoder)?;
+        encoder.finish()?.sync_all()
+    }
+
+    fn same_data(&self, other: &CaseData) -> bool {
+        if self.size() != other.size() {
+            return false;
+        }
+        match (self.read(), other.read()) {
+            (Ok(data), Ok(other_data)) => data == other_data,
+            _ => false,
+        }
+    }
+}
+
+// Digit runs are compared as numbers and other runs without case, named cases(Like examples) come before numbered ones
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (a_runs, b_runs) = (name_runs(a), name_runs(b));
+    for (a_run, b_run) in a_runs.iter().zip(&b_runs) {
+        let a_is_number = a_run.starts_with(|c: char| c.is_ascii_digit());
+        let b_is_number = b_run.starts_with(|c: char| c.is_ascii_digit());
+        let ordering = match (a_is_number, b_is_number) {
+            (true, true) => {
+                let (a_digits, b_digits) = (a_run.trim_start_matches('0'), b_run.trim_start_matches('0'));
+                a_digits.len().cmp(&b_digits.len()).then_with(|| a_digits.cmp(b_digits))
+            }
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => a_run.to_lowercase().cmp(&b_run.to_lowercase()),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    // Names that only differ by leading zeros or capitalization still get a fixed order
+    a_runs.len().cmp(&b_runs.len()).then_with(|| a.cmp(b))
+}
+
+// secret_10a becomes secret_, 10, a
+fn name_runs(name: &str) -> Vec<&str> {
+    let mut runs = vec![];
+    let mut start = 0;
+    let mut last_was_digit = None;
+    for (i, c) in name.char_indices() {
+        let is_digit = c.is_ascii_digit();
+        if last_was_digit.is_some_and(|last_was_digit| last_was_digit != is_digit) {
+            runs.push(&name[start..i]);
+            start = i;
+        }
+        last_was_digit = Some(is_digit);
+    }
+    if start < name.len() {
+        runs.push(&name[start..]);
     }
-    pub fn get_output(&self) -> &String {
-        &self.output
+    runs
+}
+
+// Files written by other tools might not have the size in the header, those are decompressed to count it
+fn compressed_size(path: &Path) -> io::Result<u64> {
+    let mut header = Vec::with_capacity(MAX_FRAME_HEADER_SIZE);
+    File::open(path)?.take(MAX_FRAME_HEADER_SIZE as u64).read_to_end(&mut header)?;
+    match zstd_safe::get_frame_content_size(&header) {
+        CONTENTSIZE_ERROR => Err(io::Error::new(io::ErrorKind::InvalidData, "Not a zstd file")),
+        CONTENTSIZE_UNKNOWN => io::copy(&mut zstd::stream::Decoder::new(File::open(path)?)?, &mut io::sink()),
+        size => Ok(size),
+    }
+}
+
+// 1.out.alt1 for 1.out, and 1.out.alt1.zst for 1.out.zst
+pub fn alternate_path(output_path: &Path, index: usize) -> PathBuf {
+    let file_name = output_path.file_name().unwrap_or_default().to_string_lossy();
+    let file_name = match file_name.strip_suffix(&format!(".{}", COMPRESSED_EXTENSION)) {
+        Some(stem) => format!("{}.{}{}.{}", stem, ALTERNATE_SUFFIX, index, COMPRESSED_EXTENSION),
+        None => format!("{}.{}{}", file_name, ALTERNATE_SUFFIX, index),
+    };
+    output_path.with_file_name(file_name)
+}
+
+fn remove_stale_output(output_path: &Path, name: &String) -> Result<(), String> {
+    if output_path.is_file() {
+        handle_error!(
+            fs::remove_file(output_path),
+            format!("Failed to remove old test case output({:?}) for test case \"{}\"", output_path, name)
+        );
+    }
+    Ok(())
+}
+
+// Case data and program output for showing, anything that isn't UTF-8 is converted lossily with a note
+pub fn display_bytes(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => format!("{}\n(non-UTF8, shown lossily)", String::from_utf8_lossy(bytes)),
     }
 }
 
+// Cut after max_lines whole lines, with a note of how much was left out of total_size bytes, None shows everything
+pub fn preview_bytes(bytes: &[u8], max_lines: Option<usize>, total_size: u64) -> String {
+    let Some(max_lines) = max_lines else {
+        return display_bytes(bytes);
+    };
+    let bytes = &bytes[..bytes.len().min(max_lines * PREVIEW_LINE_CHARS)];
+    let mut end = 0;
+    for (i, line) in bytes.split_inclusive(|byte| *byte == b'\n').enumerate() {
+        if i == max_lines {
+            break;
+        }
+        if !line.ends_with(b"\n") && (end + line.len()) as u64 != total_size {
+            // A line that is too long by itself is cut, at a character boundary
+            if i == 0 {
+                end = line.len();
+                if let Err(error) = std::str::from_utf8(line) {
+                    if error.error_len().is_none() {
+                        end = error.valid_up_to();
+                    }
+                }
+            }
+            break;
+        }
+        end += line.len();
+    }
+    let preview = display_bytes(&bytes[..end]);
+    if end as u64 >= total_size {
+        return preview;
+    }
+    format!(
+        "{}\n... ({} more)",
+        preview.trim_end_matches('\n'),
+        format_file_size((total_size - end as u64) as usize)
+    )
+}
+
 impl From<EmptyTest> for Test {
     fn from(empty_test: EmptyTest) -> Self {
         Test {
@@ -235,6 +1061,14 @@ impl From<EmptyTest> for Test {
             output_io: empty_test.output_io,
             submission_data: empty_test.submission_data,
             description: empty_test.description,
+            tags: empty_test.tags,
+            time_limit_ms: empty_test.time_limit_ms,
+            memory_limit_mb: empty_test.memory_limit_mb,
+            checker: empty_test.checker,
+            config_tag: empty_test.config_tag,
+            subtasks: empty_test.subtasks,
+            metadata: empty_test.metadata,
+            unmatched: UnmatchedFiles::default(),
         }
     }
 }
@@ -248,6 +1082,13 @@ impl From<&Test> for EmptyTest {
             output_io: test.output_io.clone(),
             submission_data: test.submission_data.clone(),
             description: test.description.clone(),
+            tags: test.tags.clone(),
+            time_limit_ms: test.time_limit_ms,
+            memory_limit_mb: test.memory_limit_mb,
+            checker: test.checker.clone(),
+            config_tag: test.config_tag.clone(),
+            subtasks: test.subtasks.clone(),
+            metadata: test.metadata.clone(),
         }
     }
 }
@@ -260,3 +1101,184 @@ impl IOType {
         }
     }
 }
+
+// Inputs and outputs in the scanned folders that aren't part of any case
+fn find_unmatched(
+    folder: &Path,
+    dirs: &[PathBuf],
+    naming: &CaseNaming,
+    test_case_files: &[(PathBuf, String, PathBuf, Option<PathBuf>)],
+    alternates: &HashMap<PathBuf, Vec<PathBuf>>,
+) -> Result<UnmatchedFiles, String> {
+    let mut unmatched = UnmatchedFiles::default();
+    let alternate_files = alternates.values().flatten().collect::<HashSet<&PathBuf>>();
+    for dir in dirs {
+        for file in case_naming::read_files(dir)? {
+            if alternate_files.contains(&file) || test_case_files.iter().any(|(_, _, input, output)| *input == file || output.as_ref() == Some(&file)) {
+                continue;
+            }
+            let file_name = file.file_name().unwrap_or_default().to_string_lossy().to_string();
+            let relative_path = file.strip_prefix(folder).unwrap_or(&file).to_path_buf();
+            if naming.case_name(&file_name).is_some() || is_dir_named(dir, &INPUT_DIR_NAMES) {
+                unmatched.inputs.push(relative_path);
+            } else if naming.is_output_file(&file_name) || is_dir_named(dir, &OUTPUT_DIR_NAMES) {
+                unmatched.outputs.push(relative_path);
+            }
+        }
+    }
+    Ok(unmatched)
+}
+
+// Files next to an output named like it with something after it(Like 3.out.alt1 or 3.out.b for 3.out) are other accepted outputs of its case
+// Keyed by the output path, in natural order of their names
+fn find_alternates(
+    dirs: &[PathBuf],
+    test_case_files: &[(PathBuf, String, PathBuf, Option<PathBuf>)],
+) -> Result<HashMap<PathBuf, Vec<PathBuf>>, String> {
+    let inputs = test_case_files.iter().map(|(_, _, input, _)| input).collect::<HashSet<&PathBuf>>();
+    let outputs = test_case_files.iter().filter_map(|(_, _, _, output)| output.as_ref()).collect::<HashSet<&PathBuf>>();
+    let compressed_suffix = format!(".{}", COMPRESSED_EXTENSION);
+    let mut alternates: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+    for dir in dirs {
+        for file in case_naming::read_files(dir)? {
+            if inputs.contains(&file) || outputs.contains(&file) {
+                continue;
+            }
+            let Some(file_name) = file.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            // Compressed alternates go with compressed outputs, 1.out.alt1.zst is an alternate of 1.out.zst
+            let (stem, suffix) = match file_name.strip_suffix(&compressed_suffix) {
+                Some(stem) => (stem, compressed_suffix.as_str()),
+                None => (file_name, ""),
+            };
+            // The longest output name it starts with, in case the name of one output starts with another
+            let output = stem
+                .rmatch_indices('.')
+                .map(|(i, _)| dir.join(format!("{}{}", &stem[..i], suffix)))
+                .find(|output| outputs.contains(output));
+            if let Some(output) = output {
+                alternates.entry(output).or_default().push(file);
+            }
+        }
+    }
+    for files in alternates.values_mut() {
+        files.sort_by(|a, b| natural_cmp(&a.to_string_lossy(), &b.to_string_lossy()));
+    }
+    Ok(alternates)
+}
+
+fn is_dir_named(dir: &Path, names: &[&str]) -> bool {
+    dir.file_name()
+        .is_some_and(|name| names.contains(&name.to_string_lossy().to_lowercase().as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Writes the files, then finds the cases like add does
+    fn scan(files: &[(&str, &str)]) -> Test {
+        let dir = tempfile::tempdir().unwrap();
+        for (path, contents) in files {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        let naming = CaseNaming::EXTENSIONS("in".to_string(), "out".to_string());
+        Test::from_folder(dir.path().to_path_buf(), &naming, IOType::STD, IOType::STD, None, None, ScanOptions::default()).unwrap()
+    }
+
+    fn output(test: &Test, case_name: &str) -> Vec<u8> {
+        test.cases[case_name].output.as_ref().unwrap().read().unwrap().to_vec()
+    }
+
+    #[test]
+    fn cases_in_a_top_level_folder() {
+        let test = scan(&[("problem/1.in", "1\n"), ("problem/1.out", "2\n"), ("problem/2.in", "3\n"), ("problem/2.out", "4\n")]);
+        assert_eq!(test.get_sorted_case_names(), ["1", "2"]);
+        assert_eq!(output(&test, "2"), b"4\n");
+    }
+
+    #[test]
+    fn cases_in_sibling_in_and_out_folders() {
+        let test = scan(&[
+            ("testdata/in/1.txt", "1\n"),
+            ("testdata/out/1.txt", "2\n"),
+            ("testdata/input/2.in", "3\n"),
+            ("testdata/output/2.out", "4\n"),
+        ]);
+        assert_eq!(test.get_sorted_case_names(), ["1", "2"]);
+        assert_eq!(output(&test, "1"), b"2\n");
+        assert_eq!(output(&test, "2"), b"4\n");
+    }
+
+    #[test]
+    fn colliding_stems_get_the_folder_as_a_prefix() {
+        let test = scan(&[("sample/1.in", "1\n"), ("sample/1.out", "2\n"), ("secret/1.in", "3\n"), ("secret/1.out", "4\n")]);
+        assert_eq!(test.get_sorted_case_names(), ["sample_1", "secret_1"]);
+        assert_eq!(output(&test, "secret_1"), b"4\n");
+        // The same case in two folders is only added once
+        let test = scan(&[("a/1.in", "1\n"), ("a/1.out", "2\n"), ("b/1.in", "1\n"), ("b/1.out", "2\n")]);
+        assert_eq!(test.cases.len(), 1);
+    }
+
+    #[test]
+    fn hidden_folders_are_skipped() {
+        let test = scan(&[
+            ("1.in", "1\n"),
+            ("1.out", "2\n"),
+            (".git/2.in", "3\n"),
+            (".git/2.out", "4\n"),
+            ("__MACOSX/3.in", "5\n"),
+            ("__MACOSX/3.out", "6\n"),
+        ]);
+        assert_eq!(test.get_sorted_case_names(), ["1"]);
+    }
+
+    fn sorted(case_names: &[&str]) -> Vec<String> {
+        let mut test = Test::adhoc(TestCase::new(vec![], None));
+        test.cases = case_names.iter().map(|name| (name.to_string(), TestCase::new(vec![], None))).collect();
+        test.get_sorted_case_names().into_iter().cloned().collect()
+    }
+
+    #[test]
+    fn case_names_sort_naturally() {
+        assert_eq!(sorted(&["10", "2", "1", "example", "02"]), ["example", "1", "02", "2", "10"]);
+        assert_eq!(sorted(&["case10", "Case2", "case1", "case2a", "case2"]), ["case1", "Case2", "case2", "case2a", "case10"]);
+        assert_eq!(name_runs("secret_10a"), ["secret_", "10", "a"]);
+    }
+
+    #[test]
+    fn samples_come_before_secret_cases() {
+        assert_eq!(
+            sorted(&["secret_10", "secret_2", "Sample_2", "sample_1", "extra"]),
+            ["sample_1", "Sample_2", "extra", "secret_2", "secret_10"]
+        );
+        // Without secret cases the samples are only sorted by name
+        assert_eq!(sorted(&["sample_2", "1", "sample_1"]), ["sample_1", "sample_2", "1"]);
+    }
+
+    #[test]
+    fn missing_tests_and_cases_suggest_close_names() {
+        let test = scan(&[("example1.in", "1\n"), ("example1.out", "2\n"), ("secret_7.in", "3\n"), ("secret_7.out", "4\n")]);
+        assert_eq!(test.case_not_found("exampel1"), "Test case with name \"exampel1\" does not exist. Did you mean: example1?");
+        assert_eq!(test.case_not_found("zzz"), "Test case with name \"zzz\" does not exist");
+        let tests = HashMap::from([("fence_painting_bronze_jan21".to_string(), test)]);
+        let error = Test::not_found("fence_paiting_bronze_jan21", &tests);
+        assert!(matches!(&error, Error::TEST_NOT_FOUND(_)));
+        let message = "Test with name \"fence_paiting_bronze_jan21\" doesn't exist. Did you mean: fence_painting_bronze_jan21?";
+        assert_eq!(error.to_string(), message);
+    }
+
+    #[test]
+    fn keep_alternates_adds_only_new_outputs() {
+        let mut stored = TestCase::new(b"1 2\n".to_vec(), Some(b"3\n".to_vec()));
+        stored.alternates = vec![CaseData::Bytes(b"3.0\n".to_vec()), CaseData::Bytes(b"3\n".to_vec()), CaseData::Bytes(b"03\n".to_vec())];
+        let mut restored = TestCase::new(b"1 2\n".to_vec(), Some(b"3\n".to_vec()));
+        restored.alternates = vec![CaseData::Bytes(b"03".to_vec())];
+        restored.keep_alternates(&stored).unwrap();
+        let alternates = restored.alternates.iter().map(|alternate| alternate.read().unwrap().to_vec()).collect::<Vec<Vec<u8>>>();
+        assert_eq!(alternates, [b"03".to_vec(), b"3.0\n".to_vec()]);
+    }
+}
@@ -0,0 +1,87 @@
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::Path,
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use toml_edit::{DocumentMut, Item, Table};
+
+use crate::{
+    error::{Context, Error},
+    json_file::with_suffix,
+};
+
+// config.toml is read the same way as config.json, so the rest of the config code doesn't depend on the format
+pub fn read<T: DeserializeOwned>(path: &Path) -> Result<T, Error> {
+    let contents = fs::read_to_string(path).context(format!("Failed to read {:?}", path))?;
+    parse(&contents).map_err(|message| Error::PARSE { context: format!("Failed to parse {:?}", path), message })
+}
+
+// Errors have the line and column, like serde_json
+pub fn parse<T: DeserializeOwned>(contents: &str) -> Result<T, String> {
+    toml_edit::de::from_str(contents).map_err(|error| error.to_string())
+}
+
+// Only values that changed are rewritten, so comments and the order of the file stay how they were written
+// Written to a temp file that replaces the old one, like json_file::write
+pub fn write<T: Serialize>(path: &Path, value: &T) -> Result<(), Error> {
+    let mut value = serde_json::to_value(value).context(format!("Failed to serialize {:?}", path))?;
+    // TOML has no null, a missing value is the same as null for the config
+    remove_nulls(&mut value);
+    let contents = toml_edit::ser::to_string_pretty(&value)
+        .map_err(|error| error.to_string())
+        .context(format!("Failed to serialize {:?}", path))?;
+    let new_document = contents
+        .parse::<DocumentMut>()
+        .map_err(|error| error.to_string())
+        .context(format!("Failed to serialize {:?}", path))?;
+    let mut document = fs::read_to_string(path)
+        .ok()
+        .and_then(|old| old.parse::<DocumentMut>().ok())
+        .unwrap_or_default();
+    update_table(document.as_table_mut(), new_document.as_table());
+    let temp_path = with_suffix(path, "tmp");
+    let mut temp_file = File::create(&temp_path).context(format!("Failed to create {:?}", temp_path))?;
+    temp_file.write_all(document.to_string().as_bytes()).context(format!("Failed to write {:?}", temp_path))?;
+    temp_file.sync_all().context(format!("Failed to write {:?}", temp_path))?;
+    fs::rename(&temp_path, path).context(format!("Failed to replace {:?}", path))
+}
+
+fn remove_nulls(value: &mut Value) {
+    if let Value::Object(map) = value {
+        map.retain(|_, value| !value.is_null());
+        map.values_mut().for_each(remove_nulls);
+    }
+}
+
+// Keys in old that aren't in new are removed, and tables written inline in old stay inline
+fn update_table(old: &mut Table, new: &Table) {
+    old.retain(|key, _| new.contains_key(key));
+    for (key, new_item) in new.iter() {
+        let Some(old_item) = old.get_mut(key) else {
+            old.insert(key, new_item.clone());
+            continue;
+        };
+        match (old_item, new_item) {
+            (Item::Table(old_table), Item::Table(new_table)) => update_table(old_table, new_table),
+            (Item::Value(old_value), new_item) => {
+                let mut new_value = match new_item {
+                    Item::Table(new_table) => toml_edit::Value::InlineTable(new_table.clone().into_inline_table()),
+                    Item::Value(new_value) => new_value.clone(),
+                    _ => continue,
+                };
+                // The comment after a value is part of its decor
+                let decor = old_value.decor().clone();
+                old_value.decor_mut().clear();
+                new_value.decor_mut().clear();
+                if old_value.to_string() != new_value.to_string() {
+                    *old_value = new_value;
+                }
+                *old_value.decor_mut() = decor;
+            }
+            (old_item, new_item) => *old_item = new_item.clone(),
+        }
+    }
+}